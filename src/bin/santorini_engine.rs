@@ -0,0 +1,25 @@
+use std::io;
+use anyhow::{bail, Result};
+use santorini_minimax::engine_protocol::Engine;
+use santorini_minimax::game_state::game_state_4x4_binary_3bit::GameState4x4Binary3Bit;
+use santorini_minimax::game_state::game_state_5x5_binary_composite::GameState5x5BinaryComposite;
+use santorini_minimax::game_state::SantoriniEval;
+use santorini_minimax::strategy::heuristics::dynamic_heuristic::{dynamic_heuristic, DynamicHeuristicParams};
+
+// Usage: santorini_engine [5x5|4x4], speaks the protocol described in engine_protocol on stdin and stdout
+fn main() -> Result<()> {
+    let board = std::env::args().nth(1).unwrap_or("5x5".to_string());
+    match board.as_str() {
+        "5x5" => {
+            let params = DynamicHeuristicParams::default();
+            let mut engine = Engine::new(move |state: &GameState5x5BinaryComposite| dynamic_heuristic(state, &params), Box::new(io::stdout()));
+            engine.run(io::stdin().lock())?;
+        }
+        "4x4" => {
+            let mut engine = Engine::new(|state: &GameState4x4Binary3Bit| state.get_child_evaluation(), Box::new(io::stdout()));
+            engine.run(io::stdin().lock())?;
+        }
+        _ => bail!("Unknown board '{}', expected 5x5 or 4x4", board),
+    }
+    return Ok(());
+}
@@ -0,0 +1,53 @@
+use std::io;
+use anyhow::{bail, Result};
+use santorini_minimax::game_state::game_state_4x4_binary_3bit::GameState4x4Binary3Bit;
+use santorini_minimax::game_state::game_state_5x5_binary_composite::GameState5x5BinaryComposite;
+use santorini_minimax::game_state::{get_initial_setup_state, SantoriniEval};
+use santorini_minimax::play_game::terminal_game::TerminalGame;
+use santorini_minimax::strategy::dynamic_minimax_strategy::DynamicMinimaxStrategy;
+use santorini_minimax::strategy::heuristics::dynamic_heuristic::{dynamic_heuristic, DynamicHeuristicParams};
+use santorini_minimax::strategy::iterative_deepening_strategy::IterativeDeepeningStrategy;
+use santorini_minimax::strategy::random_strategy::RandomStrategy;
+
+/*
+Usage: santorini_play [5x5|4x4] [human|random|minimax] [a|b]
+The second argument is the opponent, the third the player it plays (player B by default).
+ */
+fn main() -> Result<()> {
+    let board = std::env::args().nth(1).unwrap_or("5x5".to_string());
+    let opponent = std::env::args().nth(2).unwrap_or("minimax".to_string());
+    let opponent_plays_player_a = match std::env::args().nth(3).as_deref() {
+        None | Some("b") => false,
+        Some("a") => true,
+        Some(player) => bail!("Unknown player '{}', expected a or b", player),
+    };
+    let seed = rand::random::<u64>();
+
+    match board.as_str() {
+        "5x5" => {
+            type GS = GameState5x5BinaryComposite;
+            let params = DynamicHeuristicParams::default();
+            let game = TerminalGame::<GS>::new(get_initial_setup_state(), move |state| dynamic_heuristic(state, &params)).with_seed(seed);
+            let mut game = match opponent.as_str() {
+                "human" => game,
+                "random" => game.with_opponent("Random", RandomStrategy::<GS>::new(), opponent_plays_player_a),
+                "minimax" => game.with_opponent("Minimax depth 4", DynamicMinimaxStrategy::<GS>::new(4, params), opponent_plays_player_a),
+                _ => bail!("Unknown opponent '{}', expected human, random or minimax", opponent),
+            };
+            game.run(io::stdin().lock(), io::stdout())?;
+        }
+        "4x4" => {
+            type GS = GameState4x4Binary3Bit;
+            let game = TerminalGame::<GS>::new(get_initial_setup_state(), |state| state.get_child_evaluation()).with_seed(seed);
+            let mut game = match opponent.as_str() {
+                "human" => game,
+                "random" => game.with_opponent("Random", RandomStrategy::<GS>::new(), opponent_plays_player_a),
+                "minimax" => game.with_opponent("Minimax depth 8", IterativeDeepeningStrategy::<GS>::new(8, |state| state.get_child_evaluation()), opponent_plays_player_a),
+                _ => bail!("Unknown opponent '{}', expected human, random or minimax", opponent),
+            };
+            game.run(io::stdin().lock(), io::stdout())?;
+        }
+        _ => bail!("Unknown board '{}', expected 5x5 or 4x4", board),
+    }
+    return Ok(());
+}
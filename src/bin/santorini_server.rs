@@ -0,0 +1,46 @@
+use std::net::{Ipv4Addr, SocketAddr};
+use anyhow::{bail, Result};
+use santorini_minimax::game_state::game_state_4x4_binary_3bit::GameState4x4Binary3Bit;
+use santorini_minimax::game_state::game_state_5x5_binary_composite::GameState5x5BinaryComposite;
+use santorini_minimax::game_state::SantoriniEval;
+use santorini_minimax::play_server::PlayServer;
+use santorini_minimax::strategy::dynamic_minimax_strategy::DynamicMinimaxStrategy;
+use santorini_minimax::strategy::heuristics::dynamic_heuristic::{dynamic_heuristic, DynamicHeuristicParams};
+use santorini_minimax::strategy::iterative_deepening_strategy::IterativeDeepeningStrategy;
+use santorini_minimax::strategy::random_strategy::RandomStrategy;
+
+const DEFAULT_PORT: u16 = 8080;
+
+// Usage: santorini_server [5x5|4x4] [port], the server only listens on localhost
+#[tokio::main]
+async fn main() -> Result<()> {
+    let board = std::env::args().nth(1).unwrap_or("5x5".to_string());
+    let port = match std::env::args().nth(2) {
+        Some(port) => port.parse::<u16>()?,
+        None => DEFAULT_PORT,
+    };
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    println!("Serving {} games on http://{}", board, address);
+
+    match board.as_str() {
+        "5x5" => {
+            type GS = GameState5x5BinaryComposite;
+            let params = DynamicHeuristicParams::default();
+            PlayServer::<GS>::new(move |state| dynamic_heuristic(state, &params))
+                .with_bot("random", RandomStrategy::<GS>::new)
+                .with_bot("minimax_depth_3", || DynamicMinimaxStrategy::<GS>::new(3, DynamicHeuristicParams::default()))
+                .with_bot("minimax_depth_4", || DynamicMinimaxStrategy::<GS>::new(4, DynamicHeuristicParams::default()))
+                .with_bot("iterative_deepening", || IterativeDeepeningStrategy::<GS>::new(8, |state| state.get_child_evaluation()))
+                .serve(address).await?;
+        }
+        "4x4" => {
+            type GS = GameState4x4Binary3Bit;
+            PlayServer::<GS>::new(|state| state.get_child_evaluation())
+                .with_bot("random", RandomStrategy::<GS>::new)
+                .with_bot("iterative_deepening", || IterativeDeepeningStrategy::<GS>::new(10, |state| state.get_child_evaluation()))
+                .serve(address).await?;
+        }
+        _ => bail!("Unknown board '{}', expected 5x5 or 4x4", board),
+    }
+    return Ok(());
+}
@@ -0,0 +1,507 @@
+use std::fmt;
+use std::io::{BufRead, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use anyhow::{anyhow, bail, ensure, Result};
+use crate::game_state::{get_initial_setup_state, GameState, SantoriniEval};
+use crate::game_state::perft::{perft, perft_divide};
+use crate::generic_game_state::move_notation::{apply_moves, get_move_notation};
+use crate::generic_game_state::GenericGameState;
+use crate::minimax::analysis::get_principal_variation;
+use crate::minimax::minimax_cache::MinimaxCache;
+use crate::minimax::minimax_custom_heuristic_with_stop;
+use crate::minimax::score::{get_moves_to_win, is_decisive, is_player_a_win, step_back};
+
+/*
+Line based protocol between a client (GUI, arena or ExternalEngineStrategy) and an engine, similar to UCI.
+Moves use the notation of generic_game_state::move_notation, positions use position strings (see GenericGameState).
+
+Client to engine:
+- stp: Handshake, the engine answers with "id" lines followed by "stpok"
+- isready: The engine answers with "readyok"
+- newgame: Resets the position to the empty board
+- position (startpos | <position string>) [moves <move> ...]: Sets the position and applies the moves
+- moves <move> ...: Applies the moves to the current position
+- go [depth <depth>] [movetime <milliseconds>] [infinite]: Starts a search, without limits the default depth is searched
+- stop: Stops the search, the engine answers with the best move found so far
+- perft <depth> [divide]: Counts the states at the depth from the current position (see game_state::perft), divide also counts per move
+- quit
+
+Engine to client:
+- id name <name>, id board <rows>x<columns> workers <workers per player>
+- info depth <depth> score (<value> | win <moves> | loss <moves>) nodes <nodes> time <milliseconds> pv <move> ...
+  The score is from the perspective of the player to move, wins and losses are given in moves (plies)
+- bestmove (<move> | none)
+- perft <move> <nodes> for every move in divide mode, followed by perft nodes <nodes> time <milliseconds>
+- error <message>
+ */
+
+pub const ENGINE_NAME: &str = "santorini-minimax";
+// Used by "go" without a depth, move time or infinite
+pub const DEFAULT_SEARCH_DEPTH: usize = 5;
+const MAX_SEARCH_DEPTH: usize = 99;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SearchLimits {
+    pub depth: Option<usize>,
+    pub move_time: Option<Duration>,
+    pub infinite: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EngineCommand {
+    Handshake,
+    IsReady,
+    NewGame,
+    // None is the start position (empty board)
+    Position { position_string: Option<String>, moves: Vec<String> },
+    Moves(Vec<String>),
+    Go(SearchLimits),
+    Stop,
+    Perft { depth: usize, divide: bool },
+    Quit,
+}
+
+impl fmt::Display for EngineCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            EngineCommand::Handshake => write!(f, "stp"),
+            EngineCommand::IsReady => write!(f, "isready"),
+            EngineCommand::NewGame => write!(f, "newgame"),
+            EngineCommand::Position { position_string, moves } => {
+                write!(f, "position {}", position_string.as_deref().unwrap_or("startpos"))?;
+                if !moves.is_empty() {
+                    write!(f, " moves {}", moves.join(" "))?;
+                }
+                Ok(())
+            }
+            EngineCommand::Moves(moves) => write!(f, "moves {}", moves.join(" ")),
+            EngineCommand::Go(limits) => {
+                write!(f, "go")?;
+                if let Some(depth) = limits.depth {
+                    write!(f, " depth {}", depth)?;
+                }
+                if let Some(move_time) = limits.move_time {
+                    write!(f, " movetime {}", move_time.as_millis())?;
+                }
+                if limits.infinite {
+                    write!(f, " infinite")?;
+                }
+                Ok(())
+            }
+            EngineCommand::Stop => write!(f, "stop"),
+            EngineCommand::Perft { depth, divide } => write!(f, "perft {}{}", depth, if *divide { " divide" } else { "" }),
+            EngineCommand::Quit => write!(f, "quit"),
+        };
+    }
+}
+
+pub fn parse_engine_command(line: &str) -> Result<EngineCommand> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let Some((&command, arguments)) = tokens.split_first() else {
+        bail!("Empty command");
+    };
+
+    return match command {
+        "stp" => Ok(EngineCommand::Handshake),
+        "isready" => Ok(EngineCommand::IsReady),
+        "newgame" => Ok(EngineCommand::NewGame),
+        "position" => {
+            let (&position, rest) = arguments.split_first().ok_or_else(|| anyhow!("Missing position"))?;
+            let position_string = if position == "startpos" { None } else { Some(position.to_string()) };
+            let moves = match rest.split_first() {
+                None => Vec::new(),
+                Some((&"moves", moves)) => moves.iter().map(|notation| notation.to_string()).collect(),
+                Some((token, _)) => bail!("Expected 'moves' after the position, got '{}'", token),
+            };
+            Ok(EngineCommand::Position { position_string, moves })
+        }
+        "moves" => Ok(EngineCommand::Moves(arguments.iter().map(|notation| notation.to_string()).collect())),
+        "go" => {
+            let mut limits = SearchLimits::default();
+            let mut remaining_arguments = arguments.iter();
+            while let Some(&argument) = remaining_arguments.next() {
+                match argument {
+                    "depth" => {
+                        let depth = remaining_arguments.next().ok_or_else(|| anyhow!("Missing depth"))?.parse::<usize>()?;
+                        ensure!((1..=MAX_SEARCH_DEPTH).contains(&depth), "Depth must be between 1 and {}", MAX_SEARCH_DEPTH);
+                        limits.depth = Some(depth);
+                    }
+                    "movetime" => {
+                        let milliseconds = remaining_arguments.next().ok_or_else(|| anyhow!("Missing move time"))?.parse::<u64>()?;
+                        limits.move_time = Some(Duration::from_millis(milliseconds));
+                    }
+                    "infinite" => limits.infinite = true,
+                    _ => bail!("Unknown search limit '{}'", argument),
+                }
+            }
+            Ok(EngineCommand::Go(limits))
+        }
+        "stop" => Ok(EngineCommand::Stop),
+        "perft" => {
+            let depth = arguments.first().ok_or_else(|| anyhow!("Missing depth"))?.parse::<usize>()?;
+            let divide = match arguments.get(1..) {
+                Some([]) | None => false,
+                Some(["divide"]) => true,
+                Some(_) => bail!("Expected only 'divide' after the depth"),
+            };
+            Ok(EngineCommand::Perft { depth, divide })
+        }
+        "quit" => Ok(EngineCommand::Quit),
+        _ => Err(anyhow!("Unknown command '{}'", command)),
+    };
+}
+
+// The score of the info line is from the perspective of the player to move
+pub fn format_score(score: f32, player_a_to_move: bool) -> String {
+    if let Some(moves_to_win) = get_moves_to_win(score) {
+        let player_to_move_wins = is_player_a_win(score) == player_a_to_move;
+        return format!("{} {}", if player_to_move_wins { "win" } else { "loss" }, moves_to_win);
+    }
+    let score = if player_a_to_move { score } else { -score };
+    // Negating 0.0 results in -0.0, which would be printed as "-0"
+    return format!("{}", if score == 0.0 { 0.0 } else { score });
+}
+
+type EngineOutput = Arc<Mutex<Box<dyn Write + Send>>>;
+
+fn write_line(output: &EngineOutput, line: &str) {
+    let mut output = output.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    // The client might have closed the connection, there is nobody left to report the error to
+    let _ = writeln!(output, "{}", line);
+    let _ = output.flush();
+}
+
+/**
+Engine that answers the commands of the protocol, searches run in a separate thread, so they can be stopped.
+Searches use iterative deepening and check the stop flag at every state, the move time sets the stop flag when it is up.
+ */
+pub struct Engine<GS: GameState + SantoriniEval + 'static, F: Fn(&GS) -> f32 + Send + Sync + 'static> {
+    current_state: GS,
+    heuristic_function: Arc<F>,
+    output: EngineOutput,
+    stop: Arc<AtomicBool>,
+    search: Option<JoinHandle<()>>,
+}
+
+impl<GS: GameState + SantoriniEval + 'static, F: Fn(&GS) -> f32 + Send + Sync + 'static> Engine<GS, F> {
+    pub fn new(heuristic_function: F, output: Box<dyn Write + Send>) -> Engine<GS, F> {
+        return Engine {
+            current_state: get_initial_setup_state::<GS>(),
+            heuristic_function: Arc::new(heuristic_function),
+            output: Arc::new(Mutex::new(output)),
+            stop: Arc::new(AtomicBool::new(false)),
+            search: None,
+        };
+    }
+
+    // Handles commands until "quit" or the end of the input
+    pub fn run<R: BufRead>(&mut self, input: R) -> Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if !self.handle_line(&line) {
+                break;
+            }
+        }
+        self.stop_search();
+        return Ok(());
+    }
+
+    // Returns false if the engine should quit
+    pub fn handle_line(&mut self, line: &str) -> bool {
+        let result = parse_engine_command(line).and_then(|command| self.handle_command(command));
+        return match result {
+            Ok(keep_running) => keep_running,
+            Err(error) => {
+                write_line(&self.output, &format!("error {}", error));
+                true
+            }
+        };
+    }
+
+    fn handle_command(&mut self, command: EngineCommand) -> Result<bool> {
+        match command {
+            EngineCommand::Handshake => {
+                write_line(&self.output, &format!("id name {}", ENGINE_NAME));
+                write_line(&self.output, &format!("id board {}x{} workers {}", GS::GenericGameState::ROWS, GS::GenericGameState::COLUMNS, GS::GenericGameState::WORKERS_PER_PLAYER));
+                write_line(&self.output, "stpok");
+            }
+            EngineCommand::IsReady => write_line(&self.output, "readyok"),
+            EngineCommand::NewGame => {
+                self.stop_search();
+                self.current_state = get_initial_setup_state::<GS>();
+            }
+            EngineCommand::Position { position_string, moves } => {
+                // A running search is stopped first, it still reports its best move
+                self.stop_search();
+                let state = match position_string {
+                    Some(position_string) => GS::from_generic_game_state(&GS::GenericGameState::from_position_string(&position_string)?),
+                    None => get_initial_setup_state::<GS>(),
+                };
+                self.current_state = apply_moves(&state, &moves.iter().map(|notation| notation.as_str()).collect::<Vec<&str>>())?;
+            }
+            EngineCommand::Moves(moves) => {
+                self.stop_search();
+                self.current_state = apply_moves(&self.current_state, &moves.iter().map(|notation| notation.as_str()).collect::<Vec<&str>>())?;
+            }
+            EngineCommand::Go(limits) => {
+                self.stop_search();
+                self.stop.store(false, Ordering::SeqCst);
+                let state = self.current_state;
+                let heuristic_function = self.heuristic_function.clone();
+                let output = self.output.clone();
+                let stop = self.stop.clone();
+                self.search = Some(std::thread::spawn(move || {
+                    // The client waits for an answer to "go", so a panic is reported instead of ending the search silently
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| search(state, limits, heuristic_function.as_ref(), &stop, &output)));
+                    if result.is_err() {
+                        write_line(&output, "error The search failed");
+                    }
+                }));
+            }
+            EngineCommand::Stop => self.stop_search(),
+            EngineCommand::Perft { depth, divide } => {
+                // Perft is a debugging tool, so it runs on the command thread and can't be stopped
+                self.stop_search();
+                let start = Instant::now();
+                let nodes = if divide {
+                    let divide = perft_divide(&self.current_state, depth)?;
+                    for (notation, count) in &divide {
+                        write_line(&self.output, &format!("perft {} {}", notation, count));
+                    }
+                    divide.iter().map(|(_, count)| count).sum()
+                } else {
+                    perft(&self.current_state, depth)
+                };
+                write_line(&self.output, &format!("perft nodes {} time {}", nodes, start.elapsed().as_millis()));
+            }
+            EngineCommand::Quit => {
+                self.stop_search();
+                return Ok(false);
+            }
+        }
+        return Ok(true);
+    }
+
+    fn stop_search(&mut self) {
+        if let Some(search) = self.search.take() {
+            self.stop.store(true, Ordering::SeqCst);
+            let _ = search.join();
+        }
+    }
+}
+
+fn search<GS: GameState + SantoriniEval, F: Fn(&GS) -> f32>(state: GS, limits: SearchLimits, heuristic_function: &F, stop: &AtomicBool, output: &EngineOutput) {
+    let (search_done, search_done_receiver) = mpsc::channel::<()>();
+    std::thread::scope(|scope| {
+        if let Some(move_time) = limits.move_time {
+            scope.spawn(move || {
+                // The sender is dropped when the search finishes, which ends the wait early
+                if search_done_receiver.recv_timeout(move_time) == Err(RecvTimeoutError::Timeout) {
+                    stop.store(true, Ordering::SeqCst);
+                }
+            });
+        }
+        iterative_deepening_search(state, limits, heuristic_function, stop, output);
+        drop(search_done);
+    });
+}
+
+fn iterative_deepening_search<GS: GameState + SantoriniEval, F: Fn(&GS) -> f32>(state: GS, limits: SearchLimits, heuristic_function: &F, stop: &AtomicBool, output: &EngineOutput) {
+    let start = Instant::now();
+    let player_a_to_move = state.is_player_a_turn();
+    let mut children_states = state.get_children_states();
+    if children_states.is_empty() || state.has_player_a_won() || state.has_player_b_won() {
+        write_line(output, "bestmove none");
+        return;
+    }
+
+    let max_depth = match limits.depth {
+        Some(depth) => depth,
+        None if limits.infinite || limits.move_time.is_some() => MAX_SEARCH_DEPTH,
+        None => DEFAULT_SEARCH_DEPTH,
+    };
+
+    let mut cache = MinimaxCache::new();
+    let mut best_child = children_states[0];
+    let mut nodes = 0;
+
+    for depth in 1..=max_depth {
+        // The best move of the previous depth is searched first
+        if let Some(best_index) = children_states.iter().position(|child| *child == best_child) {
+            children_states.swap(0, best_index);
+        }
+
+        let mut depth_best: Option<(GS, f32)> = None;
+        let mut completed = true;
+        for child in &children_states {
+            let Some((child_score, evaluated_states)) = minimax_custom_heuristic_with_stop(child, depth - 1, &mut cache, heuristic_function, stop) else {
+                completed = false;
+                break;
+            };
+            nodes += evaluated_states;
+            let score = step_back(child_score);
+            let is_better = match depth_best {
+                None => true,
+                Some((_, best_score)) => if player_a_to_move { score > best_score } else { score < best_score },
+            };
+            if is_better {
+                depth_best = Some((*child, score));
+            }
+        }
+
+        // An incomplete depth is only used if no depth was completed before
+        let Some((depth_best_child, depth_best_score)) = depth_best else {
+            break;
+        };
+        if !completed && depth > 1 {
+            break;
+        }
+        best_child = depth_best_child;
+
+        let (principal_variation, evaluated_states) = get_principal_variation(best_child, depth - 1, &mut cache, heuristic_function);
+        nodes += evaluated_states;
+        let mut principal_variation_moves = Vec::with_capacity(principal_variation.len());
+        let mut previous_state = state;
+        for pv_state in principal_variation {
+            principal_variation_moves.push(get_move_notation(&previous_state, &pv_state).unwrap_or("?".to_string()));
+            previous_state = pv_state;
+        }
+        write_line(output, &format!(
+            "info depth {} score {} nodes {} time {} pv {}",
+            depth,
+            format_score(depth_best_score, player_a_to_move),
+            nodes,
+            start.elapsed().as_millis(),
+            principal_variation_moves.join(" ")
+        ));
+
+        // Deeper searches can't change a forced result, and the next depth would most likely exceed the move time
+        let is_half_time_up = limits.move_time.is_some_and(|move_time| start.elapsed() >= move_time / 2);
+        if !completed || (is_decisive(depth_best_score) && !limits.infinite) || is_half_time_up {
+            break;
+        }
+    }
+
+    let best_move = get_move_notation(&state, &best_child).unwrap_or("none".to_string());
+    write_line(output, &format!("bestmove {}", best_move));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    use crate::engine_protocol::{parse_engine_command, Engine};
+    use crate::game_state::{get_initial_setup_state, SantoriniEval};
+    use crate::game_state::game_state_5x5_binary_composite::GameState5x5BinaryComposite;
+    use crate::generic_game_state::move_notation::{apply_moves, find_move};
+
+    const PLACEMENT_MOVES: [&str; 2] = ["a1,b2", "c3,d4"];
+
+    // Output of the engine that can be read while the search thread writes to it
+    #[derive(Clone, Default)]
+    struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            return Ok(buf.len());
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            return Ok(());
+        }
+    }
+
+    impl SharedOutput {
+        fn get_lines(&self) -> Vec<String> {
+            return String::from_utf8(self.0.lock().unwrap().clone()).unwrap().lines().map(|line| line.to_string()).collect();
+        }
+
+        fn wait_for_line(&self, prefix: &str) -> String {
+            let start = Instant::now();
+            while start.elapsed() < Duration::from_secs(60) {
+                if let Some(line) = self.get_lines().into_iter().find(|line| line.starts_with(prefix)) {
+                    return line;
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            panic!("No line starting with '{}', output: {:?}", prefix, self.get_lines());
+        }
+
+        fn wait_for_best_move(&self) -> String {
+            return self.wait_for_line("bestmove ")["bestmove ".len()..].to_string();
+        }
+    }
+
+    fn new_engine(output: &SharedOutput) -> Engine<GameState5x5BinaryComposite, fn(&GameState5x5BinaryComposite) -> f32> {
+        return Engine::new(|state: &GameState5x5BinaryComposite| state.get_child_evaluation(), Box::new(output.clone()));
+    }
+
+    #[test]
+    fn test_commands_are_formatted_like_they_are_parsed() {
+        for line in ["stp", "position startpos moves a1,b2 c3,d4 b2-b3+b2", "position 0000000000000000/-/-/A", "go depth 3 movetime 500", "go infinite", "stop", "perft 2", "perft 3 divide"] {
+            assert_eq!(parse_engine_command(line).unwrap().to_string(), line);
+        }
+    }
+
+    #[test]
+    fn test_incomplete_commands_are_rejected() {
+        assert!(parse_engine_command("go depth").is_err());
+        assert!(parse_engine_command("go depth 0").is_err());
+        assert!(parse_engine_command("position startpos a1").is_err());
+        assert!(parse_engine_command("perft 3 moves").is_err());
+    }
+
+    #[test]
+    fn test_go_reports_every_depth_and_a_legal_best_move() {
+        let output = SharedOutput::default();
+        let mut engine = new_engine(&output);
+        assert!(engine.handle_line(&format!("position startpos moves {}", PLACEMENT_MOVES.join(" "))));
+        assert!(engine.handle_line("go depth 2"));
+        let best_move = output.wait_for_best_move();
+
+        let lines = output.get_lines();
+        assert!(lines[0].starts_with("info depth 1 score "), "{:?}", lines);
+        assert!(lines[1].starts_with("info depth 2 score "), "{:?}", lines);
+        let state = apply_moves(&get_initial_setup_state::<GameState5x5BinaryComposite>(), &PLACEMENT_MOVES).unwrap();
+        assert!(find_move(&state, &best_move).is_ok(), "{}", best_move);
+        assert!(!engine.handle_line("quit"));
+    }
+
+    #[test]
+    fn test_stop_interrupts_the_search_immediately() {
+        let output = SharedOutput::default();
+        let mut engine = new_engine(&output);
+        engine.handle_line(&format!("position startpos moves {}", PLACEMENT_MOVES.join(" ")));
+        engine.handle_line("go infinite");
+        // The searches of the single moves of the next depth take long enough that waiting for one to finish would be noticeable
+        output.wait_for_line("info depth 5 ");
+
+        let start = Instant::now();
+        engine.handle_line("stop");
+        assert!(start.elapsed() < Duration::from_millis(100), "Stopping took {:?}", start.elapsed());
+        let best_move = output.get_lines().last().unwrap().strip_prefix("bestmove ").unwrap().to_string();
+        let state = apply_moves(&get_initial_setup_state::<GameState5x5BinaryComposite>(), &PLACEMENT_MOVES).unwrap();
+        assert!(find_move(&state, &best_move).is_ok(), "{}", best_move);
+    }
+
+    #[test]
+    fn test_move_time_ends_the_search() {
+        let output = SharedOutput::default();
+        let mut engine = new_engine(&output);
+        engine.handle_line(&format!("position startpos moves {}", PLACEMENT_MOVES.join(" ")));
+        let start = Instant::now();
+        engine.handle_line("go movetime 200");
+        output.wait_for_best_move();
+        assert!(start.elapsed() < Duration::from_millis(700), "The search took {:?}", start.elapsed());
+    }
+}
@@ -1,6 +1,7 @@
 use std::fmt::{Debug, Display};
 use crate::generic_game_state::GenericGameState;
 
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SantoriniState4x4 {
     pub position_heights: [u8; 16],
     pub worker_a_position: u8,
@@ -8,6 +9,7 @@ pub struct SantoriniState4x4 {
     pub player_a_turn: bool,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SantoriniState5x5 {
     pub tile_heights: [u8; 25],
     pub worker_a_tiles: [u8; 2],
@@ -30,12 +32,51 @@ pub trait GameState: Display + Send + Sync + Copy + Clone + Eq + PartialEq + std
     fn get_children_states_reuse_vec(&self, possible_next_states: &mut Vec<Self>);
 }
 
+/**
+Identifies a move by the tiles involved (positions for the 4x4 binary encodings).
+For worker placements, worker_from is NO_TILE, worker_to is the (first) placed worker and build is the second placed worker,
+or NO_TILE if only one worker is placed.
+ */
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct SantoriniMove {
+    pub worker_from: u8,
+    pub worker_to: u8,
+    pub build: u8,
+}
+
+impl SantoriniMove {
+    pub const NO_TILE: u8 = u8::MAX;
+
+    pub fn is_placement(&self) -> bool {
+        return self.worker_from == Self::NO_TILE;
+    }
+}
+
 pub trait SantoriniEval: GameState {
     type SantoriniState;
     fn get_santorini_state(&self) -> Self::SantoriniState;
     fn get_child_evaluation(&self) -> f32;
 }
 
+// The empty board before any worker is placed
+pub fn get_initial_setup_state<GS: GameState>() -> GS {
+    let heights = "0".repeat(GS::GenericGameState::ROWS * GS::GenericGameState::COLUMNS);
+    let generic_state = GS::GenericGameState::from_position_string(&format!("{}/-/-/A", heights))
+        .expect("The empty board is always a valid position");
+    return GS::from_generic_game_state(&generic_state);
+}
+
+pub trait MoveIdentity: GameState {
+    // The child state has to be one of the children states of this state
+    fn get_child_move(&self, child: &Self) -> SantoriniMove;
+
+    fn get_children_states_with_moves_reuse_vec(&self, reusable_children_vec: &mut Vec<Self>, children_with_moves: &mut Vec<(Self, SantoriniMove)>) {
+        self.get_children_states_reuse_vec(reusable_children_vec);
+        children_with_moves.clear();
+        children_with_moves.extend(reusable_children_vec.iter().map(|child| (*child, self.get_child_move(child))));
+    }
+}
+
 pub trait SimplifiedState {
     fn get_simplified_state(&self) -> Self;
     fn is_simplified(&self) -> bool;
@@ -63,5 +104,7 @@ pub mod game_state_5x5_struct;
 pub mod game_state_5x5_binary_composite;
 pub mod game_state_5x5_5bit;
 pub mod utils;
+pub mod perft;
+pub mod differential_fuzzing;
 
 mod game_state_tests;
\ No newline at end of file
@@ -0,0 +1,608 @@
+use std::fs;
+use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe};
+use anyhow::{bail, Context, Result};
+use rand::Rng;
+use crate::game_state::{ContinuousBlockId, ContinuousId, GameState, SantoriniEval, SantoriniState4x4, SantoriniState5x5, SimplifiedState};
+use crate::game_state::game_state_4x4_binary_3bit::GameState4x4Binary3Bit;
+use crate::game_state::game_state_4x4_binary_4bit::GameState4x4Binary4Bit;
+use crate::game_state::game_state_4x4_struct::GameState4x4Struct;
+use crate::game_state::game_state_5x5_5bit::GameState5x5Binary5bit;
+use crate::game_state::game_state_5x5_binary_128bit::GameState5x5Binary128bit;
+use crate::game_state::game_state_5x5_binary_composite::GameState5x5BinaryComposite;
+use crate::game_state::game_state_5x5_struct::GameState5x5Struct;
+use crate::generic_game_state::generic_santorini_game_state::GenericSantoriniGameState;
+use crate::generic_game_state::move_notation::{find_move, get_move_notation};
+use crate::generic_game_state::GenericGameState;
+
+// A position and the moves that are played from it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FuzzingSequence {
+    pub position: String,
+    pub moves: Vec<String>,
+}
+
+// The representations disagree after step moves of the sequence
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FuzzingDiscrepancy {
+    pub step: usize,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FuzzingFailure {
+    pub sequence: FuzzingSequence,
+    pub discrepancy: FuzzingDiscrepancy,
+}
+
+/*
+Everything a representation reports about its current state, the children are the sorted (normalized) position strings of the children states.
+The child evaluation is compared separately, as the representations sum up the same valuations in different orders.
+ */
+pub struct Observation {
+    pub properties: Vec<(String, String)>,
+    pub child_evaluation: (String, f32),
+    pub children: Vec<String>,
+}
+
+const CHILD_EVALUATION_TOLERANCE: f32 = 1e-4;
+
+// A representation that is played in lockstep with the others, positions and moves use the generic notations
+pub trait FuzzedRepresentation {
+    fn get_name(&self) -> &str;
+    fn set_position(&mut self, position: &str) -> Result<()>;
+    fn apply_move(&mut self, notation: &str) -> Result<()>;
+    fn get_position(&self) -> String;
+    fn get_move_notations(&self) -> Result<Vec<String>>;
+    fn observe(&self) -> Result<Observation>;
+}
+
+type PropertyFunction<GS> = fn(&GS) -> Result<Option<String>>;
+
+// Like positions, santorini states are compared with the workers of each player sorted
+pub trait NormalizedSantoriniState {
+    fn to_normalized_string(&self) -> String;
+}
+
+impl NormalizedSantoriniState for SantoriniState4x4 {
+    fn to_normalized_string(&self) -> String {
+        return format!("{:?}", self);
+    }
+}
+
+impl NormalizedSantoriniState for SantoriniState5x5 {
+    fn to_normalized_string(&self) -> String {
+        let mut normalized_state = self.clone();
+        normalized_state.worker_a_tiles.sort_unstable();
+        normalized_state.worker_b_tiles.sort_unstable();
+        return format!("{:?}", normalized_state);
+    }
+}
+
+/*
+Reports the position, turn, winner and children of the state, and the santorini state and child evaluation.
+The santorini states of the 4x4 representations use different layouts (tiles or positions), so they are only compared between
+representations with the same santorini state layout. Further properties like continuous ids can be added if the representation has them.
+ */
+pub struct GameStateRepresentation<GS: GameState> {
+    name: String,
+    state: Option<GS>,
+    santorini_state_layout: String,
+    additional_properties: Vec<(String, PropertyFunction<GS>)>,
+}
+
+impl<GS: GameState + SantoriniEval> GameStateRepresentation<GS> where GS::SantoriniState: NormalizedSantoriniState {
+    pub fn new(name: &str) -> GameStateRepresentation<GS> {
+        return GameStateRepresentation {
+            name: name.to_string(),
+            state: None,
+            santorini_state_layout: "tiles".to_string(),
+            additional_properties: Vec::new(),
+        };
+    }
+
+    pub fn with_santorini_state_layout(mut self, santorini_state_layout: &str) -> Self {
+        self.santorini_state_layout = santorini_state_layout.to_string();
+        return self;
+    }
+
+    // The property function returns None if the property is not defined for the state
+    pub fn with_property(mut self, name: &str, property_function: PropertyFunction<GS>) -> Self {
+        self.additional_properties.push((name.to_string(), property_function));
+        return self;
+    }
+
+    pub fn with_simplified_state(self) -> Self where GS: SimplifiedState {
+        return self.with_property("simplified_state", get_simplified_position);
+    }
+
+    pub fn with_continuous_ids(self) -> Self where GS: ContinuousId + ContinuousBlockId {
+        return self.with_property("continuous_id", get_continuous_id).with_property("continuous_block_id", get_continuous_block_id);
+    }
+
+    fn get_state(&self) -> Result<&GS> {
+        let Some(state) = &self.state else {
+            bail!("The position of {} was not set", self.name);
+        };
+        return Ok(state);
+    }
+
+    fn is_game_over(state: &GS) -> bool {
+        return state.has_player_a_won() || state.has_player_b_won();
+    }
+}
+
+// The workers of a player can be in any order, so they are sorted to compare positions
+fn get_normalized_position<GS: GameState>(state: &GS) -> String {
+    let position = state.to_generic_game_state().to_position_string();
+    return position.split('/').enumerate()
+        .map(|(index, part)| {
+            if index == 0 || !part.contains(',') {
+                return part.to_string();
+            }
+            let mut workers: Vec<u8> = part.split(',').filter_map(|worker| worker.parse().ok()).collect();
+            workers.sort_unstable();
+            return workers.iter().map(|worker| worker.to_string()).collect::<Vec<String>>().join(",");
+        })
+        .collect::<Vec<String>>()
+        .join("/");
+}
+
+fn get_simplified_position<GS: GameState + SimplifiedState>(state: &GS) -> Result<Option<String>> {
+    let simplified_state = state.get_simplified_state();
+    if !simplified_state.is_simplified() {
+        bail!("The simplified state {} is not simplified", simplified_state.to_generic_game_state().to_position_string());
+    }
+    return Ok(Some(get_normalized_position(&simplified_state)));
+}
+
+// Continuous ids only exist for simplified states in which all workers are placed and no player has won
+fn get_continuous_id_state<GS: GameState + SimplifiedState + ContinuousBlockId>(state: &GS) -> Option<GS> {
+    if state.has_player_a_won() || state.has_player_b_won() || state.get_block_count() < 0 {
+        return None;
+    }
+    return Some(state.get_simplified_state());
+}
+
+fn get_continuous_id<GS: GameState + ContinuousId + ContinuousBlockId>(state: &GS) -> Result<Option<String>> {
+    let Some(simplified_state) = get_continuous_id_state(state) else {
+        return Ok(None);
+    };
+    let continuous_id = simplified_state.get_continuous_id();
+    if GS::from_continuous_id(continuous_id) != simplified_state {
+        bail!("The continuous id {} doesn't restore the state", continuous_id);
+    }
+    return Ok(Some(continuous_id.to_string()));
+}
+
+fn get_continuous_block_id<GS: GameState + ContinuousBlockId>(state: &GS) -> Result<Option<String>> {
+    let Some(simplified_state) = get_continuous_id_state(state) else {
+        return Ok(None);
+    };
+    let block_count = simplified_state.get_block_count();
+    let continuous_block_id = simplified_state.get_continuous_block_id();
+    if GS::from_continuous_block_id(block_count as isize, continuous_block_id) != simplified_state {
+        bail!("The continuous block id {} doesn't restore the state", continuous_block_id);
+    }
+    return Ok(Some(format!("{} {}", block_count, continuous_block_id)));
+}
+
+impl<GS: GameState + SantoriniEval> FuzzedRepresentation for GameStateRepresentation<GS> where GS::SantoriniState: NormalizedSantoriniState {
+    fn get_name(&self) -> &str {
+        return &self.name;
+    }
+
+    fn set_position(&mut self, position: &str) -> Result<()> {
+        self.state = Some(GS::from_generic_game_state(&GS::GenericGameState::from_position_string(position)?));
+        return Ok(());
+    }
+
+    fn apply_move(&mut self, notation: &str) -> Result<()> {
+        self.state = Some(find_move(self.get_state()?, notation)?.1);
+        return Ok(());
+    }
+
+    fn get_position(&self) -> String {
+        return self.state.map(|state| state.to_generic_game_state().to_position_string()).unwrap_or_default();
+    }
+
+    fn get_move_notations(&self) -> Result<Vec<String>> {
+        let state = self.get_state()?;
+        if Self::is_game_over(state) {
+            return Ok(Vec::new());
+        }
+        return state.get_children_states().iter().map(|child| get_move_notation(state, child)).collect();
+    }
+
+    fn observe(&self) -> Result<Observation> {
+        let state = self.get_state()?;
+        let mut properties = vec![
+            ("position".to_string(), get_normalized_position(state)),
+            ("player_a_turn".to_string(), state.is_player_a_turn().to_string()),
+            ("player_a_won".to_string(), state.has_player_a_won().to_string()),
+            ("player_b_won".to_string(), state.has_player_b_won().to_string()),
+            (format!("santorini_state ({})", self.santorini_state_layout), state.get_santorini_state().to_normalized_string()),
+        ];
+        for (name, property_function) in &self.additional_properties {
+            if let Some(value) = property_function(state)? {
+                properties.push((name.clone(), value));
+            }
+        }
+
+        // Won states have no children (and get_children_states must not be called for them)
+        let mut children: Vec<String> = if Self::is_game_over(state) {
+            Vec::new()
+        } else {
+            state.get_children_states().iter().map(get_normalized_position).collect()
+        };
+        children.sort();
+        let child_evaluation = (format!("child_evaluation ({})", self.santorini_state_layout), state.get_child_evaluation());
+        return Ok(Observation { properties, child_evaluation, children });
+    }
+}
+
+/**
+Plays random games on all representations of a board size in lockstep and compares them after every move:
+the representations have to agree on every property they report, and on the children states.
+Panics of a representation are reported as discrepancies, so they can be minimized like other discrepancies.
+ */
+pub struct DifferentialFuzzer<GGS: GenericGameState> {
+    _marker: PhantomData<GGS>,
+    representations: Vec<Box<dyn FuzzedRepresentation>>,
+}
+
+impl<GGS: GenericGameState> Default for DifferentialFuzzer<GGS> {
+    fn default() -> Self {
+        return DifferentialFuzzer::new();
+    }
+}
+
+pub fn new_4x4_fuzzer() -> DifferentialFuzzer<GenericSantoriniGameState<4, 4, 1>> {
+    // The struct uses tiles for the santorini state, the binary encodings positions, and the 4 bit encoding is from the perspective of the player to move
+    return DifferentialFuzzer::new()
+        .with_representation(GameStateRepresentation::<GameState4x4Struct>::new("4x4_struct"))
+        .with_representation(GameStateRepresentation::<GameState4x4Binary3Bit>::new("4x4_binary_3bit")
+            .with_santorini_state_layout("positions")
+            .with_simplified_state()
+            .with_continuous_ids())
+        .with_representation(GameStateRepresentation::<GameState4x4Binary4Bit>::new("4x4_binary_4bit")
+            .with_santorini_state_layout("positions, player to move")
+            .with_simplified_state());
+}
+
+pub fn new_5x5_fuzzer() -> DifferentialFuzzer<GenericSantoriniGameState<5, 5, 2>> {
+    return DifferentialFuzzer::new()
+        .with_representation(GameStateRepresentation::<GameState5x5Struct>::new("5x5_struct"))
+        .with_representation(GameStateRepresentation::<GameState5x5Binary128bit>::new("5x5_binary_128bit"))
+        .with_representation(GameStateRepresentation::<GameState5x5BinaryComposite>::new("5x5_binary_composite"))
+        .with_representation(GameStateRepresentation::<GameState5x5Binary5bit>::new("5x5_binary_5bit"));
+}
+
+impl<GGS: GenericGameState> DifferentialFuzzer<GGS> {
+    pub fn new() -> DifferentialFuzzer<GGS> {
+        return DifferentialFuzzer {
+            _marker: PhantomData,
+            representations: Vec::new(),
+        };
+    }
+
+    pub fn with_representation<GS: GameState<GenericGameState=GGS> + SantoriniEval + 'static>(mut self, representation: GameStateRepresentation<GS>) -> Self where GS::SantoriniState: NormalizedSantoriniState {
+        self.representations.push(Box::new(representation));
+        return self;
+    }
+
+    fn set_position(&mut self, position: &str) -> Result<()> {
+        for representation in &mut self.representations {
+            representation.set_position(position)?;
+        }
+        return Ok(());
+    }
+
+    fn compare_representations(&self, step: usize) -> Option<FuzzingDiscrepancy> {
+        let discrepancy = |message: String| Some(FuzzingDiscrepancy { step, message });
+
+        let mut observations = Vec::with_capacity(self.representations.len());
+        for representation in &self.representations {
+            let name = representation.get_name();
+            match panic::catch_unwind(AssertUnwindSafe(|| representation.observe())) {
+                Ok(Ok(observation)) => observations.push((name, observation)),
+                Ok(Err(error)) => return discrepancy(format!("{}: {}", name, error)),
+                Err(_) => return discrepancy(format!("{} panicked", name)),
+            }
+        }
+
+        for (index, (name, observation)) in observations.iter().enumerate() {
+            for (property, value) in &observation.properties {
+                // Compared to the first representation with the property
+                let reference = observations[..index].iter()
+                    .find_map(|(reference_name, reference)| reference.properties.iter()
+                        .find(|(reference_property, _)| reference_property == property)
+                        .map(|(_, reference_value)| (reference_name, reference_value)));
+                if let Some((reference_name, reference_value)) = reference {
+                    if reference_value != value {
+                        return discrepancy(format!("{} is {} for {} and {} for {}", property, reference_value, reference_name, value, name));
+                    }
+                }
+            }
+
+            let (evaluation_property, evaluation) = &observation.child_evaluation;
+            let reference = observations[..index].iter().find(|(_, reference)| reference.child_evaluation.0 == *evaluation_property);
+            if let Some((reference_name, reference)) = reference {
+                let reference_evaluation = reference.child_evaluation.1;
+                // Equal infinite evaluations (wins) have no difference, subtracting them would result in NaN
+                let difference = if reference_evaluation == *evaluation { 0.0 } else { (reference_evaluation - evaluation).abs() };
+                if difference > CHILD_EVALUATION_TOLERANCE * reference_evaluation.abs().max(1.0) {
+                    return discrepancy(format!("{} is {} for {} and {} for {}", evaluation_property, reference_evaluation, reference_name, evaluation, name));
+                }
+            }
+        }
+
+        let (reference_name, reference) = &observations[0];
+        for (name, observation) in &observations[1..] {
+            if observation.children != reference.children {
+                let only_in_reference: Vec<&String> = reference.children.iter().filter(|child| !observation.children.contains(child)).collect();
+                let only_in_representation: Vec<&String> = observation.children.iter().filter(|child| !reference.children.contains(child)).collect();
+                return discrepancy(format!("{} has {} children and {} has {}, only for {}: {:?}, only for {}: {:?}",
+                    reference_name, reference.children.len(), name, observation.children.len(),
+                    reference_name, only_in_reference, name, only_in_representation));
+            }
+        }
+        return None;
+    }
+
+    fn apply_move(&mut self, step: usize, notation: &str) -> Option<FuzzingDiscrepancy> {
+        for representation in &mut self.representations {
+            let name = representation.get_name().to_string();
+            let message = match panic::catch_unwind(AssertUnwindSafe(|| representation.apply_move(notation))) {
+                Ok(Ok(())) => continue,
+                Ok(Err(error)) => format!("{}: {}", name, error),
+                Err(_) => format!("{} panicked while playing {}", name, notation),
+            };
+            return Some(FuzzingDiscrepancy { step, message });
+        }
+        return None;
+    }
+
+    // Replays the sequence, an error means that the sequence is invalid (e.g. an invalid position) rather than a discrepancy
+    pub fn find_discrepancy(&mut self, sequence: &FuzzingSequence) -> Result<Option<FuzzingDiscrepancy>> {
+        self.set_position(&sequence.position)?;
+        for (step, notation) in sequence.moves.iter().enumerate() {
+            if let Some(discrepancy) = self.compare_representations(step) {
+                return Ok(Some(discrepancy));
+            }
+            if let Some(discrepancy) = self.apply_move(step + 1, notation) {
+                return Ok(Some(discrepancy));
+            }
+        }
+        return Ok(self.compare_representations(sequence.moves.len()));
+    }
+
+    // Plays random moves from the position until the game is over, max_moves are played or the representations disagree
+    pub fn fuzz_game<RNG: Rng>(&mut self, rng: &mut RNG, position: &str, max_moves: usize) -> Result<Option<FuzzingFailure>> {
+        let mut sequence = FuzzingSequence { position: position.to_string(), moves: Vec::new() };
+        self.set_position(position)?;
+
+        for step in 0..=max_moves {
+            if let Some(discrepancy) = self.compare_representations(step) {
+                return Ok(Some(FuzzingFailure { sequence, discrepancy }));
+            }
+            let notations = self.representations[0].get_move_notations()?;
+            if step == max_moves || notations.is_empty() {
+                break;
+            }
+
+            let notation = notations[rng.gen_range(0..notations.len())].clone();
+            sequence.moves.push(notation.clone());
+            if let Some(discrepancy) = self.apply_move(step + 1, &notation) {
+                return Ok(Some(FuzzingFailure { sequence, discrepancy }));
+            }
+        }
+        return Ok(None);
+    }
+
+    /*
+    Plays games from the empty board and from random positions, every failure is minimized.
+    The fuzzing continues after a failure so that different discrepancies can be found, failures with the same message are only returned once.
+     */
+    pub fn fuzz<RNG: Rng>(&mut self, rng: &mut RNG, games: usize, max_moves: usize) -> Result<Vec<FuzzingFailure>> {
+        let empty_board = format!("{}/-/-/A", "0".repeat(GGS::ROWS * GGS::COLUMNS));
+        let mut failures = Vec::new();
+        for game in 0..games {
+            let position = if game % 2 == 0 { empty_board.clone() } else { GGS::generate_random_state_rng(rng).to_position_string() };
+            if let Some(failure) = self.fuzz_game(rng, &position, max_moves)? {
+                let failure = self.minimize(&failure.sequence)?;
+                if failures.iter().all(|known_failure: &FuzzingFailure| known_failure.discrepancy.message != failure.discrepancy.message) {
+                    failures.push(failure);
+                }
+            }
+        }
+        return Ok(failures);
+    }
+
+    /*
+    Shortens a failing sequence: the moves after the discrepancy are removed, the sequence starts as late as possible
+    and the blocks of the position are lowered as far as possible while the representations still disagree.
+     */
+    pub fn minimize(&mut self, sequence: &FuzzingSequence) -> Result<FuzzingFailure> {
+        let Some(mut discrepancy) = self.find_discrepancy(sequence)? else {
+            bail!("The representations agree on the sequence");
+        };
+        let mut minimized_sequence = FuzzingSequence {
+            position: sequence.position.clone(),
+            moves: sequence.moves[..discrepancy.step].to_vec(),
+        };
+
+        for start in (1..=minimized_sequence.moves.len()).rev() {
+            let Ok(position) = self.get_position_after(&minimized_sequence, start) else {
+                continue;
+            };
+            let candidate = FuzzingSequence { position, moves: minimized_sequence.moves[start..].to_vec() };
+            if let Ok(Some(candidate_discrepancy)) = self.find_discrepancy(&candidate) {
+                minimized_sequence = candidate;
+                discrepancy = candidate_discrepancy;
+                break;
+            }
+        }
+
+        let mut lowered_block = true;
+        while lowered_block {
+            lowered_block = false;
+            for candidate_position in get_lowered_positions(&minimized_sequence.position) {
+                let candidate = FuzzingSequence { position: candidate_position, moves: minimized_sequence.moves.clone() };
+                if let Ok(Some(candidate_discrepancy)) = self.find_discrepancy(&candidate) {
+                    minimized_sequence = candidate;
+                    discrepancy = candidate_discrepancy;
+                    lowered_block = true;
+                    break;
+                }
+            }
+        }
+
+        return Ok(FuzzingFailure { sequence: minimized_sequence, discrepancy });
+    }
+
+    // The position after the first moves of the sequence, as seen by the first representation
+    fn get_position_after(&mut self, sequence: &FuzzingSequence, moves: usize) -> Result<String> {
+        let representation = &mut self.representations[0];
+        representation.set_position(&sequence.position)?;
+        for notation in &sequence.moves[..moves] {
+            representation.apply_move(notation)?;
+        }
+        return Ok(representation.get_position());
+    }
+}
+
+// Positions with one block removed, with either player to move as the turn can depend on the block count
+fn get_lowered_positions(position: &str) -> Vec<String> {
+    let Some((heights, rest)) = position.split_once('/') else {
+        return Vec::new();
+    };
+    let Some((workers, turn)) = rest.rsplit_once('/') else {
+        return Vec::new();
+    };
+
+    let mut lowered_positions = Vec::new();
+    for (tile, height) in heights.char_indices() {
+        let Some(height) = height.to_digit(10).filter(|height| *height > 0) else {
+            continue;
+        };
+        let lowered_heights = format!("{}{}{}", &heights[..tile], height - 1, &heights[tile + 1..]);
+        for lowered_turn in [turn, if turn == "A" { "B" } else { "A" }] {
+            lowered_positions.push(format!("{}/{}/{}", lowered_heights, workers, lowered_turn));
+        }
+    }
+    return lowered_positions;
+}
+
+/*
+Failing sequences are stored as regression tests in text files with one sequence per line: the position and the moves, separated by spaces.
+Empty lines and lines starting with '#' are ignored, the discrepancy is stored as a comment above the sequence.
+ */
+pub fn load_fuzzing_sequences(path: &str) -> Result<Vec<FuzzingSequence>> {
+    let content = fs::read_to_string(path).with_context(|| format!("Could not read fuzzing sequences {}", path))?;
+    return Ok(parse_fuzzing_sequences(&content));
+}
+
+pub fn parse_fuzzing_sequences(content: &str) -> Vec<FuzzingSequence> {
+    let mut sequences = Vec::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        let position = parts.next().unwrap().to_string();
+        sequences.push(FuzzingSequence { position, moves: parts.map(|notation| notation.to_string()).collect() });
+    }
+    return sequences;
+}
+
+pub fn format_fuzzing_failure(failure: &FuzzingFailure) -> String {
+    let mut line = failure.sequence.position.clone();
+    for notation in &failure.sequence.moves {
+        line.push(' ');
+        line.push_str(notation);
+    }
+    return format!("# {}\n{}\n", failure.discrepancy.message, line);
+}
+
+// Appends the failure to the file, which is created if it doesn't exist
+pub fn save_fuzzing_failure(path: &str, failure: &FuzzingFailure) -> Result<()> {
+    let content = fs::read_to_string(path).unwrap_or_default();
+    fs::write(path, format!("{}{}", content, format_fuzzing_failure(failure))).with_context(|| format!("Could not write fuzzing sequences {}", path))?;
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use crate::game_state::differential_fuzzing::{format_fuzzing_failure, load_fuzzing_sequences, new_4x4_fuzzer, new_5x5_fuzzer, parse_fuzzing_sequences, save_fuzzing_failure, DifferentialFuzzer, FuzzingDiscrepancy, FuzzingFailure, GameStateRepresentation};
+    use crate::game_state::game_state_4x4_binary_3bit::GameState4x4Binary3Bit;
+    use crate::game_state::game_state_4x4_struct::GameState4x4Struct;
+    use crate::game_state::GameState;
+    use crate::generic_game_state::GenericGameState;
+
+    fn has_dome<GS: GameState>(state: &GS) -> Result<Option<String>> {
+        let position = state.to_generic_game_state().to_position_string();
+        return Ok(Some(position.split('/').next().unwrap().contains('4').to_string()));
+    }
+
+    // A representation that never sees domes, so it disagrees with the others once a dome is built
+    fn never_has_dome<GS: GameState>(_state: &GS) -> Result<Option<String>> {
+        return Ok(Some(false.to_string()));
+    }
+
+    #[test]
+    fn test_corpus_sequences_have_no_discrepancies() {
+        let sequences = parse_fuzzing_sequences(include_str!("../../test_suites/differential_fuzzing_4x4.txt"));
+        assert!(!sequences.is_empty());
+        let mut fuzzer = new_4x4_fuzzer();
+        for sequence in sequences {
+            assert_eq!(fuzzer.find_discrepancy(&sequence).unwrap(), None, "Regression in {:?}", sequence);
+        }
+    }
+
+    #[test]
+    fn test_random_games_have_no_discrepancies() {
+        let mut rng = rand::thread_rng();
+        assert_eq!(new_4x4_fuzzer().fuzz(&mut rng, 50, 100).unwrap(), Vec::new());
+        assert_eq!(new_5x5_fuzzer().fuzz(&mut rng, 50, 100).unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn test_discrepancies_are_minimized_and_added_to_the_corpus() {
+        let mut fuzzer = DifferentialFuzzer::new()
+            .with_representation(GameStateRepresentation::<GameState4x4Struct>::new("4x4_struct").with_property("has_dome", has_dome))
+            .with_representation(GameStateRepresentation::<GameState4x4Binary3Bit>::new("4x4_binary_3bit")
+                .with_santorini_state_layout("positions")
+                .with_property("has_dome", never_has_dome));
+        let failures = fuzzer.fuzz(&mut rand::thread_rng(), 50, 100).unwrap();
+        assert_eq!(failures.len(), 1);
+        let failure = &failures[0];
+        assert_eq!(failure.discrepancy.message, "has_dome is true for 4x4_struct and false for 4x4_binary_3bit");
+
+        // Minimized to a single dome without moves
+        assert_eq!(failure.sequence.moves, Vec::<String>::new());
+        let heights = failure.sequence.position.split('/').next().unwrap();
+        assert_eq!(heights.chars().filter(|height| *height != '0').collect::<String>(), "4");
+
+        let path = std::env::temp_dir().join("santorini_test_differential_fuzzing_corpus.txt");
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+        save_fuzzing_failure(path, failure).unwrap();
+        let sequences = load_fuzzing_sequences(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(sequences, vec![failure.sequence.clone()]);
+        assert_eq!(fuzzer.find_discrepancy(&sequences[0]).unwrap(), Some(failure.discrepancy.clone()));
+        assert_eq!(new_4x4_fuzzer().find_discrepancy(&sequences[0]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_fuzzing_failure_is_parsed_to_the_same_sequence() {
+        let sequence = parse_fuzzing_sequences("0000000000000000000000000/-/-/A a1,b2 c3,d4 b2-b3+b2").remove(0);
+        assert_eq!(sequence.moves.len(), 3);
+        let failure = FuzzingFailure { sequence: sequence.clone(), discrepancy: FuzzingDiscrepancy { step: 3, message: "Discrepancy".to_string() } };
+        assert_eq!(format_fuzzing_failure(&failure), "# Discrepancy\n0000000000000000000000000/-/-/A a1,b2 c3,d4 b2-b3+b2\n");
+        assert_eq!(parse_fuzzing_sequences(&format_fuzzing_failure(&failure)), vec![sequence]);
+    }
+}
@@ -2,13 +2,15 @@ use std::collections::HashMap;
 use std::fmt;
 use std::fmt::Formatter;
 use once_cell::sync::Lazy;
-use crate::game_state::{ContinuousBlockId, ContinuousId, GameState, SimplifiedState, SantoriniEval, SantoriniState4x4};
+use crate::game_state::{ContinuousBlockId, ContinuousId, GameState, SimplifiedState, SantoriniEval, SantoriniState4x4, MoveIdentity, SantoriniMove};
 use crate::game_state::utils::precompute_position_to_tile_id::precompute_position_to_tile_id;
 use crate::game_state::utils::get_binomial_coefficient::get_binomial_coefficient;
 use crate::game_state::utils::child_evaluation::gs4x4_child_evaluation;
+use crate::game_state::utils::child_move::gs4x4_child_move;
 use crate::game_state::utils::symmetric_simplified::gs4x4_symmetric_simplified;
 use crate::game_state::utils::symmetric_simplified::gs4x4_symmetric_simplified::POSSIBLE_SIMPLIFIED_STATE_VARIANTS;
 use crate::generic_game_state::generic_santorini_game_state::GenericSantoriniGameState;
+use crate::minimax::score::win_score;
 
 /*
 Bits 0-47: 3 bits per tile, 16 tiles
@@ -306,15 +308,22 @@ impl SantoriniEval for GameState4x4Binary3Bit {
 
     fn get_child_evaluation(&self) -> f32 {
         if self.has_player_a_won() {
-            return f32::INFINITY;
+            return win_score(true);
         } else if self.has_player_b_won() {
-            return f32::NEG_INFINITY;
+            return win_score(false);
         }
 
         return gs4x4_child_evaluation::get_child_evaluation(self.get_santorini_state());
     }
 }
 
+impl MoveIdentity for GameState4x4Binary3Bit {
+    fn get_child_move(&self, child: &Self) -> SantoriniMove {
+        return gs4x4_child_move::get_child_move(self.get_santorini_state(), child.get_santorini_state());
+    }
+}
+
+
 impl SimplifiedState for GameState4x4Binary3Bit {
     fn get_simplified_state(&self) -> Self {
         let height_information = self.0 & 0xFFFFFFFFFFFF;
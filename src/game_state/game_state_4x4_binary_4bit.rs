@@ -1,11 +1,13 @@
 use std::fmt;
 use std::fmt::Formatter;
-use crate::game_state::{GameState, SantoriniEval, SantoriniState4x4, SimplifiedState};
+use crate::game_state::{GameState, SantoriniEval, SantoriniState4x4, SimplifiedState, MoveIdentity, SantoriniMove};
 
 use crate::game_state::utils::precompute_position_to_tile_id::precompute_position_to_tile_id;
 use crate::game_state::utils::child_evaluation::gs4x4_child_evaluation;
+use crate::game_state::utils::child_move;
 use crate::game_state::utils::symmetric_simplified::gs4x4_symmetric_simplified;
 use crate::generic_game_state::generic_santorini_game_state::GenericSantoriniGameState;
+use crate::minimax::score::win_score;
 
 /*
 For this encoding, player A is always the active player
@@ -116,7 +118,7 @@ impl GameState4x4Binary4Bit {
             return false;
         }
         let player_a_bits = self.0 & Self::PLAYER_A_MASK;
-        let cleaned_player_b_bit = player_b_bits & !(player_a_bits << 1);
+        let cleaned_player_b_bit = player_b_bits & !(player_a_bits >> 1);
         let player_b_position = cleaned_player_b_bit.trailing_zeros() / 4;
         return ((self.0 >> (player_b_position * 4)) & 3) == 3;
     }
@@ -390,15 +392,25 @@ impl SantoriniEval for GameState4x4Binary4Bit {
         let state = self.get_santorini_state();
 
         if state.worker_a_position != 16 && state.position_heights[state.worker_a_position as usize] == 3 {
-            return f32::INFINITY;
+            return win_score(true);
         } else if state.worker_b_position != 16 && state.position_heights[state.worker_b_position as usize] == 3 {
-            return f32::NEG_INFINITY;
+            return win_score(false);
         }
 
         return gs4x4_child_evaluation::get_child_evaluation(state);
     }
 }
 
+impl MoveIdentity for GameState4x4Binary4Bit {
+    fn get_child_move(&self, child: &Self) -> SantoriniMove {
+        // The active player is always stored as worker A, so the moved worker is worker B in the child state
+        let parent_state = self.get_santorini_state();
+        let child_state = child.get_santorini_state();
+        return child_move::get_child_move(&parent_state.position_heights, &child_state.position_heights, &[parent_state.worker_a_position], &[child_state.worker_b_position]);
+    }
+}
+
+
 impl SimplifiedState for GameState4x4Binary4Bit {
     fn get_simplified_state(&self) -> Self {
         let (player_a_position, player_b_position) = self.get_generic_player_positions();
@@ -452,4 +464,22 @@ impl SimplifiedState for GameState4x4Binary4Bit {
         }
         return false;
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::game_state_4x4_binary_4bit::GameState4x4Binary4Bit;
+    use crate::game_state::GameState;
+    use crate::generic_game_state::generic_santorini_game_state::GenericSantoriniGameState;
+    use crate::generic_game_state::GenericGameState;
+
+    #[test]
+    fn test_player_b_wins_with_a_dome_before_their_worker() {
+        // Domes set both worker bits, the dome on tile 0 comes before the worker of player B on height 3
+        let generic_state = GenericSantoriniGameState::<4, 4, 1>::from_position_string("4000030000000001/10/5/A").unwrap();
+        let state = GameState4x4Binary4Bit::from_generic_game_state(&generic_state);
+        assert!(state.has_player_b_won());
+        assert!(!state.has_player_a_won());
+        assert_eq!(state.to_generic_game_state(), generic_state);
+    }
+}
@@ -1,8 +1,10 @@
 use std::fmt;
 use std::fmt::Formatter;
-use crate::game_state::{GameState, SantoriniEval, SantoriniState4x4};
+use crate::game_state::{GameState, SantoriniEval, SantoriniState4x4, MoveIdentity, SantoriniMove};
 use crate::game_state::utils::child_evaluation::gs4x4_child_evaluation;
+use crate::game_state::utils::child_move::gs4x4_child_move;
 use crate::generic_game_state::generic_santorini_game_state::GenericSantoriniGameState;
+use crate::minimax::score::win_score;
 
 #[derive(Copy, Clone, Hash, Eq, PartialEq, Debug)]
 pub struct GameState4x4Struct {
@@ -244,11 +246,17 @@ impl SantoriniEval for GameState4x4Struct {
 
     fn get_child_evaluation(&self) -> f32 {
         if self.player_a_worker != Self::WORKER_NOT_PLACED && self.tile_heights[self.player_a_worker as usize] == 3 {
-            return f32::INFINITY;
+            return win_score(true);
         } else if self.player_b_worker != Self::WORKER_NOT_PLACED && self.tile_heights[self.player_b_worker as usize] == 3 {
-            return f32::NEG_INFINITY;
+            return win_score(false);
         }
 
         return gs4x4_child_evaluation::get_child_evaluation(self.get_santorini_state());
     }
-}
\ No newline at end of file
+}
+
+impl MoveIdentity for GameState4x4Struct {
+    fn get_child_move(&self, child: &Self) -> SantoriniMove {
+        return gs4x4_child_move::get_child_move(self.get_santorini_state(), child.get_santorini_state());
+    }
+}
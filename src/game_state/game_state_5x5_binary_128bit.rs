@@ -1,8 +1,10 @@
 use std::fmt;
 use std::fmt::Formatter;
-use crate::game_state::{GameState, SantoriniEval, SantoriniState5x5};
+use crate::game_state::{GameState, SantoriniEval, SantoriniState5x5, MoveIdentity, SantoriniMove};
 use crate::game_state::utils::child_evaluation::gs5x5_child_evaluation;
+use crate::game_state::utils::child_move::gs5x5_child_move;
 use crate::generic_game_state::generic_santorini_game_state::GenericSantoriniGameState;
+use crate::minimax::score::win_score;
 
 /*
 Bits 0-74: 3 bits per tile, 25 tiles
@@ -310,11 +312,17 @@ impl SantoriniEval for GameState5x5Binary128bit {
 
     fn get_child_evaluation(&self) -> f32 {
         if self.has_player_a_won() {
-            return f32::INFINITY;
+            return win_score(true);
         } else if self.has_player_b_won() {
-            return f32::NEG_INFINITY;
+            return win_score(false);
         }
 
         return gs5x5_child_evaluation::get_child_evaluation(self.get_santorini_state())
     }
-}
\ No newline at end of file
+}
+
+impl MoveIdentity for GameState5x5Binary128bit {
+    fn get_child_move(&self, child: &Self) -> SantoriniMove {
+        return gs5x5_child_move::get_child_move(self.get_santorini_state(), child.get_santorini_state());
+    }
+}
@@ -6,10 +6,13 @@ mod tests {
     use crate::game_state::game_state_5x5_binary_128bit::GameState5x5Binary128bit;
     use crate::game_state::game_state_5x5_binary_composite::GameState5x5BinaryComposite;
     use crate::game_state::game_state_5x5_struct::GameState5x5Struct;
-    use crate::game_state::{GameState, SimplifiedState};
+    use crate::game_state::{GameState, MoveIdentity, SimplifiedState};
     use crate::game_state::game_state_5x5_5bit::GameState5x5Binary5bit;
     use crate::generic_game_state::generic_santorini_game_state::GenericSantoriniGameState;
+    #[allow(deprecated)]
+    use crate::generic_game_state::generic_4x4_game_state::Generic4x4GameState;
     use crate::generic_game_state::GenericGameState;
+    use crate::generic_game_state::position_constraints::{PositionConstraints, PositionPhase, WorkerAdjacency};
 
     fn find_4x4_generic_discrepancies(tries: usize) {
         let mut states_to_test = Vec::with_capacity(tries + 2);
@@ -298,6 +301,156 @@ mod tests {
         }
     }
 
+    // Returns the moves of all children as sortable tuples, the order of placed workers doesn't matter for the move
+    fn check_child_moves<GS: MoveIdentity>(state: &GS) -> Vec<(u8, u8, u8)> {
+        if state.has_player_a_won() || state.has_player_b_won() {
+            return Vec::new();
+        }
+        let children_states = state.get_children_states();
+        let mut children_with_moves = Vec::new();
+        state.get_children_states_with_moves_reuse_vec(&mut Vec::new(), &mut children_with_moves);
+        assert_eq!(children_with_moves.len(), children_states.len());
+
+        let mut moves = Vec::with_capacity(children_states.len());
+        for (child_state, (child_with_move, child_move)) in children_states.iter().zip(children_with_moves.iter()) {
+            assert!(child_state == child_with_move);
+            assert_eq!(state.get_child_move(child_state), *child_move);
+            // The move has to identify the child, so looking it up has to find exactly this child
+            let matching_children: Vec<&GS> = children_states.iter().filter(|child| state.get_child_move(child) == *child_move).collect();
+            assert!(matching_children.len() == 1 && matching_children[0] == child_state, "{:?}", child_move);
+
+            if child_move.is_placement() {
+                moves.push((child_move.worker_from, child_move.worker_to.min(child_move.build), child_move.worker_to.max(child_move.build)));
+            } else {
+                moves.push((child_move.worker_from, child_move.worker_to, child_move.build));
+            }
+        }
+        moves.sort_unstable();
+        return moves;
+    }
+
+    fn find_child_move_discrepancies(tries: usize) {
+        let mut states_4x4 = vec![GenericSantoriniGameState::<4, 4, 1>::new(None, None, [[0; 4]; 4], true).unwrap()];
+        let mut states_5x5 = vec![
+            GenericSantoriniGameState::<5, 5, 2>::new(None, None, [[0; 5]; 5], true).unwrap(),
+            GenericSantoriniGameState::<5, 5, 2>::new(Some([0, 1]), None, [[0; 5]; 5], false).unwrap(),
+        ];
+        for _ in 0..tries {
+            states_4x4.push(GenericSantoriniGameState::<4, 4, 1>::generate_random_state());
+            states_5x5.push(GenericSantoriniGameState::<5, 5, 2>::generate_random_state());
+        }
+
+        // The 4x4 representations use their own tile layouts, so only the round trip within a representation is compared
+        for state_to_test in &states_4x4 {
+            check_child_moves(&GameState4x4Binary3Bit::from_generic_game_state(state_to_test));
+            check_child_moves(&GameState4x4Binary4Bit::from_generic_game_state(state_to_test));
+            check_child_moves(&GameState4x4Struct::from_generic_game_state(state_to_test));
+        }
+
+        for state_to_test in &states_5x5 {
+            let moves_binary = check_child_moves(&GameState5x5Binary128bit::from_generic_game_state(state_to_test));
+            assert_eq!(moves_binary, check_child_moves(&GameState5x5Struct::from_generic_game_state(state_to_test)), "{}", state_to_test);
+            assert_eq!(moves_binary, check_child_moves(&GameState5x5BinaryComposite::from_generic_game_state(state_to_test)), "{}", state_to_test);
+            assert_eq!(moves_binary, check_child_moves(&GameState5x5Binary5bit::from_generic_game_state(state_to_test)), "{}", state_to_test);
+        }
+    }
+
+    fn find_position_string_discrepancies(tries: usize) {
+        for _ in 0..tries {
+            let state_4x4 = GenericSantoriniGameState::<4, 4, 1>::generate_random_state();
+            let position_string_4x4 = state_4x4.to_position_string();
+            assert_eq!(state_4x4, GenericSantoriniGameState::<4, 4, 1>::from_position_string(&position_string_4x4).unwrap(), "{}", position_string_4x4);
+
+            let state_5x5 = GenericSantoriniGameState::<5, 5, 2>::generate_random_state();
+            let position_string_5x5 = state_5x5.to_position_string();
+            assert_eq!(state_5x5, GenericSantoriniGameState::<5, 5, 2>::from_position_string(&position_string_5x5).unwrap(), "{}", position_string_5x5);
+        }
+
+        let state_without_workers = GenericSantoriniGameState::<5, 5, 2>::new(None, None, [[0; 5]; 5], true).unwrap();
+        assert_eq!(state_without_workers.to_position_string(), "0000000000000000000000000/-/-/A");
+        assert_eq!(state_without_workers, GenericSantoriniGameState::<5, 5, 2>::from_position_string("0000000000000000000000000/-/-/A").unwrap());
+    }
+
+    fn find_constrained_position_discrepancies(tries: usize) {
+        let constraints_4x4 = PositionConstraints::new().with_no_immediate_win().with_block_count(15);
+        for state_to_test in constraints_4x4.generate_states::<GenericSantoriniGameState<4, 4, 1>>(0, tries).unwrap() {
+            let block_count = state_to_test.tile_heights.iter().flatten().map(|&height| height as usize).sum::<usize>();
+            assert_eq!(block_count, 15);
+
+            // The player to move must not be able to win with any move in any representation
+            let player_a_turn = state_to_test.player_a_turn;
+            let state_3b = GameState4x4Binary3Bit::from_generic_game_state(&state_to_test);
+            let state_struct = GameState4x4Struct::from_generic_game_state(&state_to_test);
+            assert!(state_3b.get_children_states().iter().all(|child| if player_a_turn { !child.has_player_a_won() } else { !child.has_player_b_won() }), "{}", state_to_test);
+            assert!(state_struct.get_children_states().iter().all(|child| if player_a_turn { !child.has_player_a_won() } else { !child.has_player_b_won() }), "{}", state_to_test);
+        }
+
+        let constraints_5x5 = PositionConstraints::new()
+            .with_no_immediate_win()
+            .with_block_count(30)
+            .with_worker_heights(vec![2, 1], vec![0, 2])
+            .with_dome_count(3)
+            .with_worker_adjacency(WorkerAdjacency::NonAdjacent);
+        for state_to_test in constraints_5x5.generate_states::<GenericSantoriniGameState<5, 5, 2>>(0, tries).unwrap() {
+            let block_count = state_to_test.tile_heights.iter().flatten().map(|&height| height as usize).sum::<usize>();
+            let dome_count = state_to_test.tile_heights.iter().flatten().filter(|&&height| height == 4).count();
+            let mut player_a_heights = state_to_test.player_a_workers.unwrap().map(|tile| state_to_test.get_tile_height(tile as usize));
+            let mut player_b_heights = state_to_test.player_b_workers.unwrap().map(|tile| state_to_test.get_tile_height(tile as usize));
+            player_a_heights.sort_unstable();
+            player_b_heights.sort_unstable();
+            assert_eq!(block_count, 30);
+            assert_eq!(dome_count, 3);
+            assert_eq!(player_a_heights, [1, 2]);
+            assert_eq!(player_b_heights, [0, 2]);
+
+            let player_a_turn = state_to_test.player_a_turn;
+            let binary_state = GameState5x5Binary128bit::from_generic_game_state(&state_to_test);
+            let struct_state = GameState5x5Struct::from_generic_game_state(&state_to_test);
+            assert!(binary_state.get_children_states().iter().all(|child| if player_a_turn { !child.has_player_a_won() } else { !child.has_player_b_won() }), "{}", state_to_test);
+            assert!(struct_state.get_children_states().iter().all(|child| if player_a_turn { !child.has_player_a_won() } else { !child.has_player_b_won() }), "{}", state_to_test);
+        }
+
+        // The same seed must result in the same positions
+        let setup_constraints = PositionConstraints::new().with_phase(PositionPhase::Setup);
+        let setup_states = setup_constraints.generate_states::<GenericSantoriniGameState<5, 5, 2>>(1, tries).unwrap();
+        assert_eq!(setup_states, setup_constraints.generate_states::<GenericSantoriniGameState<5, 5, 2>>(1, tries).unwrap());
+        assert!(setup_states.iter().all(|state| state.player_b_workers.is_none()));
+
+        assert!(PositionConstraints::new().with_block_count(10).with_dome_count(3).generate_states::<GenericSantoriniGameState<5, 5, 2>>(0, 1).is_err());
+    }
+
+    #[allow(deprecated)]
+    fn find_symmetry_discrepancies(tries: usize) {
+        for _ in 0..tries {
+            let state_to_test = GenericSantoriniGameState::<5, 5, 2>::generate_random_state();
+            let canonical_position_string = state_to_test.to_canonical_position_string();
+            let child_count = GameState5x5Binary128bit::from_generic_game_state(&state_to_test).get_children_states().len();
+
+            // Symmetric states must be equivalent for the game, so they need the same canonical form and the same amount of moves
+            for symmetry in 0..GenericSantoriniGameState::<5, 5, 2>::get_symmetry_count() {
+                let symmetric_state = state_to_test.get_symmetric_state(symmetry);
+                assert_eq!(canonical_position_string, symmetric_state.to_canonical_position_string());
+                assert_eq!(child_count, GameState5x5Binary128bit::from_generic_game_state(&symmetric_state).get_children_states().len());
+                assert_eq!(state_to_test.can_player_win_immediately(true), symmetric_state.can_player_win_immediately(true));
+                assert_eq!(state_to_test.can_player_win_immediately(false), symmetric_state.can_player_win_immediately(false));
+            }
+
+            // The deprecated 4x4 representation uses the symmetries of GenericSantoriniGameState<4, 4, 1> for its canonical form
+            let state_4x4 = GenericSantoriniGameState::<4, 4, 1>::generate_random_state();
+            if state_4x4.player_a_workers.is_none() || state_4x4.player_b_workers.is_none() {
+                continue;
+            }
+            let old_state_4x4 = Generic4x4GameState::from_position_string(&state_4x4.to_position_string()).unwrap();
+            let canonical_position_string_4x4 = old_state_4x4.to_canonical_position_string();
+            for symmetry in 0..GenericSantoriniGameState::<4, 4, 1>::get_symmetry_count() {
+                let symmetric_state = state_4x4.get_symmetric_state(symmetry);
+                assert_eq!(canonical_position_string_4x4, symmetric_state.to_canonical_position_string());
+                let old_symmetric_state = Generic4x4GameState::from_position_string(&symmetric_state.to_position_string()).unwrap();
+                assert_eq!(canonical_position_string_4x4, old_symmetric_state.to_canonical_position_string());
+            }
+        }
+    }
+
     #[test]
     fn test_find_4x4_generic_discrepancies() {
         find_4x4_generic_discrepancies(100000);
@@ -322,4 +475,24 @@ mod tests {
     fn test_find_5x5_child_discrepancies() {
         find_5x5_child_discrepancies(10000);
     }
+
+    #[test]
+    fn test_find_child_move_discrepancies() {
+        find_child_move_discrepancies(1000);
+    }
+
+    #[test]
+    fn test_find_position_string_discrepancies() {
+        find_position_string_discrepancies(10000);
+    }
+
+    #[test]
+    fn test_find_constrained_position_discrepancies() {
+        find_constrained_position_discrepancies(1000);
+    }
+
+    #[test]
+    fn test_find_symmetry_discrepancies() {
+        find_symmetry_discrepancies(10000);
+    }
 }
\ No newline at end of file
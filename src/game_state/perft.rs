@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use anyhow::{ensure, Result};
+use crate::game_state::GameState;
+use crate::generic_game_state::move_notation::{find_move, get_move_notation};
+use crate::generic_game_state::GenericGameState;
+
+/*
+Perft counts the states that are reached after exactly depth moves (plies) from a position, which validates the children generation.
+States in which a player has won or that have no children end the game, so they only count if they are reached at the depth.
+ */
+pub fn perft<GS: GameState>(state: &GS, depth: usize) -> u64 {
+    // One vector per depth, so no vectors are allocated during the search
+    let mut children_vecs = vec![Vec::new(); depth];
+    return perft_with_vecs(state, &mut children_vecs);
+}
+
+fn perft_with_vecs<GS: GameState>(state: &GS, children_vecs: &mut [Vec<GS>]) -> u64 {
+    let Some((children, deeper_children_vecs)) = children_vecs.split_first_mut() else {
+        return 1;
+    };
+    if state.has_player_a_won() || state.has_player_b_won() {
+        return 0;
+    }
+
+    state.get_children_states_reuse_vec(children);
+    // The children at the last depth don't have to be generated to be counted
+    if deeper_children_vecs.is_empty() {
+        return children.len() as u64;
+    }
+    let mut count = 0;
+    for child in children.iter() {
+        count += perft_with_vecs(child, deeper_children_vecs);
+    }
+    return count;
+}
+
+// Perft of every child (at depth - 1), sorted by the notation of the moves so that the results of different representations can be compared
+pub fn perft_divide<GS: GameState>(state: &GS, depth: usize) -> Result<Vec<(String, u64)>> {
+    ensure!(depth > 0, "Perft divide needs a depth of at least 1");
+    if state.has_player_a_won() || state.has_player_b_won() {
+        return Ok(Vec::new());
+    }
+
+    let mut divide = Vec::new();
+    for child in state.get_children_states() {
+        divide.push((get_move_notation(state, &child)?, perft(&child, depth - 1)));
+    }
+    divide.sort();
+    return Ok(divide);
+}
+
+pub fn format_perft_divide(divide: &[(String, u64)]) -> String {
+    let mut result: String = divide.iter().map(|(notation, count)| format!("{}: {}\n", notation, count)).collect();
+    result.push_str(&format!("Total: {}\n", divide.iter().map(|(_, count)| count).sum::<u64>()));
+    return result;
+}
+
+// State in which the children of two representations differ
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PerftDiscrepancy {
+    // Moves from the compared position to the state
+    pub moves: Vec<String>,
+    pub position: String,
+    pub only_in_first: Vec<String>,
+    pub only_in_second: Vec<String>,
+}
+
+/*
+Localizes a perft difference between two representations: starting at the position, the divides of both are compared
+and the first child with different counts is followed until the children of a state differ.
+Returns None if both representations have the same counts up to the depth.
+ */
+pub fn find_perft_discrepancy<GS1: GameState, GS2: GameState<GenericGameState=GS1::GenericGameState>>(generic_state: &GS1::GenericGameState, depth: usize) -> Result<Option<PerftDiscrepancy>> {
+    let mut first_state = GS1::from_generic_game_state(generic_state);
+    let mut second_state = GS2::from_generic_game_state(generic_state);
+    let mut moves = Vec::new();
+
+    for remaining_depth in (1..=depth).rev() {
+        let first_divide: HashMap<String, u64> = perft_divide(&first_state, remaining_depth)?.into_iter().collect();
+        let second_divide: HashMap<String, u64> = perft_divide(&second_state, remaining_depth)?.into_iter().collect();
+
+        let mut only_in_first: Vec<String> = first_divide.keys().filter(|notation| !second_divide.contains_key(*notation)).cloned().collect();
+        let mut only_in_second: Vec<String> = second_divide.keys().filter(|notation| !first_divide.contains_key(*notation)).cloned().collect();
+        if !only_in_first.is_empty() || !only_in_second.is_empty() {
+            only_in_first.sort();
+            only_in_second.sort();
+            return Ok(Some(PerftDiscrepancy {
+                moves,
+                position: first_state.to_generic_game_state().to_position_string(),
+                only_in_first,
+                only_in_second,
+            }));
+        }
+
+        let mut differing_moves: Vec<&String> = first_divide.iter()
+            .filter(|(notation, count)| second_divide[*notation] != **count)
+            .map(|(notation, _)| notation)
+            .collect();
+        differing_moves.sort();
+        let Some(differing_move) = differing_moves.first() else {
+            return Ok(None);
+        };
+        first_state = find_move(&first_state, differing_move)?.1;
+        second_state = find_move(&second_state, differing_move)?.1;
+        moves.push(differing_move.to_string());
+    }
+    return Ok(None);
+}
+
+// Perft counts of a position for the depths 1, 2, ..., computed with all representations of the board size
+pub struct PerftReference {
+    pub name: &'static str,
+    pub position: &'static str,
+    pub counts: &'static [u64],
+}
+
+impl PerftReference {
+    pub fn get_state<GS: GameState>(&self) -> Result<GS> {
+        return Ok(GS::from_generic_game_state(&GS::GenericGameState::from_position_string(self.position)?));
+    }
+}
+
+pub const PERFT_REFERENCES_5X5: [PerftReference; 4] = [
+    PerftReference {
+        name: "start",
+        position: "0000000000000000000000000/-/-/A",
+        counts: &[300, 75900, 4313232, 237559488, 14380444776],
+    },
+    PerftReference {
+        name: "placed_workers",
+        position: "0000000000000000000000000/6,18/16,8/A",
+        counts: &[80, 6176, 426384, 29096316, 1874228956],
+    },
+    PerftReference {
+        name: "midgame_player_a",
+        position: "0001020424040212212210040/16,20/24,18/A",
+        counts: &[31, 762, 16270, 254998, 5475384, 96119671],
+    },
+    PerftReference {
+        name: "midgame_player_b",
+        position: "0002104013131322300103140/17,20/0,24/B",
+        counts: &[18, 245, 4687, 82841, 1746650, 27294658],
+    },
+];
+
+pub const PERFT_REFERENCES_4X4: [PerftReference; 4] = [
+    PerftReference {
+        name: "start",
+        position: "0000000000000000/-/-/A",
+        counts: &[16, 240, 6480, 163956, 5027508, 150179992, 4404024476],
+    },
+    PerftReference {
+        name: "placed_workers",
+        position: "0000000000000000/5/10/A",
+        counts: &[37, 1518, 43124, 1278457, 37648487, 1094146108],
+    },
+    PerftReference {
+        name: "midgame_player_a",
+        position: "2012101001101101/9/7/A",
+        counts: &[45, 934, 25219, 780531, 21388991, 544127786],
+    },
+    PerftReference {
+        name: "midgame_player_b",
+        position: "2100011030001200/7/10/B",
+        counts: &[35, 826, 21990, 565386, 13832667, 340441431, 8212050110],
+    },
+];
+
+// Checks the perft counts of the representation against the references up to max_depth
+pub fn verify_perft_references<GS: GameState>(references: &[PerftReference], max_depth: usize) -> Result<()> {
+    for reference in references {
+        let state = reference.get_state::<GS>()?;
+        for (depth, expected_count) in (1..=max_depth).zip(reference.counts) {
+            let count = perft(&state, depth);
+            ensure!(count == *expected_count, "Perft of {} at depth {} is {} instead of {}", reference.name, depth, count, expected_count);
+        }
+    }
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+    use crate::game_state::game_state_4x4_binary_3bit::GameState4x4Binary3Bit;
+    use crate::game_state::game_state_4x4_binary_4bit::GameState4x4Binary4Bit;
+    use crate::game_state::game_state_4x4_struct::GameState4x4Struct;
+    use crate::game_state::game_state_5x5_5bit::GameState5x5Binary5bit;
+    use crate::game_state::game_state_5x5_binary_128bit::GameState5x5Binary128bit;
+    use crate::game_state::game_state_5x5_binary_composite::GameState5x5BinaryComposite;
+    use crate::game_state::game_state_5x5_struct::GameState5x5Struct;
+    use crate::game_state::perft::{find_perft_discrepancy, format_perft_divide, perft, perft_divide, verify_perft_references, PERFT_REFERENCES_4X4, PERFT_REFERENCES_5X5};
+    use crate::game_state::GameState;
+    use crate::generic_game_state::generic_santorini_game_state::GenericSantoriniGameState;
+    use crate::generic_game_state::GenericGameState;
+
+    #[test]
+    fn test_every_representation_matches_the_perft_references() {
+        verify_perft_references::<GameState4x4Struct>(&PERFT_REFERENCES_4X4, 4).unwrap();
+        verify_perft_references::<GameState4x4Binary3Bit>(&PERFT_REFERENCES_4X4, 4).unwrap();
+        verify_perft_references::<GameState4x4Binary4Bit>(&PERFT_REFERENCES_4X4, 4).unwrap();
+        verify_perft_references::<GameState5x5Struct>(&PERFT_REFERENCES_5X5, 3).unwrap();
+        verify_perft_references::<GameState5x5Binary128bit>(&PERFT_REFERENCES_5X5, 3).unwrap();
+        verify_perft_references::<GameState5x5BinaryComposite>(&PERFT_REFERENCES_5X5, 3).unwrap();
+        verify_perft_references::<GameState5x5Binary5bit>(&PERFT_REFERENCES_5X5, 3).unwrap();
+    }
+
+    #[test]
+    fn test_representations_have_the_same_perft_on_random_positions() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..200 {
+            let block_count_4x4 = rng.gen_range(0..=30);
+            let state_4x4 = GenericSantoriniGameState::<4, 4, 1>::generate_random_state_with_blocks_rng(&mut rng, block_count_4x4);
+            assert_eq!(find_perft_discrepancy::<GameState4x4Struct, GameState4x4Binary3Bit>(&state_4x4, 3).unwrap(), None);
+            assert_eq!(find_perft_discrepancy::<GameState4x4Struct, GameState4x4Binary4Bit>(&state_4x4, 3).unwrap(), None);
+
+            let block_count_5x5 = rng.gen_range(0..=50);
+            let state_5x5 = GenericSantoriniGameState::<5, 5, 2>::generate_random_state_with_blocks_rng(&mut rng, block_count_5x5);
+            assert_eq!(find_perft_discrepancy::<GameState5x5Struct, GameState5x5Binary128bit>(&state_5x5, 2).unwrap(), None);
+            assert_eq!(find_perft_discrepancy::<GameState5x5Struct, GameState5x5BinaryComposite>(&state_5x5, 2).unwrap(), None);
+            assert_eq!(find_perft_discrepancy::<GameState5x5Struct, GameState5x5Binary5bit>(&state_5x5, 2).unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn test_divide_adds_up_to_the_perft() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..50 {
+            let block_count = rng.gen_range(0..=50);
+            let generic_state = GenericSantoriniGameState::<5, 5, 2>::generate_random_state_with_blocks_rng(&mut rng, block_count);
+            let state = GameState5x5BinaryComposite::from_generic_game_state(&generic_state);
+            let divide = perft_divide(&state, 2).unwrap();
+            let divide_total: u64 = divide.iter().map(|(_, count)| count).sum();
+            assert_eq!(divide_total, perft(&state, 2));
+            assert!(format_perft_divide(&divide).ends_with(&format!("Total: {}\n", divide_total)));
+        }
+        assert!(perft_divide(&PERFT_REFERENCES_5X5[0].get_state::<GameState5x5BinaryComposite>().unwrap(), 0).is_err());
+    }
+
+    #[test]
+    fn test_won_positions_only_count_at_depth_0() {
+        // Player B has won on height 3
+        let generic_state = GenericSantoriniGameState::<4, 4, 1>::from_position_string("4000030000000001/10/5/A").unwrap();
+        let state = GameState4x4Binary4Bit::from_generic_game_state(&generic_state);
+        assert_eq!(perft(&state, 0), 1);
+        assert_eq!(perft(&state, 2), 0);
+        assert_eq!(perft_divide(&state, 2).unwrap(), Vec::new());
+        assert_eq!(find_perft_discrepancy::<GameState4x4Struct, GameState4x4Binary4Bit>(&generic_state, 2).unwrap(), None);
+    }
+}
@@ -1,4 +1,5 @@
 pub mod precompute_position_to_tile_id;
 pub mod get_binomial_coefficient;
 pub mod child_evaluation;
-pub mod symmetric_simplified;
\ No newline at end of file
+pub mod symmetric_simplified;
+pub mod child_move;
\ No newline at end of file
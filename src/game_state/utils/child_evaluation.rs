@@ -6,6 +6,7 @@ pub mod gs4x4_child_evaluation {
     0  1  2  4
      */
     use crate::game_state::SantoriniState4x4;
+    use crate::minimax::score::win_score;
 
     const TILE_ID_TO_POSITION: [usize; 16] = [0, 1, 2, 4, 14, 3, 7, 5, 13, 15, 11, 6, 12, 10, 9, 8];
 
@@ -62,16 +63,21 @@ pub mod gs4x4_child_evaluation {
         let worker_a_position = state.worker_a_position as usize;
         let worker_b_position = state.worker_b_position as usize;
 
-        if worker_a_position < 16 && state.position_heights[worker_a_position] == 3 {
-            return if state.player_a_turn { f32::MAX } else { f32::MIN };
-        } else if worker_b_position < 16 && state.position_heights[worker_b_position] == 3 {
-            return if state.player_a_turn { f32::MIN } else { f32::MAX };
+        // The valuation table only has the 16 tiles, so an unplaced worker would index it out of bounds.
+        // Like for 5x5, positions of the setup stage are not evaluated.
+        if worker_a_position == 16 || worker_b_position == 16 {
+            return 0.0;
+        }
+
+        if state.position_heights[worker_a_position] == 3 {
+            return win_score(state.player_a_turn);
+        } else if state.position_heights[worker_b_position] == 3 {
+            return win_score(!state.player_a_turn);
         }
 
         let mut valuation = 0.0;
 
         // TODO: Consider whose turn it is
-        // TODO: Consider unplaced workers
 
         for i in 0..16 {
             valuation += POSITION_TO_POSITION_TO_HEIGHT_TO_HEIGHT_TO_VALUATION[worker_a_position][i][state.position_heights[worker_a_position] as usize][state.position_heights[i] as usize];
@@ -284,4 +290,31 @@ pub mod gs5x5_child_evaluation {
 
         return valuation;
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::game_state_4x4_binary_3bit::GameState4x4Binary3Bit;
+    use crate::game_state::game_state_4x4_binary_4bit::GameState4x4Binary4Bit;
+    use crate::game_state::game_state_4x4_struct::GameState4x4Struct;
+    use crate::game_state::{GameState, SantoriniEval};
+    use crate::generic_game_state::GenericGameState;
+
+    fn get_child_evaluations(position_string: &str) -> [f32; 3] {
+        let generic_state = <GameState4x4Struct as GameState>::GenericGameState::from_position_string(position_string).unwrap();
+        return [
+            GameState4x4Struct::from_generic_game_state(&generic_state).get_child_evaluation(),
+            GameState4x4Binary3Bit::from_generic_game_state(&generic_state).get_child_evaluation(),
+            GameState4x4Binary4Bit::from_generic_game_state(&generic_state).get_child_evaluation(),
+        ];
+    }
+
+    #[test]
+    fn test_4x4_positions_with_unplaced_workers_are_not_evaluated() {
+        for position_string in ["0000000000000000/-/-/A", "0000000000000000/9/-/B"] {
+            assert_eq!(get_child_evaluations(position_string), [0.0; 3], "{}", position_string);
+        }
+        // Once both workers are placed, the position is evaluated again
+        assert!(get_child_evaluations("0000000000000000/0/5/A").iter().all(|&evaluation| evaluation != 0.0));
+    }
+}
@@ -0,0 +1,55 @@
+use crate::game_state::SantoriniMove;
+
+// Moved workers are identified by comparing the worker tiles of the moving player, the build by comparing the heights
+pub fn get_child_move<const TILE_COUNT: usize, const WORKERS_PER_PLAYER: usize>(
+    parent_heights: &[u8; TILE_COUNT],
+    child_heights: &[u8; TILE_COUNT],
+    parent_workers: &[u8; WORKERS_PER_PLAYER],
+    child_workers: &[u8; WORKERS_PER_PLAYER],
+) -> SantoriniMove {
+    // Workers that are not placed are encoded with values outside the board
+    if parent_workers.iter().all(|&tile| tile as usize >= TILE_COUNT) {
+        let mut placed_tiles = child_workers.iter().filter(|&&tile| (tile as usize) < TILE_COUNT);
+        return SantoriniMove {
+            worker_from: SantoriniMove::NO_TILE,
+            worker_to: *placed_tiles.next().unwrap_or(&SantoriniMove::NO_TILE),
+            build: *placed_tiles.next().unwrap_or(&SantoriniMove::NO_TILE),
+        };
+    }
+
+    let worker_from = parent_workers.iter().find(|tile| !child_workers.contains(tile)).copied().unwrap_or(SantoriniMove::NO_TILE);
+    let worker_to = child_workers.iter().find(|tile| !parent_workers.contains(tile)).copied().unwrap_or(SantoriniMove::NO_TILE);
+    let build = (0..TILE_COUNT).find(|&tile| child_heights[tile] > parent_heights[tile]).map(|tile| tile as u8).unwrap_or(SantoriniMove::NO_TILE);
+
+    return SantoriniMove {
+        worker_from,
+        worker_to,
+        build,
+    };
+}
+
+pub mod gs4x4_child_move {
+    use crate::game_state::{SantoriniMove, SantoriniState4x4};
+
+    pub fn get_child_move(parent: SantoriniState4x4, child: SantoriniState4x4) -> SantoriniMove {
+        let (parent_worker, child_worker) = if parent.player_a_turn {
+            (parent.worker_a_position, child.worker_a_position)
+        } else {
+            (parent.worker_b_position, child.worker_b_position)
+        };
+        return super::get_child_move(&parent.position_heights, &child.position_heights, &[parent_worker], &[child_worker]);
+    }
+}
+
+pub mod gs5x5_child_move {
+    use crate::game_state::{SantoriniMove, SantoriniState5x5};
+
+    pub fn get_child_move(parent: SantoriniState5x5, child: SantoriniState5x5) -> SantoriniMove {
+        let (parent_workers, child_workers) = if parent.player_a_turn {
+            (parent.worker_a_tiles, child.worker_a_tiles)
+        } else {
+            (parent.worker_b_tiles, child.worker_b_tiles)
+        };
+        return super::get_child_move(&parent.tile_heights, &child.tile_heights, &parent_workers, &child_workers);
+    }
+}
@@ -1,9 +1,59 @@
+use crate::generic_game_state::position_constraints::PositionConstraints;
+
 pub trait GenericGameState : std::fmt::Display + std::fmt::Debug {
+    const ROWS: usize;
+    const COLUMNS: usize;
+    const WORKERS_PER_PLAYER: usize;
+
+    // Height of the tile, 4 for a dome
+    fn get_tile_height(&self, tile_id: usize) -> u8;
+    // 'A' or 'B' for the workers of the players, ' ' for empty tiles
+    fn get_character_on_tile(&self, tile_id: usize) -> char;
+
     fn generate_random_state() -> Self;
     fn generate_random_state_rng<RNG: rand::Rng>(rng: &mut RNG) -> Self;
     fn generate_random_state_with_blocks(block_amount: usize) -> Self;
     fn generate_random_state_with_blocks_rng<RNG: rand::Rng>(rng: &mut RNG, block_amount: usize) -> Self;
+    // True if the given player could move a worker up to height 3 if it was their turn
+    fn can_player_win_immediately(&self, player_a: bool) -> bool;
+    // Not every representation supports constraints, these return an error
+    fn generate_constrained_state_rng<RNG: rand::Rng>(_rng: &mut RNG, _constraints: &PositionConstraints) -> anyhow::Result<Self> where Self: Sized {
+        anyhow::bail!("Constrained position generation is not supported for this game state");
+    }
+
+    /*
+    Compact single line representation of a state: the tile heights row by row, the worker tiles of both players
+    ("-" if not placed) and the player to move, separated by "/". Example for 4x4: "0000100002000000/5/10/B"
+     */
+    fn to_position_string(&self) -> String;
+    fn from_position_string(position_string: &str) -> anyhow::Result<Self> where Self: Sized;
+    // Position string that is identical for all positions that are symmetric to each other (rotations and reflections)
+    fn to_canonical_position_string(&self) -> String;
+
+    // See move_notation for the format, the child has to be reachable from this state with a single move
+    fn get_move_notation(&self, child: &Self) -> anyhow::Result<String> where Self: Sized;
+    fn normalize_move_notation(notation: &str) -> anyhow::Result<String> where Self: Sized;
+}
+
+// Parses the worker tiles of a position string, returns None if the workers are not placed
+pub(crate) fn parse_position_string_workers<const WORKERS_PER_PLAYER: usize>(workers_string: &str) -> anyhow::Result<Option<[u8; WORKERS_PER_PLAYER]>> {
+    if workers_string == "-" {
+        return Ok(None);
+    }
+    let worker_tiles = workers_string.split(',').map(|tile| tile.trim().parse::<u8>()).collect::<Result<Vec<u8>, _>>()?;
+    let worker_tiles: [u8; WORKERS_PER_PLAYER] = worker_tiles.try_into()
+        .map_err(|tiles: Vec<u8>| anyhow::anyhow!("Expected {} worker tiles, got {}", WORKERS_PER_PLAYER, tiles.len()))?;
+    return Ok(Some(worker_tiles));
+}
+
+pub(crate) fn format_position_string_workers(workers: Option<&[u8]>) -> String {
+    return match workers {
+        Some(workers) => workers.iter().map(|tile| tile.to_string()).collect::<Vec<String>>().join(","),
+        None => "-".to_string(),
+    };
 }
 
 pub mod generic_4x4_game_state;
-pub mod generic_santorini_game_state;
\ No newline at end of file
+pub mod generic_santorini_game_state;
+pub mod position_constraints;
+pub mod move_notation;
\ No newline at end of file
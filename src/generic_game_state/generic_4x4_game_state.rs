@@ -1,7 +1,8 @@
 use std::fmt;
 use std::fmt::Formatter;
 use anyhow::{ensure, Result};
-use crate::generic_game_state::GenericGameState;
+use crate::generic_game_state::{format_position_string_workers, parse_position_string_workers, GenericGameState};
+use crate::generic_game_state::generic_santorini_game_state::GenericSantoriniGameState;
 
 #[derive(Eq, PartialEq, Ord, PartialOrd)]
 #[deprecated] // In favor of GenericSantoriniGameState<4, 4, 1>
@@ -62,6 +63,24 @@ impl Generic4x4GameState {
 
 #[allow(deprecated)]
 impl GenericGameState for Generic4x4GameState {
+    const ROWS: usize = 4;
+    const COLUMNS: usize = 4;
+    const WORKERS_PER_PLAYER: usize = 1;
+
+    fn get_tile_height(&self, tile_id: usize) -> u8 {
+        return self.tile_heights[tile_id];
+    }
+
+    fn get_character_on_tile(&self, tile_id: usize) -> char {
+        if self.player_a_tile as usize == tile_id {
+            return 'A';
+        }
+        if self.player_b_tile as usize == tile_id {
+            return 'B';
+        }
+        return ' ';
+    }
+
     fn generate_random_state() -> Generic4x4GameState {
         return Self::generate_random_state_rng(&mut rand::thread_rng());
     }
@@ -125,4 +144,74 @@ impl GenericGameState for Generic4x4GameState {
 
         return Generic4x4GameState::new(player_a_tile, player_b_tile, tile_heights).expect("Randomly generated invalid game state");
     }
+
+    // The player to move is not stored, so it is derived from the block count like for GenericSantoriniGameState
+    fn to_position_string(&self) -> String {
+        let heights: String = self.tile_heights.iter().map(|height| char::from(b'0' + height)).collect();
+        let block_count: usize = self.tile_heights.iter().map(|&height| height as usize).sum();
+        return format!(
+            "{}/{}/{}/{}",
+            heights,
+            format_position_string_workers(Some(&[self.player_a_tile])),
+            format_position_string_workers(Some(&[self.player_b_tile])),
+            if block_count % 2 == 0 { "A" } else { "B" }
+        );
+    }
+
+    fn from_position_string(position_string: &str) -> Result<Self> {
+        let parts: Vec<&str> = position_string.trim().split('/').collect();
+        ensure!(parts.len() == 4, "Position string must have 4 parts separated by '/', got {}", parts.len());
+        ensure!(parts[0].len() == 16, "Position string must have 16 tile heights, got {}", parts[0].len());
+
+        let mut tile_heights = [0; 16];
+        for (tile_id, height_char) in parts[0].chars().enumerate() {
+            let height = height_char.to_digit(10).ok_or_else(|| anyhow::anyhow!("Invalid tile height '{}'", height_char))?;
+            tile_heights[tile_id] = height as u8;
+        }
+
+        let player_a_tile = parse_position_string_workers::<1>(parts[1])?.ok_or_else(|| anyhow::anyhow!("Player A worker must be placed"))?[0];
+        let player_b_tile = parse_position_string_workers::<1>(parts[2])?.ok_or_else(|| anyhow::anyhow!("Player B worker must be placed"))?[0];
+        return Generic4x4GameState::new(player_a_tile, player_b_tile, tile_heights);
+    }
+
+    // Both representations share the position string format, so the symmetries of the current representation are used
+    fn to_canonical_position_string(&self) -> String {
+        return match GenericSantoriniGameState::<4, 4, 1>::from_position_string(&self.to_position_string()) {
+            Ok(state) => state.to_canonical_position_string(),
+            Err(_) => self.to_position_string(),
+        };
+    }
+
+    fn get_move_notation(&self, child: &Self) -> Result<String> {
+        let state = GenericSantoriniGameState::<4, 4, 1>::from_position_string(&self.to_position_string())?;
+        let child_state = GenericSantoriniGameState::<4, 4, 1>::from_position_string(&child.to_position_string())?;
+        return state.get_move_notation(&child_state);
+    }
+
+    fn normalize_move_notation(notation: &str) -> Result<String> {
+        return GenericSantoriniGameState::<4, 4, 1>::normalize_move_notation(notation);
+    }
+
+    fn can_player_win_immediately(&self, player_a: bool) -> bool {
+        let (worker_tile, other_worker_tile) = if player_a {
+            (self.player_a_tile as usize, self.player_b_tile as usize)
+        } else {
+            (self.player_b_tile as usize, self.player_a_tile as usize)
+        };
+        if self.tile_heights[worker_tile] != 2 {
+            return false;
+        }
+
+        let row = worker_tile / 4;
+        let column = worker_tile % 4;
+        for neighbor_row in row.saturating_sub(1)..=(row + 1).min(3) {
+            for neighbor_column in column.saturating_sub(1)..=(column + 1).min(3) {
+                let neighbor_tile = neighbor_row * 4 + neighbor_column;
+                if neighbor_tile != other_worker_tile && self.tile_heights[neighbor_tile] == 3 {
+                    return true;
+                }
+            }
+        }
+        return false;
+    }
 }
\ No newline at end of file
@@ -4,8 +4,17 @@ use std::fmt;
 use std::fmt::Formatter;
 use anyhow::{bail, ensure, Result};
 use plotters::prelude::*;
+use plotters::coord::Shift;
 use plotters::style::text_anchor::{HPos, Pos, VPos};
-use crate::generic_game_state::GenericGameState;
+use crate::generic_game_state::{format_position_string_workers, parse_position_string_workers, GenericGameState};
+use crate::generic_game_state::position_constraints::PositionConstraints;
+
+const PIXELS_PER_TILE: usize = 100;
+const PADDING_BETWEEN_TILES: usize = 5;
+const BORDER_WIDTH: usize = 5;
+const CAPTION_HEIGHT: usize = 50;
+pub const PLAYER_A_COLOR: RGBColor = RGBColor(50, 59, 237);
+pub const PLAYER_B_COLOR: RGBColor = RGBColor(237, 50, 75);
 
 #[derive(Debug)]
 pub struct GenericSantoriniGameState<const ROWS: usize, const COLUMNS: usize, const WORKERS_PER_PLAYER: usize> {
@@ -183,6 +192,50 @@ impl<const ROWS: usize, const COLUMNS: usize, const WORKERS_PER_PLAYER: usize> G
         return false;
     }
 
+    // Rotations and reflections of the board, transposing symmetries are only possible on square boards
+    pub fn get_symmetry_count() -> usize {
+        return if ROWS == COLUMNS { 8 } else { 4 };
+    }
+
+    // Symmetry 0 is the identity, 1 and 2 mirror the rows and columns, 3 rotates by 180 degrees and 4 to 7 also transpose the board
+    pub fn get_symmetric_tile(tile_id: usize, symmetry: usize) -> usize {
+        let row = tile_id / COLUMNS;
+        let column = tile_id % COLUMNS;
+        let mirrored_row = if symmetry & 1 != 0 { ROWS - 1 - row } else { row };
+        let mirrored_column = if symmetry & 2 != 0 { COLUMNS - 1 - column } else { column };
+        return if symmetry & 4 != 0 {
+            mirrored_column * COLUMNS + mirrored_row
+        } else {
+            mirrored_row * COLUMNS + mirrored_column
+        };
+    }
+
+    // The workers of each player are sorted, so that symmetric states are equal regardless of the worker order
+    pub fn get_symmetric_state(&self, symmetry: usize) -> GenericSantoriniGameState<ROWS, COLUMNS, WORKERS_PER_PLAYER> {
+        assert!(symmetry < Self::get_symmetry_count(), "Symmetry {} is not possible for a {}x{} board", symmetry, ROWS, COLUMNS);
+
+        let mut tile_heights = [[0; COLUMNS]; ROWS];
+        for tile_id in 0..ROWS * COLUMNS {
+            let symmetric_tile = Self::get_symmetric_tile(tile_id, symmetry);
+            tile_heights[symmetric_tile / COLUMNS][symmetric_tile % COLUMNS] = self.get_tile_height(tile_id);
+        }
+
+        let get_symmetric_workers = |workers: Option<[u8; WORKERS_PER_PLAYER]>| {
+            return workers.map(|workers| {
+                let mut symmetric_workers = workers.map(|tile| Self::get_symmetric_tile(tile as usize, symmetry) as u8);
+                symmetric_workers.sort_unstable();
+                return symmetric_workers;
+            });
+        };
+
+        return GenericSantoriniGameState {
+            player_a_turn: self.player_a_turn,
+            player_a_workers: get_symmetric_workers(self.player_a_workers),
+            player_b_workers: get_symmetric_workers(self.player_b_workers),
+            tile_heights,
+        };
+    }
+
     pub fn get_character_on_tile(&self, tile_id: usize) -> char {
         if self.player_a_workers.iter().flatten().any(|&x| x as usize == tile_id) {
             return 'A';
@@ -197,85 +250,110 @@ impl<const ROWS: usize, const COLUMNS: usize, const WORKERS_PER_PLAYER: usize> G
 
 impl<const ROWS: usize, const COLUMNS: usize, const WORKERS_PER_PLAYER: usize> GenericSantoriniGameState<ROWS, COLUMNS, WORKERS_PER_PLAYER> {
     pub fn draw_image(&self, path: &str) -> Result<()> {
-        let pixels_per_tile = 100;
-        let padding_between_tiles = 5;
-        let border_width = 5;
+        let root = SVGBackend::new(path, Self::get_image_size()).into_drawing_area();
+        self.draw_on(&root, &format!("Turn: Player {}", if self.player_a_turn { "A" } else { "B" }))?;
+        root.present()?;
 
-        let width = COLUMNS * pixels_per_tile + (COLUMNS + 1) * padding_between_tiles;
-        let height = ROWS * pixels_per_tile + (ROWS + 1) * padding_between_tiles + 50;
+        return Ok(());
+    }
 
-        let root = SVGBackend::new(path, (width as u32, height as u32)).into_drawing_area();
+    // Size of the images of draw_image, the caption is below the tiles
+    pub fn get_image_size() -> (u32, u32) {
+        let width = COLUMNS * PIXELS_PER_TILE + (COLUMNS + 1) * PADDING_BETWEEN_TILES;
+        let height = ROWS * PIXELS_PER_TILE + (ROWS + 1) * PADDING_BETWEEN_TILES + CAPTION_HEIGHT;
+        return (width as u32, height as u32);
+    }
 
-        for x in 0..ROWS {
-            for y in 0..COLUMNS {
-                let tile_id = x * COLUMNS + y;
-                let height = self.get_tile_height(tile_id);
-                let character = self.get_character_on_tile(tile_id);
+    // Top left corner of the tile in the image, the first row is at the bottom
+    pub fn get_tile_position(tile_id: usize) -> (i32, i32) {
+        let row = tile_id / COLUMNS;
+        let column = tile_id % COLUMNS;
+        let x = column * PIXELS_PER_TILE + (column + 1) * PADDING_BETWEEN_TILES;
+        let y = (ROWS - row - 1) * PIXELS_PER_TILE + (ROWS - row) * PADDING_BETWEEN_TILES;
+        return (x as i32, y as i32);
+    }
 
-                // Adjust x and y for drawing rectangles
-                let rect_x = (y * pixels_per_tile + (y + 1) * padding_between_tiles) as i32;
-                let rect_y = ((ROWS - x - 1) * pixels_per_tile + (ROWS - x) * padding_between_tiles) as i32;
-
-                let color = match height {
-                    0 => RGBColor(255, 255, 255),
-                    1 => RGBColor(224, 194, 157),
-                    2 => RGBColor(224, 178, 123),
-                    3 => RGBColor(214, 153, 77),
-                    _ => RGBColor(110, 102, 93),
-                };
-
-                let border_color = match character {
-                    'A' => RGBColor(50, 59, 237),
-                    'B' => RGBColor(237, 50, 75),
-                    _ => RGBColor(0, 0, 0),
-                };
-
-                let text_color = BLACK;
-
-                // Outer rectangle (border)
-                let outer_rect = Rectangle::new(
-                    [(rect_x, rect_y), (rect_x + pixels_per_tile as i32, rect_y + pixels_per_tile as i32)],
-                    ShapeStyle {
-                        color: border_color.to_rgba(),
-                        filled: true,
-                        stroke_width: 1,
-                    },
-                );
-
-                // Inner filled rectangle
-                let inner_rect = Rectangle::new(
-                    [(rect_x + border_width as i32, rect_y + border_width as i32), (rect_x + (pixels_per_tile - border_width) as i32, rect_y + (pixels_per_tile - border_width) as i32)],
-                    ShapeStyle {
-                        color: color.to_rgba(),
-                        filled: true,
-                        stroke_width: 0,
-                    },
-                );
-
-                // Height text in the center
-                let height_text = Text::new(
-                    format!("{}", height),
-                    (rect_x + (pixels_per_tile / 2) as i32, rect_y + (pixels_per_tile / 2) as i32),
-                    ("Arial", 40.0).into_font().color(&text_color).pos(Pos::new(HPos::Center, VPos::Center)),
-                );
-
-                // Character text in the center (if any)
-                let char_text = Text::new(
-                    format!("{}", character),
-                    (rect_x + (pixels_per_tile / 2) as i32, rect_y + (pixels_per_tile / 2) as i32 + 25),
-                    ("Arial", 30.0).into_font().color(&text_color).pos(Pos::new(HPos::Center, VPos::Center)),
-                );
-
-                root.draw(&outer_rect)?;
-                root.draw(&inner_rect)?;
-                root.draw(&height_text)?;
-                root.draw(&char_text)?;
-            }
+    // Draws a rectangle inside the border of the tile, which shows the worker on it, so the highlight doesn't hide the worker
+    pub fn draw_tile_highlight<DB: DrawingBackend, S: Into<ShapeStyle>>(root: &DrawingArea<DB, Shift>, tile_id: usize, style: S) -> Result<()> where DB::ErrorType: 'static {
+        let (x, y) = Self::get_tile_position(tile_id);
+        let inset = 2 * BORDER_WIDTH as i32;
+        let pixels_per_tile = PIXELS_PER_TILE as i32;
+        root.draw(&Rectangle::new([(x + inset, y + inset), (x + pixels_per_tile - inset, y + pixels_per_tile - inset)], style))?;
+        return Ok(());
+    }
+
+    // Draws the board in the top left corner of the drawing area, which needs at least the size of get_image_size
+    pub fn draw_on<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>, caption: &str) -> Result<()> where DB::ErrorType: 'static {
+        let (width, height) = Self::get_image_size();
+        let (width, height) = (width as i32, height as i32);
+        let pixels_per_tile = PIXELS_PER_TILE as i32;
+        let border_width = BORDER_WIDTH as i32;
+
+        for tile_id in 0..ROWS * COLUMNS {
+            let height = self.get_tile_height(tile_id);
+            let character = self.get_character_on_tile(tile_id);
+            let (rect_x, rect_y) = Self::get_tile_position(tile_id);
+
+            let color = match height {
+                0 => RGBColor(255, 255, 255),
+                1 => RGBColor(224, 194, 157),
+                2 => RGBColor(224, 178, 123),
+                3 => RGBColor(214, 153, 77),
+                _ => RGBColor(110, 102, 93),
+            };
+
+            let border_color = match character {
+                'A' => PLAYER_A_COLOR,
+                'B' => PLAYER_B_COLOR,
+                _ => RGBColor(0, 0, 0),
+            };
+
+            let text_color = BLACK;
+
+            // Outer rectangle (border)
+            let outer_rect = Rectangle::new(
+                [(rect_x, rect_y), (rect_x + pixels_per_tile, rect_y + pixels_per_tile)],
+                ShapeStyle {
+                    color: border_color.to_rgba(),
+                    filled: true,
+                    stroke_width: 1,
+                },
+            );
+
+            // Inner filled rectangle
+            let inner_rect = Rectangle::new(
+                [(rect_x + border_width, rect_y + border_width), (rect_x + pixels_per_tile - border_width, rect_y + pixels_per_tile - border_width)],
+                ShapeStyle {
+                    color: color.to_rgba(),
+                    filled: true,
+                    stroke_width: 0,
+                },
+            );
+
+            // Height text in the center
+            let height_text = Text::new(
+                format!("{}", height),
+                (rect_x + pixels_per_tile / 2, rect_y + pixels_per_tile / 2),
+                ("Arial", 40.0).into_font().color(&text_color).pos(Pos::new(HPos::Center, VPos::Center)),
+            );
+
+            // Character text in the center (if any)
+            let char_text = Text::new(
+                format!("{}", character),
+                (rect_x + pixels_per_tile / 2, rect_y + pixels_per_tile / 2 + 25),
+                ("Arial", 30.0).into_font().color(&text_color).pos(Pos::new(HPos::Center, VPos::Center)),
+            );
+
+            root.draw(&outer_rect)?;
+            root.draw(&inner_rect)?;
+            root.draw(&height_text)?;
+            root.draw(&char_text)?;
         }
 
         // Draw white box behind text
+        let text_half_width = (caption.len() as i32 * 15 / 2).max(110);
         let text_background = Rectangle::new(
-            [(width as i32 / 2 - 110, height as i32 - 45), (width as i32 / 2 + 110, height as i32 - 5)],
+            [(width / 2 - text_half_width, height - 45), (width / 2 + text_half_width, height - 5)],
             ShapeStyle {
                 color: RGBColor(255, 255, 255).to_rgba(),
                 filled: true,
@@ -283,16 +361,14 @@ impl<const ROWS: usize, const COLUMNS: usize, const WORKERS_PER_PLAYER: usize> G
             },
         );
         root.draw(&text_background)?;
-        // At the bottom, display the current player's turn
+        // At the bottom, display the caption (e.g. the current player's turn)
         let text = Text::new(
-            format!("Turn: Player {}", if self.player_a_turn { "A" } else { "B" }),
-            (width as i32 / 2, height as i32 - 25),
+            caption.to_string(),
+            (width / 2, height - 25),
             ("Arial", 40.0).into_font().color(&BLACK).pos(Pos::new(HPos::Center, VPos::Center)),
         );
         root.draw(&text)?;
 
-        root.present()?;
-
         return Ok(());
     }
 }
@@ -331,7 +407,7 @@ fn count_configurations(
 }
 
 
-fn sample_heights(
+pub(crate) fn sample_heights(
     tile_index: usize,
     remaining_blocks: usize,
     max_heights: &[usize],
@@ -386,7 +462,7 @@ fn sample_heights(
 
 
 impl<const ROWS: usize, const COLUMNS: usize, const WORKERS_PER_PLAYER: usize> GenericSantoriniGameState<ROWS, COLUMNS, WORKERS_PER_PLAYER> {
-    fn get_random_worker_positions<RNG: rand::Rng>(rng: &mut RNG) -> ([u8; WORKERS_PER_PLAYER], [u8; WORKERS_PER_PLAYER]) {
+    pub(crate) fn get_random_worker_positions<RNG: rand::Rng>(rng: &mut RNG) -> ([u8; WORKERS_PER_PLAYER], [u8; WORKERS_PER_PLAYER]) {
         let mut player_a_workers = [0; WORKERS_PER_PLAYER];
         let mut player_b_workers = [0; WORKERS_PER_PLAYER];
 
@@ -407,6 +483,19 @@ impl<const ROWS: usize, const COLUMNS: usize, const WORKERS_PER_PLAYER: usize> G
 }
 
 impl<const ROWS: usize, const COLUMNS: usize, const WORKERS_PER_PLAYER: usize> GenericGameState for GenericSantoriniGameState<ROWS, COLUMNS, WORKERS_PER_PLAYER> {
+    const ROWS: usize = ROWS;
+    const COLUMNS: usize = COLUMNS;
+    const WORKERS_PER_PLAYER: usize = WORKERS_PER_PLAYER;
+
+    // The inherent methods are used, so they are also available without the trait
+    fn get_tile_height(&self, tile_id: usize) -> u8 {
+        return GenericSantoriniGameState::get_tile_height(self, tile_id);
+    }
+
+    fn get_character_on_tile(&self, tile_id: usize) -> char {
+        return GenericSantoriniGameState::get_character_on_tile(self, tile_id);
+    }
+
     fn generate_random_state() -> GenericSantoriniGameState<ROWS, COLUMNS, WORKERS_PER_PLAYER> {
         return Self::generate_random_state_rng(&mut rand::thread_rng());
     }
@@ -474,4 +563,84 @@ impl<const ROWS: usize, const COLUMNS: usize, const WORKERS_PER_PLAYER: usize> G
         return GenericSantoriniGameState::new(Some(player_a_workers), Some(player_b_workers), tile_heights, player_a_turn)
             .expect("Randomly generated invalid game state, this should not be possible");
     }
+
+    fn generate_constrained_state_rng<RNG: rand::Rng>(rng: &mut RNG, constraints: &PositionConstraints) -> Result<Self> {
+        return Self::generate_constrained_state_with_rng(rng, constraints);
+    }
+
+    fn to_position_string(&self) -> String {
+        let heights: String = self.tile_heights.iter().flatten().map(|height| char::from(b'0' + height)).collect();
+        return format!(
+            "{}/{}/{}/{}",
+            heights,
+            format_position_string_workers(self.player_a_workers.as_ref().map(|workers| workers.as_slice())),
+            format_position_string_workers(self.player_b_workers.as_ref().map(|workers| workers.as_slice())),
+            if self.player_a_turn { "A" } else { "B" }
+        );
+    }
+
+    fn from_position_string(position_string: &str) -> Result<Self> {
+        let parts: Vec<&str> = position_string.trim().split('/').collect();
+        ensure!(parts.len() == 4, "Position string must have 4 parts separated by '/', got {}", parts.len());
+        ensure!(parts[0].len() == ROWS * COLUMNS, "Position string must have {} tile heights, got {}", ROWS * COLUMNS, parts[0].len());
+
+        let mut tile_heights = [[0; COLUMNS]; ROWS];
+        for (tile_id, height_char) in parts[0].chars().enumerate() {
+            let height = height_char.to_digit(10).ok_or_else(|| anyhow::anyhow!("Invalid tile height '{}'", height_char))?;
+            tile_heights[tile_id / COLUMNS][tile_id % COLUMNS] = height as u8;
+        }
+
+        let player_a_workers = parse_position_string_workers::<WORKERS_PER_PLAYER>(parts[1])?;
+        let player_b_workers = parse_position_string_workers::<WORKERS_PER_PLAYER>(parts[2])?;
+        let player_a_turn = match parts[3] {
+            "A" => true,
+            "B" => false,
+            player => bail!("Invalid player to move '{}', must be A or B", player),
+        };
+
+        return GenericSantoriniGameState::new(player_a_workers, player_b_workers, tile_heights, player_a_turn);
+    }
+
+    fn to_canonical_position_string(&self) -> String {
+        return (0..Self::get_symmetry_count())
+            .map(|symmetry| self.get_symmetric_state(symmetry).to_position_string())
+            .min()
+            .expect("There is at least the identity symmetry");
+    }
+
+    fn get_move_notation(&self, child: &Self) -> Result<String> {
+        return self.format_move_notation(child);
+    }
+
+    fn normalize_move_notation(notation: &str) -> Result<String> {
+        return Self::normalize_notation(notation);
+    }
+
+    fn can_player_win_immediately(&self, player_a: bool) -> bool {
+        let workers = if player_a { self.player_a_workers } else { self.player_b_workers };
+        let Some(workers) = workers else {
+            return false;
+        };
+
+        for worker_tile in workers {
+            let row = worker_tile as isize / COLUMNS as isize;
+            let column = worker_tile as isize % COLUMNS as isize;
+            if self.tile_heights[row as usize][column as usize] != 2 {
+                continue;
+            }
+
+            for neighbor_row in (row - 1).max(0)..=(row + 1).min(ROWS as isize - 1) {
+                for neighbor_column in (column - 1).max(0)..=(column + 1).min(COLUMNS as isize - 1) {
+                    if self.tile_heights[neighbor_row as usize][neighbor_column as usize] != 3 {
+                        continue;
+                    }
+                    let neighbor_tile = (neighbor_row as usize * COLUMNS + neighbor_column as usize) as u8;
+                    if self.player_a_workers.iter().flatten().chain(self.player_b_workers.iter().flatten()).all(|&tile| tile != neighbor_tile) {
+                        return true;
+                    }
+                }
+            }
+        }
+        return false;
+    }
 }
\ No newline at end of file
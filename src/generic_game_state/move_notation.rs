@@ -0,0 +1,213 @@
+use anyhow::{anyhow, bail, ensure, Result};
+use crate::game_state::GameState;
+use crate::generic_game_state::generic_santorini_game_state::GenericSantoriniGameState;
+use crate::generic_game_state::GenericGameState;
+
+/*
+Moves are written with tile coordinates like in chess: the column as a letter starting with 'a' and the row as a number starting with 1.
+- Worker placements list the tiles of the placed workers separated by ',', e.g. "a1,c3"
+- Other moves are written as "<from>-<to>+<build>", e.g. "b2-c3+d4". If the child state has no additional block (e.g. some winning moves), the build is omitted: "b2-c3"
+ */
+
+pub fn format_tile<const COLUMNS: usize>(tile_id: usize) -> String {
+    let column = (b'a' + (tile_id % COLUMNS) as u8) as char;
+    return format!("{}{}", column, tile_id / COLUMNS + 1);
+}
+
+pub fn parse_tile<const ROWS: usize, const COLUMNS: usize>(tile: &str) -> Result<usize> {
+    let mut chars = tile.trim().chars();
+    let column_char = chars.next().ok_or_else(|| anyhow!("Empty tile"))?;
+    ensure!(column_char.is_ascii_lowercase(), "Invalid column '{}' in tile '{}'", column_char, tile);
+    let column = (column_char as u8 - b'a') as usize;
+    let row = chars.as_str().parse::<usize>().map_err(|_| anyhow!("Invalid row in tile '{}'", tile))?;
+    ensure!(column < COLUMNS && row >= 1 && row <= ROWS, "Tile '{}' is outside of the {}x{} board", tile, ROWS, COLUMNS);
+    return Ok((row - 1) * COLUMNS + column);
+}
+
+// Tiles that changed with a move, used for the notation and for drawing moves
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MoveTiles {
+    // Tiles of the placed workers, sorted, empty for other moves
+    pub placed: Vec<usize>,
+    pub from: Option<usize>,
+    pub to: Option<usize>,
+    pub build: Option<usize>,
+}
+
+impl<const ROWS: usize, const COLUMNS: usize, const WORKERS_PER_PLAYER: usize> GenericSantoriniGameState<ROWS, COLUMNS, WORKERS_PER_PLAYER> {
+    pub fn get_move_tiles(&self, child: &Self) -> Result<MoveTiles> {
+        let (workers, child_workers) = if self.player_a_turn {
+            (self.player_a_workers, child.player_a_workers)
+        } else {
+            (self.player_b_workers, child.player_b_workers)
+        };
+        let child_workers = child_workers.ok_or_else(|| anyhow!("The player to move has no workers after the move"))?;
+
+        let Some(workers) = workers else {
+            let mut placed = child_workers.map(|tile| tile as usize).to_vec();
+            placed.sort_unstable();
+            return Ok(MoveTiles { placed, ..MoveTiles::default() });
+        };
+
+        let from = workers.iter().find(|tile| !child_workers.contains(tile)).ok_or_else(|| anyhow!("No worker was moved"))?;
+        let to = child_workers.iter().find(|tile| !workers.contains(tile)).ok_or_else(|| anyhow!("No worker was moved"))?;
+        return Ok(MoveTiles {
+            placed: Vec::new(),
+            from: Some(*from as usize),
+            to: Some(*to as usize),
+            build: (0..ROWS * COLUMNS).find(|&tile| child.get_tile_height(tile) > self.get_tile_height(tile)),
+        });
+    }
+
+    pub(crate) fn format_move_notation(&self, child: &Self) -> Result<String> {
+        let move_tiles = self.get_move_tiles(child)?;
+        let (Some(from), Some(to)) = (move_tiles.from, move_tiles.to) else {
+            return Ok(move_tiles.placed.iter().map(|&tile| format_tile::<COLUMNS>(tile)).collect::<Vec<String>>().join(","));
+        };
+
+        let mut notation = format!("{}-{}", format_tile::<COLUMNS>(from), format_tile::<COLUMNS>(to));
+        if let Some(build) = move_tiles.build {
+            notation.push('+');
+            notation.push_str(&format_tile::<COLUMNS>(build));
+        }
+        return Ok(notation);
+    }
+
+    // Brings the notation into the form returned by format_move_notation, so it can be compared
+    pub(crate) fn normalize_notation(notation: &str) -> Result<String> {
+        let notation = notation.trim();
+        if !notation.contains('-') {
+            let mut tiles = notation.split(',').map(parse_tile::<ROWS, COLUMNS>).collect::<Result<Vec<usize>>>()?;
+            ensure!(tiles.len() == WORKERS_PER_PLAYER, "Expected {} placed workers in '{}', got {}", WORKERS_PER_PLAYER, notation, tiles.len());
+            tiles.sort_unstable();
+            return Ok(tiles.iter().map(|&tile| format_tile::<COLUMNS>(tile)).collect::<Vec<String>>().join(","));
+        }
+
+        let (from, rest) = notation.split_once('-').ok_or_else(|| anyhow!("Invalid move '{}'", notation))?;
+        let (to, build) = match rest.split_once('+') {
+            Some((to, build)) => (to, Some(build)),
+            None => (rest, None),
+        };
+        let mut normalized_notation = format!("{}-{}", format_tile::<COLUMNS>(parse_tile::<ROWS, COLUMNS>(from)?), format_tile::<COLUMNS>(parse_tile::<ROWS, COLUMNS>(to)?));
+        if let Some(build) = build {
+            normalized_notation.push('+');
+            normalized_notation.push_str(&format_tile::<COLUMNS>(parse_tile::<ROWS, COLUMNS>(build)?));
+        }
+        return Ok(normalized_notation);
+    }
+}
+
+pub fn get_move_notation<GS: GameState>(state: &GS, child: &GS) -> Result<String> {
+    return state.to_generic_game_state().get_move_notation(&child.to_generic_game_state());
+}
+
+// Returns the index of the child that is reached by the move, and the child itself
+pub fn find_move<GS: GameState>(state: &GS, notation: &str) -> Result<(usize, GS)> {
+    let generic_state = state.to_generic_game_state();
+    let normalized_notation = GS::GenericGameState::normalize_move_notation(notation)?;
+    for (i, child) in state.get_children_states().into_iter().enumerate() {
+        if generic_state.get_move_notation(&child.to_generic_game_state())? == normalized_notation {
+            return Ok((i, child));
+        }
+    }
+    bail!("Move '{}' is not possible in position {}", notation, generic_state.to_position_string());
+}
+
+// Applies the moves in order, starting from the given state
+pub fn apply_moves<GS: GameState>(state: &GS, notations: &[&str]) -> Result<GS> {
+    let mut current_state = *state;
+    for notation in notations {
+        current_state = find_move(&current_state, notation)?.1;
+    }
+    return Ok(current_state);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::{get_initial_setup_state, GameState};
+    use crate::game_state::game_state_4x4_binary_3bit::GameState4x4Binary3Bit;
+    use crate::game_state::game_state_5x5_binary_128bit::GameState5x5Binary128bit;
+    use crate::generic_game_state::generic_santorini_game_state::GenericSantoriniGameState;
+    use crate::generic_game_state::GenericGameState;
+    use crate::generic_game_state::move_notation::{find_move, format_tile, get_move_notation, parse_tile};
+
+    fn check_move_notations<GS: GameState>(state: &GS) {
+        if state.has_player_a_won() || state.has_player_b_won() {
+            return;
+        }
+        let children_states = state.get_children_states();
+        for child_state in &children_states {
+            let notation = get_move_notation(state, child_state).unwrap();
+            let (index, found_state) = find_move(state, &notation).unwrap();
+            // The notation has to identify the move, so different children can't share a notation
+            assert!(found_state == *child_state && children_states[index] == *child_state, "{}", notation);
+        }
+    }
+
+    #[test]
+    fn test_move_notation_finds_the_same_child() {
+        check_move_notations(&get_initial_setup_state::<GameState5x5Binary128bit>());
+        check_move_notations(&get_initial_setup_state::<GameState4x4Binary3Bit>());
+        for _ in 0..1000 {
+            check_move_notations(&GameState5x5Binary128bit::from_generic_game_state(&GenericSantoriniGameState::<5, 5, 2>::generate_random_state()));
+            check_move_notations(&GameState4x4Binary3Bit::from_generic_game_state(&GenericSantoriniGameState::<4, 4, 1>::generate_random_state()));
+        }
+    }
+
+    fn check_move_tiles<
+        const ROWS: usize, const COLUMNS: usize, const WORKERS_PER_PLAYER: usize,
+        GS: GameState<GenericGameState=GenericSantoriniGameState<ROWS, COLUMNS, WORKERS_PER_PLAYER>>
+    >(game_state: &GS) {
+        if game_state.has_player_a_won() || game_state.has_player_b_won() {
+            return;
+        }
+        let state = game_state.to_generic_game_state();
+        let workers = if state.player_a_turn { state.player_a_workers } else { state.player_b_workers };
+        for child in game_state.get_children_states() {
+            let child = child.to_generic_game_state();
+            let move_tiles = state.get_move_tiles(&child).unwrap();
+            let notation = state.format_move_notation(&child).unwrap();
+            let tiles_notation = match (move_tiles.from, move_tiles.to) {
+                (Some(from), Some(to)) => {
+                    assert!(move_tiles.placed.is_empty());
+                    assert!(workers.unwrap().contains(&(from as u8)));
+                    let child_workers = if state.player_a_turn { child.player_a_workers } else { child.player_b_workers };
+                    assert!(child_workers.unwrap().contains(&(to as u8)) && !child_workers.unwrap().contains(&(from as u8)));
+                    match move_tiles.build {
+                        Some(build) => assert_eq!(child.get_tile_height(build), state.get_tile_height(build) + 1),
+                        // Only winning moves don't build
+                        None => assert!(child.has_player_a_won() || child.has_player_b_won()),
+                    }
+                    let build = move_tiles.build.map(|build| format!("+{}", format_tile::<COLUMNS>(build))).unwrap_or_default();
+                    format!("{}-{}{}", format_tile::<COLUMNS>(from), format_tile::<COLUMNS>(to), build)
+                }
+                _ => {
+                    assert!(workers.is_none() && move_tiles.placed.len() == WORKERS_PER_PLAYER && move_tiles.build.is_none());
+                    move_tiles.placed.iter().map(|&tile| format_tile::<COLUMNS>(tile)).collect::<Vec<String>>().join(",")
+                }
+            };
+            assert_eq!(tiles_notation, notation);
+        }
+    }
+
+    #[test]
+    fn test_move_tiles_match_the_move_notation() {
+        check_move_tiles(&get_initial_setup_state::<GameState5x5Binary128bit>());
+        check_move_tiles(&get_initial_setup_state::<GameState4x4Binary3Bit>());
+        for _ in 0..1000 {
+            check_move_tiles(&GameState5x5Binary128bit::from_generic_game_state(&GenericSantoriniGameState::<5, 5, 2>::generate_random_state()));
+            check_move_tiles(&GameState4x4Binary3Bit::from_generic_game_state(&GenericSantoriniGameState::<4, 4, 1>::generate_random_state()));
+        }
+    }
+
+    #[test]
+    fn test_notations_are_normalized_and_invalid_tiles_are_rejected() {
+        assert_eq!(GenericSantoriniGameState::<5, 5, 2>::normalize_notation(" c3,a1 ").unwrap(), "a1,c3");
+        assert_eq!(GenericSantoriniGameState::<5, 5, 2>::normalize_notation("b2-b3+b2").unwrap(), "b2-b3+b2");
+        assert!(GenericSantoriniGameState::<5, 5, 2>::normalize_notation("a1").is_err());
+        assert!(parse_tile::<5, 5>("f1").is_err());
+        assert!(parse_tile::<5, 5>("a6").is_err());
+        assert_eq!(parse_tile::<5, 5>("e5").unwrap(), 24);
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+use anyhow::{bail, ensure, Result};
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use crate::generic_game_state::generic_santorini_game_state::{sample_heights, GenericSantoriniGameState};
+use crate::generic_game_state::GenericGameState;
+
+// Positions that violate a constraint which can't be generated directly are rejected, this limits the number of retries
+const MAX_REJECTION_ATTEMPTS: usize = 100_000;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PositionPhase {
+    // Not all workers are placed yet, either no worker is placed or only the workers of player A
+    Setup,
+    #[default]
+    Placed,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WorkerAdjacency {
+    #[default]
+    Any,
+    // Every worker stands next to another worker of the same player
+    Adjacent,
+    // No two workers of the same player stand next to each other
+    NonAdjacent,
+}
+
+/**
+Properties a generated position must have, every field that is None (or Any / false) is not constrained.
+Block count, worker heights and domes are generated directly, the remaining constraints are met by rejection sampling.
+Generated positions are never already won, so worker heights must be at most 2.
+ */
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PositionConstraints {
+    pub phase: PositionPhase,
+    pub block_count: Option<usize>,
+    // The player to move can't move a worker up to height 3
+    pub no_immediate_win: bool,
+    // The order of the heights is irrelevant, as the workers of a player are interchangeable
+    pub player_a_worker_heights: Option<Vec<u8>>,
+    pub player_b_worker_heights: Option<Vec<u8>>,
+    // Exact number of tiles with height 4
+    pub dome_count: Option<usize>,
+    pub worker_adjacency: WorkerAdjacency,
+}
+
+impl PositionConstraints {
+    pub fn new() -> PositionConstraints {
+        return PositionConstraints::default();
+    }
+
+    pub fn with_phase(mut self, phase: PositionPhase) -> Self {
+        self.phase = phase;
+        return self;
+    }
+
+    pub fn with_block_count(mut self, block_count: usize) -> Self {
+        self.block_count = Some(block_count);
+        return self;
+    }
+
+    pub fn with_no_immediate_win(mut self) -> Self {
+        self.no_immediate_win = true;
+        return self;
+    }
+
+    pub fn with_worker_heights(mut self, player_a_worker_heights: Vec<u8>, player_b_worker_heights: Vec<u8>) -> Self {
+        self.player_a_worker_heights = Some(player_a_worker_heights);
+        self.player_b_worker_heights = Some(player_b_worker_heights);
+        return self;
+    }
+
+    pub fn with_dome_count(mut self, dome_count: usize) -> Self {
+        self.dome_count = Some(dome_count);
+        return self;
+    }
+
+    pub fn with_worker_adjacency(mut self, worker_adjacency: WorkerAdjacency) -> Self {
+        self.worker_adjacency = worker_adjacency;
+        return self;
+    }
+
+    // Describes all constraints except the block count, which is usually part of the stat name already
+    pub fn get_name_suffix(&self) -> String {
+        let mut suffix = "_constrained".to_string();
+        if self.phase == PositionPhase::Setup {
+            suffix.push_str("_setup");
+        }
+        if self.no_immediate_win {
+            suffix.push_str("_nowin");
+        }
+        for (player, worker_heights) in [("a", &self.player_a_worker_heights), ("b", &self.player_b_worker_heights)] {
+            if let Some(worker_heights) = worker_heights {
+                let mut sorted_heights = worker_heights.clone();
+                sorted_heights.sort_unstable();
+                suffix.push_str(&format!("_{}{}", player, sorted_heights.iter().map(|height| height.to_string()).collect::<String>()));
+            }
+        }
+        if let Some(dome_count) = self.dome_count {
+            suffix.push_str(&format!("_d{}", dome_count));
+        }
+        match self.worker_adjacency {
+            WorkerAdjacency::Any => {}
+            WorkerAdjacency::Adjacent => suffix.push_str("_adjacent"),
+            WorkerAdjacency::NonAdjacent => suffix.push_str("_nonadjacent"),
+        }
+        return suffix;
+    }
+
+    // The same seed always results in the same positions
+    pub fn generate_states<GGS: GenericGameState>(&self, seed: u64, number_of_states: usize) -> Result<Vec<GGS>> {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+        return (0..number_of_states).map(|_| GGS::generate_constrained_state_rng(&mut rng, self)).collect();
+    }
+
+    fn validate<const ROWS: usize, const COLUMNS: usize, const WORKERS_PER_PLAYER: usize>(&self) -> Result<()> {
+        if self.phase == PositionPhase::Setup {
+            ensure!(self.block_count.unwrap_or(0) == 0, "No blocks can be built in the setup phase");
+            ensure!(self.dome_count.unwrap_or(0) == 0, "No domes can be built in the setup phase");
+            ensure!(self.player_a_worker_heights.is_none() && self.player_b_worker_heights.is_none(), "Worker heights can't be constrained in the setup phase");
+            ensure!(self.worker_adjacency == WorkerAdjacency::Any, "Worker adjacency can't be constrained in the setup phase");
+            return Ok(());
+        }
+
+        if self.worker_adjacency != WorkerAdjacency::Any {
+            ensure!(WORKERS_PER_PLAYER > 1, "Worker adjacency can only be constrained with more than one worker per player");
+        }
+
+        let mut min_block_count = 0;
+        let mut max_block_count = 0;
+        for worker_heights in [&self.player_a_worker_heights, &self.player_b_worker_heights] {
+            match worker_heights {
+                Some(worker_heights) => {
+                    ensure!(worker_heights.len() == WORKERS_PER_PLAYER, "Expected {} worker heights, got {}", WORKERS_PER_PLAYER, worker_heights.len());
+                    ensure!(worker_heights.iter().all(|&height| height <= 2), "Worker heights must be at most 2, a worker on height 3 has already won");
+                    let worker_blocks = worker_heights.iter().map(|&height| height as usize).sum::<usize>();
+                    min_block_count += worker_blocks;
+                    max_block_count += worker_blocks;
+                }
+                None => max_block_count += WORKERS_PER_PLAYER * 2,
+            }
+        }
+
+        let free_tiles = ROWS * COLUMNS - WORKERS_PER_PLAYER * 2;
+        match self.dome_count {
+            Some(dome_count) => {
+                ensure!(dome_count <= free_tiles, "At most {} domes can be built, got {}", free_tiles, dome_count);
+                min_block_count += dome_count * 4;
+                max_block_count += dome_count * 4 + (free_tiles - dome_count) * 3;
+            }
+            None => max_block_count += free_tiles * 4,
+        }
+
+        if let Some(block_count) = self.block_count {
+            ensure!(block_count >= min_block_count && block_count <= max_block_count,
+                "Block count {} is impossible with the other constraints, must be between {} and {}", block_count, min_block_count, max_block_count);
+        }
+        return Ok(());
+    }
+}
+
+fn are_workers_adjacent<const COLUMNS: usize>(worker_tile: u8, other_worker_tile: u8) -> bool {
+    let (row, column) = (worker_tile as usize / COLUMNS, worker_tile as usize % COLUMNS);
+    let (other_row, other_column) = (other_worker_tile as usize / COLUMNS, other_worker_tile as usize % COLUMNS);
+    return worker_tile != other_worker_tile && row.abs_diff(other_row) <= 1 && column.abs_diff(other_column) <= 1;
+}
+
+fn has_worker_adjacency<const COLUMNS: usize>(workers: &[u8], worker_adjacency: WorkerAdjacency) -> bool {
+    return match worker_adjacency {
+        WorkerAdjacency::Any => true,
+        WorkerAdjacency::Adjacent => workers.iter()
+            .all(|&worker| workers.iter().any(|&other_worker| are_workers_adjacent::<COLUMNS>(worker, other_worker))),
+        WorkerAdjacency::NonAdjacent => workers.iter()
+            .all(|&worker| workers.iter().all(|&other_worker| !are_workers_adjacent::<COLUMNS>(worker, other_worker))),
+    };
+}
+
+impl<const ROWS: usize, const COLUMNS: usize, const WORKERS_PER_PLAYER: usize> GenericSantoriniGameState<ROWS, COLUMNS, WORKERS_PER_PLAYER> {
+    pub(crate) fn generate_constrained_state_with_rng<RNG: Rng>(rng: &mut RNG, constraints: &PositionConstraints) -> Result<Self> {
+        constraints.validate::<ROWS, COLUMNS, WORKERS_PER_PLAYER>()?;
+
+        if constraints.phase == PositionPhase::Setup {
+            // The player to move can never win immediately in the setup phase
+            if rng.gen_bool(0.5) {
+                return GenericSantoriniGameState::new(None, None, [[0; COLUMNS]; ROWS], true);
+            }
+            let (player_a_workers, _) = Self::get_random_worker_positions(rng);
+            return GenericSantoriniGameState::new(Some(player_a_workers), None, [[0; COLUMNS]; ROWS], false);
+        }
+
+        for _ in 0..MAX_REJECTION_ATTEMPTS {
+            let (player_a_workers, player_b_workers) = Self::get_random_worker_positions(rng);
+            if !has_worker_adjacency::<COLUMNS>(&player_a_workers, constraints.worker_adjacency) ||
+                !has_worker_adjacency::<COLUMNS>(&player_b_workers, constraints.worker_adjacency) {
+                continue;
+            }
+
+            // Tiles without a fixed height get their height sampled, limited by max_heights
+            let mut heights = vec![0; ROWS * COLUMNS];
+            let mut max_heights = vec![0; ROWS * COLUMNS];
+            let mut fixed_block_count = 0;
+
+            for (workers, worker_heights) in [(player_a_workers, &constraints.player_a_worker_heights), (player_b_workers, &constraints.player_b_worker_heights)] {
+                for (i, &worker_tile) in workers.iter().enumerate() {
+                    match worker_heights {
+                        Some(worker_heights) => {
+                            heights[worker_tile as usize] = worker_heights[i] as usize;
+                            fixed_block_count += worker_heights[i] as usize;
+                        }
+                        None => max_heights[worker_tile as usize] = 2,
+                    }
+                }
+            }
+
+            let mut free_tiles: Vec<usize> = (0..ROWS * COLUMNS)
+                .filter(|&tile| !player_a_workers.contains(&(tile as u8)) && !player_b_workers.contains(&(tile as u8)))
+                .collect();
+            let free_tile_max_height = match constraints.dome_count {
+                Some(dome_count) => {
+                    for _ in 0..dome_count {
+                        let dome_tile = free_tiles.swap_remove(rng.gen_range(0..free_tiles.len()));
+                        heights[dome_tile] = 4;
+                    }
+                    fixed_block_count += dome_count * 4;
+                    3
+                }
+                None => 4,
+            };
+            for &tile in &free_tiles {
+                max_heights[tile] = free_tile_max_height;
+            }
+
+            match constraints.block_count {
+                Some(block_count) => {
+                    // Uniformly distributes the remaining blocks over the tiles without a fixed height
+                    let mut sampled_heights = vec![0; ROWS * COLUMNS];
+                    sample_heights(0, block_count - fixed_block_count, &max_heights, &mut sampled_heights, rng, &mut HashMap::new());
+                    for (height, sampled_height) in heights.iter_mut().zip(sampled_heights) {
+                        *height += sampled_height;
+                    }
+                }
+                None => {
+                    for (height, &max_height) in heights.iter_mut().zip(max_heights.iter()) {
+                        *height += rng.gen_range(0..=max_height);
+                    }
+                }
+            }
+
+            let mut tile_heights = [[0; COLUMNS]; ROWS];
+            for (i, &height) in heights.iter().enumerate() {
+                tile_heights[i / COLUMNS][i % COLUMNS] = height as u8;
+            }
+            let player_a_turn = heights.iter().sum::<usize>() % 2 == 0;
+
+            let state = GenericSantoriniGameState::new(Some(player_a_workers), Some(player_b_workers), tile_heights, player_a_turn)?;
+            if constraints.no_immediate_win && state.can_player_win_immediately(player_a_turn) {
+                continue;
+            }
+            return Ok(state);
+        }
+
+        bail!("Could not generate a position with the given constraints in {} attempts", MAX_REJECTION_ATTEMPTS);
+    }
+}
@@ -7,4 +7,6 @@ pub mod minimax;
 pub mod precompute_state_winner;
 pub mod strategy;
 pub mod play_game;
-pub mod stats;
\ No newline at end of file
+pub mod stats;
+pub mod engine_protocol;
+pub mod play_server;
\ No newline at end of file
@@ -6,24 +6,32 @@ use anyhow::Result;
 use rand::SeedableRng;
 use santorini_minimax::game_state::game_state_5x5_binary_composite::GameState5x5BinaryComposite;
 use santorini_minimax::game_state::game_state_5x5_struct::GameState5x5Struct;
-use santorini_minimax::game_state::{GameState, SantoriniEval, SantoriniState5x5};
+use santorini_minimax::game_state::{GameState, MoveIdentity, SantoriniEval, SantoriniState5x5};
 use santorini_minimax::game_state::game_state_4x4_binary_3bit::GameState4x4Binary3Bit;
+use santorini_minimax::game_state::differential_fuzzing::{format_fuzzing_failure, new_4x4_fuzzer, new_5x5_fuzzer, save_fuzzing_failure};
 use santorini_minimax::generic_game_state::GenericGameState;
-use santorini_minimax::minimax::alpha_beta_sorted_minimax;
+use santorini_minimax::minimax::{alpha_beta_sorted_minimax, minimax_custom_heuristic};
+use santorini_minimax::minimax::minimax_cache::MinimaxCache;
 use santorini_minimax::play_game::simulate_random_games;
 use santorini_minimax::stats::benchmark_minimax_alpha_beta::BenchmarkMinimaxAlphaBeta;
 use santorini_minimax::stats::benchmark_minimax_cached::BenchmarkMinimaxCached;
 use santorini_minimax::stats::benchmark_minimax_infinite::BenchmarkMinimaxInfinite;
+use santorini_minimax::stats::benchmark_minimax_null_window::BenchmarkMinimaxNullWindow;
 use santorini_minimax::stats::benchmark_minimax_simple::BenchmarkMinimaxSimple;
 use santorini_minimax::stats::benchmark_minimax_sorted::BenchmarkMinimaxSorted;
+use santorini_minimax::stats::benchmark_proof_number_search::BenchmarkProofNumberSearch;
 use santorini_minimax::stats::minimax_solve_stats::MinimaxSolveStats;
+use santorini_minimax::stats::move_ordering_stats::MoveOrderingStats;
 use santorini_minimax::stats::presolve_analysis::PresolveAnalysis;
+use santorini_minimax::stats::search_test_suite::{format_search_test_suite_report, get_depth_limited_search_functions, load_search_test_suite, run_search_function, run_strategy};
+use santorini_minimax::stats::utils::draw_evaluation_heatmap::{draw_evaluation_heatmap, get_tile_evaluations, HeatmapTile};
 use santorini_minimax::strategy::console_input_strategy::ConsoleInputStrategy;
 use santorini_minimax::strategy::dynamic_minimax_strategy::DynamicMinimaxStrategy;
 use santorini_minimax::strategy::heuristic_minimax_strategy::HeuristicMinimaxStrategy;
+use santorini_minimax::strategy::iterative_deepening_strategy::IterativeDeepeningStrategy;
 use santorini_minimax::strategy::heuristics::boreham_greedy_heuristic::boreham_greedy_heuristic;
 use santorini_minimax::strategy::heuristics::boreham_heuristic::boreham_heuristic;
-use santorini_minimax::strategy::heuristics::dynamic_heuristic::DynamicHeuristicParams;
+use santorini_minimax::strategy::heuristics::dynamic_heuristic::{dynamic_heuristic, DynamicHeuristicParams};
 use santorini_minimax::strategy::random_strategy::RandomStrategy;
 
 
@@ -36,6 +44,65 @@ fn store_game_state_image<const ROWS: usize, const COLUMNS: usize, const WORKERS
     return Ok(());
 }
 
+fn store_evaluation_heatmaps<GS: GameState<GenericGameState=GenericSantoriniGameState<5, 5, 2>> + SantoriniEval<SantoriniState = SantoriniState5x5>>(game_state: &GS, name: &str) -> Result<()> {
+    let folder_path = "stats/evaluation_heatmaps/";
+    std::fs::create_dir_all(folder_path)?;
+    let generic_state = game_state.to_generic_game_state();
+    let params = DynamicHeuristicParams::default();
+
+    for heatmap_tile in [HeatmapTile::Destination, HeatmapTile::Build] {
+        let tile_name = heatmap_tile.get_name().to_lowercase();
+
+        let evaluations = get_tile_evaluations(game_state, heatmap_tile, |state| dynamic_heuristic(state, &params))?;
+        draw_evaluation_heatmap(&generic_state, &evaluations, &format!("{}: Dynamic", heatmap_tile.get_name()), &format!("{}/{}_{}_dynamic.svg", folder_path, name, tile_name))?;
+
+        let evaluations = get_tile_evaluations(game_state, heatmap_tile, boreham_heuristic)?;
+        draw_evaluation_heatmap(&generic_state, &evaluations, &format!("{}: Boreham", heatmap_tile.get_name()), &format!("{}/{}_{}_boreham.svg", folder_path, name, tile_name))?;
+
+        let evaluations = get_tile_evaluations(game_state, heatmap_tile, |state| state.get_child_evaluation())?;
+        draw_evaluation_heatmap(&generic_state, &evaluations, &format!("{}: Child", heatmap_tile.get_name()), &format!("{}/{}_{}_child.svg", folder_path, name, tile_name))?;
+
+        let mut cache = MinimaxCache::new();
+        let evaluations = get_tile_evaluations(game_state, heatmap_tile, |state| minimax_custom_heuristic(state, 3, &mut cache, &|state: &GS| dynamic_heuristic(state, &params)))?;
+        draw_evaluation_heatmap(&generic_state, &evaluations, &format!("{}: Depth 3", heatmap_tile.get_name()), &format!("{}/{}_{}_depth_3.svg", folder_path, name, tile_name))?;
+    }
+    return Ok(());
+}
+
+fn run_search_test_suite<GS: GameState + SantoriniEval<SantoriniState = SantoriniState5x5> + MoveIdentity + 'static>(path: &str) -> Result<()> {
+    let tests = load_search_test_suite(path)?;
+    let mut results = Vec::new();
+
+    for (name, search_function) in get_depth_limited_search_functions::<GS>() {
+        results.push(run_search_function(&name, search_function.as_ref(), &tests)?);
+    }
+
+    // Strategies search with their own depth, so only the best moves are checked
+    let params = DynamicHeuristicParams::default();
+    results.push(run_strategy("boreham_depth_3", &mut HeuristicMinimaxStrategy::<GS>::new(3, boreham_heuristic), &tests)?);
+    results.push(run_strategy("child_heuristic_depth_3", &mut HeuristicMinimaxStrategy::<GS>::new(3, |state| state.get_child_evaluation()), &tests)?);
+    results.push(run_strategy("dynamic_depth_3", &mut DynamicMinimaxStrategy::<GS>::new(3, params), &tests)?);
+    results.push(run_strategy("iterative_deepening_depth_5", &mut IterativeDeepeningStrategy::<GS>::new(5, |state| state.get_child_evaluation()), &tests)?);
+    results.push(run_strategy("random", &mut RandomStrategy::<GS>::new(), &tests)?);
+
+    print!("{}", format_search_test_suite_report(&results));
+    return Ok(());
+}
+
+// Minimized failures are added to the regression tests
+fn run_differential_fuzzing(games: usize) -> Result<()> {
+    let mut rng = rand::thread_rng();
+    for failure in new_4x4_fuzzer().fuzz(&mut rng, games, 100)? {
+        print!("{}", format_fuzzing_failure(&failure));
+        save_fuzzing_failure("test_suites/differential_fuzzing_4x4.txt", &failure)?;
+    }
+    for failure in new_5x5_fuzzer().fuzz(&mut rng, games, 100)? {
+        print!("{}", format_fuzzing_failure(&failure));
+        save_fuzzing_failure("test_suites/differential_fuzzing_5x5.txt", &failure)?;
+    }
+    return Ok(());
+}
+
 async fn average_branching_factor<GS: GameState + SantoriniEval + 'static>(number_random_states: usize, block_count: usize, depth: usize) -> Result<f32> {
     let mut rng = rand::rngs::StdRng::seed_from_u64(0);
     let random_states: Vec<GS> = (0..number_random_states)
@@ -144,6 +211,27 @@ async fn handle_stats() {
     //benchmark_minimax_cached_5x5.generate_graph_from_most_recent_data().unwrap();
 
 
+    let benchmark_minimax_null_window_5x5 = BenchmarkMinimaxNullWindow::<GS5x5, 3, 3, 3>::new(
+        "Santorini".to_string(),
+        "5x5 Binary Composite".to_string(),
+        "5x5_binary_composite".to_string(),
+        benchmark_minimax_cached_5x5,
+    );
+    //benchmark_minimax_null_window_5x5.gather_and_store_data().await.unwrap();
+    //benchmark_minimax_null_window_5x5.generate_graph_from_most_recent_data().unwrap();
+
+
+    let move_ordering_stats_5x5 = MoveOrderingStats::<GS5x5, 3, 3>::new(
+        "5x5 Binary Composite".to_string(),
+        "5x5_binary_composite".to_string(),
+        9,
+        20,
+        100,
+    );
+    //move_ordering_stats_5x5.gather_and_store_data().await.unwrap();
+    //move_ordering_stats_5x5.generate_graph_from_most_recent_data().unwrap();
+
+
     let minimax_solve_stats_5x5 = MinimaxSolveStats::<GS5x5>::new(
         "5x5 Binary Composite".to_string(),
         "5x5_binary_composite".to_string(),
@@ -227,6 +315,19 @@ async fn handle_stats() {
 
     //benchmark_minimax_infinite.gather_and_store_data().await.unwrap();
     //benchmark_minimax_infinite.generate_graph_from_most_recent_data().unwrap();
+
+
+    let benchmark_proof_number_search = BenchmarkProofNumberSearch::<GS5x5>::new(
+        "5x5 Binary Composite".to_string(),
+        "5x5_binary_composite".to_string(),
+        1000,
+        40..=92,
+        57..=92,
+        1 << 22,
+    );
+
+    //benchmark_proof_number_search.gather_and_store_data().await.unwrap();
+    //benchmark_proof_number_search.generate_graph_from_most_recent_data().unwrap();
 }
 
 async fn benchmark_strategies<GS: GameState + SantoriniEval<SantoriniState = SantoriniState5x5> + 'static>(num_games: usize, initial_block_count: usize) {
@@ -249,7 +350,7 @@ async fn benchmark_strategies<GS: GameState + SantoriniEval<SantoriniState = San
 
 
     let start = std::time::Instant::now();
-    let results = simulate_random_games(&default_strategy_depth_4, &child_heuristic_strategy_depth_4, num_games, initial_block_count).await;
+    let results = simulate_random_games(&default_strategy_depth_4, &child_heuristic_strategy_depth_4, num_games, initial_block_count, None).await;
     let duration = start.elapsed();
 
     println!("Wins: {}", results.strategy_1_wins);
@@ -282,4 +383,10 @@ async fn tokio_main() {
     //benchmark_strategies::<GS5x5>(1000, 0).await;
 
     //let _ = average_branching_factor::<GS5x5>(1000, 20, 6).await;
+
+    //run_search_test_suite::<GS5x5>("test_suites/forced_wins_5x5.txt").unwrap();
+
+    //run_differential_fuzzing(10000).unwrap();
+
+    //store_evaluation_heatmaps(&GS5x5::from_generic_game_state(&GGS5x5::generate_random_state_with_blocks(10)), "random_10_blocks").unwrap();
 }
@@ -1,10 +1,19 @@
 pub mod minimax_cache;
+pub mod forced_win;
+pub mod proof_number_search;
+pub mod score;
+pub mod move_ordering;
+pub mod analysis;
 
+use std::sync::atomic::{AtomicBool, Ordering};
 use fnv::FnvHashMap;
 use futures::stream::FuturesUnordered;
 use futures::StreamExt;
-use crate::game_state::{GameState, SantoriniEval};
+use crate::game_state::{GameState, MoveIdentity, SantoriniEval, SantoriniMove};
+use crate::generic_game_state::GenericGameState;
 use crate::minimax::minimax_cache::{Bounds, MinimaxCache};
+use crate::minimax::move_ordering::{MoveOrdering, MoveOrderingConfig};
+use crate::minimax::score::{clamp_heuristic, is_decisive, step_back, step_forward, win_score, WIN_SCORE};
 
 #[inline(always)]
 fn order_children_states<GS: GameState + SantoriniEval>(children_states: &mut Vec<GS>, maximizing: bool) {
@@ -21,12 +30,20 @@ fn order_children_states<GS: GameState + SantoriniEval>(children_states: &mut Ve
 }
 
 
+// Won states are scored with win_score, so the evaluation function is never called for them (heuristics like dynamic_heuristic don't support won states)
 fn order_children_states_with_function<GS: GameState + SantoriniEval, F: Fn(&GS) -> f32>(
     children_states: &mut Vec<GS>,
     maximizing: bool,
     evaluation_function: &F,
 ) {
-    let mut children_evaluations: Vec<(f32, &mut GS)> = children_states.into_iter().map(|state| (evaluation_function(state), state)).collect();
+    let mut children_evaluations: Vec<(f32, &mut GS)> = children_states.into_iter().map(|state| {
+        let evaluation = if state.has_player_a_won() || state.has_player_b_won() {
+            win_score(state.has_player_a_won())
+        } else {
+            evaluation_function(state)
+        };
+        (evaluation, state)
+    }).collect();
     if maximizing {
         children_evaluations.sort_unstable_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
     } else {
@@ -46,9 +63,9 @@ fn simple_minimax_internal<GS: GameState>(
     *evaluated_states += 1;
 
     if game_state.has_player_a_won() {
-        return f32::INFINITY;
+        return WIN_SCORE;
     } else if game_state.has_player_b_won() {
-        return f32::NEG_INFINITY;
+        return -WIN_SCORE;
     }
 
     if depth == 0 {
@@ -59,18 +76,18 @@ fn simple_minimax_internal<GS: GameState>(
 
     let mut reusable_vec_for_children = Vec::with_capacity(64);
     if maximizing_player {
-        let mut max_evaluation = f32::NEG_INFINITY;
+        let mut max_evaluation = -WIN_SCORE;
         for child in reused_children_vec {
-            let evaluation = simple_minimax_internal(child, false, depth - 1, &mut reusable_vec_for_children, evaluated_states);
+            let evaluation = step_back(simple_minimax_internal(child, false, depth - 1, &mut reusable_vec_for_children, evaluated_states));
             if evaluation > max_evaluation {
                 max_evaluation = evaluation;
             }
         }
         return max_evaluation;
     } else {
-        let mut min_evaluation = f32::INFINITY;
+        let mut min_evaluation = WIN_SCORE;
         for child in reused_children_vec {
-            let evaluation = simple_minimax_internal(child, true, depth - 1, &mut reusable_vec_for_children, evaluated_states);
+            let evaluation = step_back(simple_minimax_internal(child, true, depth - 1, &mut reusable_vec_for_children, evaluated_states));
             if evaluation < min_evaluation {
                 min_evaluation = evaluation;
             }
@@ -99,9 +116,9 @@ fn alpha_beta_minimax_internal<GS: GameState>(
     *evaluated_states += 1;
 
     if game_state.has_player_a_won() {
-        return f32::INFINITY;
+        return WIN_SCORE;
     } else if game_state.has_player_b_won() {
-        return f32::NEG_INFINITY;
+        return -WIN_SCORE;
     }
 
     if depth == 0 {
@@ -112,9 +129,9 @@ fn alpha_beta_minimax_internal<GS: GameState>(
 
     let mut reusable_vec_for_children = Vec::with_capacity(64);
     if maximizing_player {
-        let mut max_evaluation = f32::NEG_INFINITY;
+        let mut max_evaluation = -WIN_SCORE;
         for child in reused_children_vec {
-            let evaluation = alpha_beta_minimax_internal(child, false, depth - 1, alpha, beta, &mut reusable_vec_for_children, evaluated_states);
+            let evaluation = step_back(alpha_beta_minimax_internal(child, false, depth - 1, step_forward(alpha), step_forward(beta), &mut reusable_vec_for_children, evaluated_states));
             if evaluation > max_evaluation {
                 max_evaluation = evaluation;
                 if max_evaluation >= beta {
@@ -127,9 +144,9 @@ fn alpha_beta_minimax_internal<GS: GameState>(
         }
         return max_evaluation;
     } else {
-        let mut min_evaluation = f32::INFINITY;
+        let mut min_evaluation = WIN_SCORE;
         for child in reused_children_vec {
-            let evaluation = alpha_beta_minimax_internal(child, true, depth - 1, alpha, beta, &mut reusable_vec_for_children, evaluated_states);
+            let evaluation = step_back(alpha_beta_minimax_internal(child, true, depth - 1, step_forward(alpha), step_forward(beta), &mut reusable_vec_for_children, evaluated_states));
             if evaluation < min_evaluation {
                 min_evaluation = evaluation;
                 if min_evaluation <= alpha {
@@ -164,9 +181,9 @@ fn alpha_beta_sorted_minimax_internal<GS: GameState + SantoriniEval, const MIN_D
     *evaluated_states += 1;
 
     if game_state.has_player_a_won() {
-        return f32::INFINITY;
+        return WIN_SCORE;
     } else if game_state.has_player_b_won() {
-        return f32::NEG_INFINITY;
+        return -WIN_SCORE;
     }
 
     if depth == 0 {
@@ -182,9 +199,9 @@ fn alpha_beta_sorted_minimax_internal<GS: GameState + SantoriniEval, const MIN_D
 
     let mut reusable_vec_for_children = Vec::with_capacity(64);
     if maximizing_player {
-        let mut max_evaluation = f32::NEG_INFINITY;
+        let mut max_evaluation = -WIN_SCORE;
         for child in reused_children_vec {
-            let evaluation = alpha_beta_sorted_minimax_internal::<GS, MIN_DEPTH_TO_SORT>(child, false, depth - 1, alpha, beta, &mut reusable_vec_for_children, evaluated_states);
+            let evaluation = step_back(alpha_beta_sorted_minimax_internal::<GS, MIN_DEPTH_TO_SORT>(child, false, depth - 1, step_forward(alpha), step_forward(beta), &mut reusable_vec_for_children, evaluated_states));
             if evaluation > max_evaluation {
                 max_evaluation = evaluation;
                 if max_evaluation >= beta {
@@ -197,9 +214,9 @@ fn alpha_beta_sorted_minimax_internal<GS: GameState + SantoriniEval, const MIN_D
         }
         return max_evaluation;
     } else {
-        let mut min_evaluation = f32::INFINITY;
+        let mut min_evaluation = WIN_SCORE;
         for child in reused_children_vec {
-            let evaluation = alpha_beta_sorted_minimax_internal::<GS, MIN_DEPTH_TO_SORT>(child, true, depth - 1, alpha, beta, &mut reusable_vec_for_children, evaluated_states);
+            let evaluation = step_back(alpha_beta_sorted_minimax_internal::<GS, MIN_DEPTH_TO_SORT>(child, true, depth - 1, step_forward(alpha), step_forward(beta), &mut reusable_vec_for_children, evaluated_states));
             if evaluation < min_evaluation {
                 min_evaluation = evaluation;
                 if min_evaluation <= alpha {
@@ -229,10 +246,80 @@ pub fn alpha_beta_sorted_minimax<GS: GameState + SantoriniEval, const MIN_DEPTH_
     return (result, evaluated_states);
 }
 
+/*
+Continues the search at the horizon as long as the player to move is threatened by an immediate win of the opponent.
+If the player to move can win immediately (move a worker up to height 3), the state is decisive. Otherwise, if the opponent
+could win immediately, every move is searched, so only moves that block all threats (e.g. building a dome) avoid the loss.
+Quiet states and states at the end of the extension are evaluated with the static evaluation.
+ */
+fn internal_threat_extension<GS: GameState, F: Fn(&GS) -> f32>(
+    game_state: &GS,
+    maximizing_player: bool,
+    extension_depth: usize,
+    mut alpha: f32,
+    mut beta: f32,
+    evaluated_states: &mut usize,
+    static_evaluation: &F,
+) -> f32 {
+    if game_state.has_player_a_won() {
+        return WIN_SCORE;
+    } else if game_state.has_player_b_won() {
+        return -WIN_SCORE;
+    }
+
+    if extension_depth == 0 {
+        return static_evaluation(game_state);
+    }
+
+    let generic_game_state = game_state.to_generic_game_state();
+    if generic_game_state.can_player_win_immediately(maximizing_player) {
+        return step_back(win_score(maximizing_player));
+    }
+    if !generic_game_state.can_player_win_immediately(!maximizing_player) {
+        return static_evaluation(game_state);
+    }
+
+    let children_states = game_state.get_children_states();
+    *evaluated_states += children_states.len();
+
+    if maximizing_player {
+        let mut max_evaluation = -WIN_SCORE;
+        for child in children_states.iter() {
+            let evaluation = step_back(internal_threat_extension(child, false, extension_depth - 1, step_forward(alpha), step_forward(beta), evaluated_states, static_evaluation));
+            if evaluation > max_evaluation {
+                max_evaluation = evaluation;
+                if max_evaluation >= beta {
+                    break;
+                }
+                if max_evaluation > alpha {
+                    alpha = max_evaluation;
+                }
+            }
+        }
+        return max_evaluation;
+    } else {
+        let mut min_evaluation = WIN_SCORE;
+        for child in children_states.iter() {
+            let evaluation = step_back(internal_threat_extension(child, true, extension_depth - 1, step_forward(alpha), step_forward(beta), evaluated_states, static_evaluation));
+            if evaluation < min_evaluation {
+                min_evaluation = evaluation;
+                if min_evaluation <= alpha {
+                    break;
+                }
+                if min_evaluation < beta {
+                    beta = min_evaluation;
+                }
+            }
+        }
+        return min_evaluation;
+    }
+}
+
 fn internal_cached_minimax<GS: GameState + SantoriniEval, const MIN_DEPTH_TO_SORT: usize, const MIN_DEPTH_TO_CACHE: usize>(
     game_state: &GS,
     maximizing_player: bool,
     depth: usize,
+    max_extension_depth: usize,
     mut alpha: f32,
     mut beta: f32,
     cache: &mut MinimaxCache<GS, 100>,
@@ -242,12 +329,15 @@ fn internal_cached_minimax<GS: GameState + SantoriniEval, const MIN_DEPTH_TO_SOR
     *evaluated_states += 1;
 
     if game_state.has_player_a_won() {
-        return f32::INFINITY;
+        return WIN_SCORE;
     } else if game_state.has_player_b_won() {
-        return f32::NEG_INFINITY;
+        return -WIN_SCORE;
     }
 
     if depth == 0 {
+        if max_extension_depth > 0 {
+            return internal_threat_extension(game_state, maximizing_player, max_extension_depth, alpha, beta, evaluated_states, &|_: &GS| 0.0);
+        }
         return 0.0;
     }
 
@@ -255,7 +345,7 @@ fn internal_cached_minimax<GS: GameState + SantoriniEval, const MIN_DEPTH_TO_SOR
 
     if maximizing_player {
         let original_alpha = alpha;
-        let mut max_evaluation = f32::NEG_INFINITY;
+        let mut max_evaluation = -WIN_SCORE;
 
         if depth >= MIN_DEPTH_TO_CACHE {
             if let Some(cached_value) = cache.get_valuation_bounds(depth, game_state) {
@@ -263,7 +353,7 @@ fn internal_cached_minimax<GS: GameState + SantoriniEval, const MIN_DEPTH_TO_SOR
                     return cached_value.value;
                 }
 
-                if cached_value.beta <= beta && cached_value.value > alpha {
+                if cached_value.value > cached_value.alpha && cached_value.value > alpha {
                     if cached_value.value >= beta {
                         return cached_value.value;
                     }
@@ -281,7 +371,7 @@ fn internal_cached_minimax<GS: GameState + SantoriniEval, const MIN_DEPTH_TO_SOR
         }
 
         for child in reused_children_vec {
-            let evaluation = internal_cached_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>(child, false, depth - 1, alpha, beta, cache, &mut reusable_vec_for_children, evaluated_states);
+            let evaluation = step_back(internal_cached_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>(child, false, depth - 1, max_extension_depth, step_forward(alpha), step_forward(beta), cache, &mut reusable_vec_for_children, evaluated_states));
             if evaluation > max_evaluation {
                 max_evaluation = evaluation;
                 if max_evaluation >= beta {
@@ -299,7 +389,7 @@ fn internal_cached_minimax<GS: GameState + SantoriniEval, const MIN_DEPTH_TO_SOR
         return max_evaluation;
     } else {
         let original_beta = beta;
-        let mut min_evaluation = f32::INFINITY;
+        let mut min_evaluation = WIN_SCORE;
 
         if depth >= MIN_DEPTH_TO_CACHE {
             if let Some(cached_value) = cache.get_valuation_bounds(depth, game_state) {
@@ -307,7 +397,7 @@ fn internal_cached_minimax<GS: GameState + SantoriniEval, const MIN_DEPTH_TO_SOR
                     return cached_value.value;
                 }
 
-                if cached_value.alpha >= alpha && cached_value.value < beta {
+                if cached_value.value < cached_value.beta && cached_value.value < beta {
                     if cached_value.value <= alpha {
                         return cached_value.value;
                     }
@@ -325,7 +415,7 @@ fn internal_cached_minimax<GS: GameState + SantoriniEval, const MIN_DEPTH_TO_SOR
         }
 
         for child in reused_children_vec {
-            let evaluation = internal_cached_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>(child, true, depth - 1, alpha, beta, cache, &mut reusable_vec_for_children, evaluated_states);
+            let evaluation = step_back(internal_cached_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>(child, true, depth - 1, max_extension_depth, step_forward(alpha), step_forward(beta), cache, &mut reusable_vec_for_children, evaluated_states));
             if evaluation < min_evaluation {
                 min_evaluation = evaluation;
                 if min_evaluation <= alpha {
@@ -346,6 +436,11 @@ fn internal_cached_minimax<GS: GameState + SantoriniEval, const MIN_DEPTH_TO_SOR
 
 
 pub fn cached_minimax<GS: GameState + SantoriniEval, const MIN_DEPTH_TO_SORT: usize, const MIN_DEPTH_TO_CACHE: usize>(game_state: &GS, depth: usize) -> (f32, usize) {
+    return cached_minimax_with_extension::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>(game_state, depth, 0);
+}
+
+// Like cached_minimax, but threatened states at the search horizon are searched up to max_extension_depth plies further
+pub fn cached_minimax_with_extension<GS: GameState + SantoriniEval, const MIN_DEPTH_TO_SORT: usize, const MIN_DEPTH_TO_CACHE: usize>(game_state: &GS, depth: usize, max_extension_depth: usize) -> (f32, usize) {
     let mut evaluated_states = 0;
     let mut cache = MinimaxCache::new();
 
@@ -353,6 +448,7 @@ pub fn cached_minimax<GS: GameState + SantoriniEval, const MIN_DEPTH_TO_SORT: us
         game_state,
         game_state.is_player_a_turn(),
         depth,
+        max_extension_depth,
         f32::NEG_INFINITY,
         f32::INFINITY,
         &mut cache,
@@ -363,6 +459,436 @@ pub fn cached_minimax<GS: GameState + SantoriniEval, const MIN_DEPTH_TO_SORT: us
     return (result, evaluated_states);
 }
 
+fn internal_principal_variation_minimax<GS: GameState + SantoriniEval, const MIN_DEPTH_TO_SORT: usize, const MIN_DEPTH_TO_CACHE: usize>(
+    game_state: &GS,
+    maximizing_player: bool,
+    depth: usize,
+    mut alpha: f32,
+    mut beta: f32,
+    cache: &mut MinimaxCache<GS, 100>,
+    reused_children_vec: &mut Vec<GS>,
+    evaluated_states: &mut usize,
+) -> f32 {
+    *evaluated_states += 1;
+
+    if game_state.has_player_a_won() {
+        return WIN_SCORE;
+    } else if game_state.has_player_b_won() {
+        return -WIN_SCORE;
+    }
+
+    if depth == 0 {
+        return 0.0;
+    }
+
+    let mut reusable_vec_for_children = Vec::with_capacity(64);
+
+    if maximizing_player {
+        let original_alpha = alpha;
+        let mut max_evaluation = -WIN_SCORE;
+
+        if depth >= MIN_DEPTH_TO_CACHE {
+            if let Some(cached_value) = cache.get_valuation_bounds(depth, game_state) {
+                if cached_value.alpha <= alpha && cached_value.beta >= beta {
+                    return cached_value.value;
+                }
+
+                if cached_value.value > cached_value.alpha && cached_value.value > alpha {
+                    if cached_value.value >= beta {
+                        return cached_value.value;
+                    }
+                    alpha = cached_value.value;
+                    max_evaluation = alpha;
+                }
+            }
+        }
+
+        game_state.get_children_states_reuse_vec(reused_children_vec);
+        if !reused_children_vec.is_empty() && depth >= MIN_DEPTH_TO_SORT {
+            order_children_states(reused_children_vec, maximizing_player);
+        }
+
+        for (i, child) in reused_children_vec.iter().enumerate() {
+            let evaluation = if i == 0 {
+                step_back(internal_principal_variation_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>(child, false, depth - 1, step_forward(alpha), step_forward(beta), cache, &mut reusable_vec_for_children, evaluated_states))
+            } else {
+                // Only prove that the child is not better than the current best child, re-search if that fails
+                let null_window_evaluation = step_back(internal_principal_variation_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>(child, false, depth - 1, step_forward(alpha), step_forward(alpha.next_up()), cache, &mut reusable_vec_for_children, evaluated_states));
+                if null_window_evaluation > alpha && null_window_evaluation < beta {
+                    step_back(internal_principal_variation_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>(child, false, depth - 1, step_forward(alpha), step_forward(beta), cache, &mut reusable_vec_for_children, evaluated_states))
+                } else {
+                    null_window_evaluation
+                }
+            };
+            if evaluation > max_evaluation {
+                max_evaluation = evaluation;
+                if max_evaluation >= beta {
+                    break;
+                }
+                if max_evaluation > alpha {
+                    alpha = max_evaluation;
+                }
+            }
+        }
+
+        if depth >= MIN_DEPTH_TO_CACHE {
+            cache.insert_valuation_bounds(depth, *game_state, Bounds { value: max_evaluation, alpha: original_alpha, beta });
+        }
+        return max_evaluation;
+    } else {
+        let original_beta = beta;
+        let mut min_evaluation = WIN_SCORE;
+
+        if depth >= MIN_DEPTH_TO_CACHE {
+            if let Some(cached_value) = cache.get_valuation_bounds(depth, game_state) {
+                if cached_value.alpha <= alpha && cached_value.beta >= beta {
+                    return cached_value.value;
+                }
+
+                if cached_value.value < cached_value.beta && cached_value.value < beta {
+                    if cached_value.value <= alpha {
+                        return cached_value.value;
+                    }
+                    beta = cached_value.value;
+                    min_evaluation = beta;
+                }
+            }
+        }
+
+        game_state.get_children_states_reuse_vec(reused_children_vec);
+        if !reused_children_vec.is_empty() && depth >= MIN_DEPTH_TO_SORT {
+            order_children_states(reused_children_vec, maximizing_player);
+        }
+
+        for (i, child) in reused_children_vec.iter().enumerate() {
+            let evaluation = if i == 0 {
+                step_back(internal_principal_variation_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>(child, true, depth - 1, step_forward(alpha), step_forward(beta), cache, &mut reusable_vec_for_children, evaluated_states))
+            } else {
+                // Only prove that the child is not better than the current best child, re-search if that fails
+                let null_window_evaluation = step_back(internal_principal_variation_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>(child, true, depth - 1, step_forward(beta.next_down()), step_forward(beta), cache, &mut reusable_vec_for_children, evaluated_states));
+                if null_window_evaluation < beta && null_window_evaluation > alpha {
+                    step_back(internal_principal_variation_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>(child, true, depth - 1, step_forward(alpha), step_forward(beta), cache, &mut reusable_vec_for_children, evaluated_states))
+                } else {
+                    null_window_evaluation
+                }
+            };
+            if evaluation < min_evaluation {
+                min_evaluation = evaluation;
+                if min_evaluation <= alpha {
+                    break;
+                }
+                if min_evaluation < beta {
+                    beta = min_evaluation;
+                }
+            }
+        }
+
+        if depth >= MIN_DEPTH_TO_CACHE {
+            cache.insert_valuation_bounds(depth, *game_state, Bounds { value: min_evaluation, alpha, beta: original_beta });
+        }
+        return min_evaluation;
+    }
+}
+
+// Principal variation search (NegaScout): Every child after the first one is only searched with a null window first
+pub fn principal_variation_minimax<GS: GameState + SantoriniEval, const MIN_DEPTH_TO_SORT: usize, const MIN_DEPTH_TO_CACHE: usize>(game_state: &GS, depth: usize) -> (f32, usize) {
+    let mut evaluated_states = 0;
+    let mut cache = MinimaxCache::new();
+
+    let result = internal_principal_variation_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>(
+        game_state,
+        game_state.is_player_a_turn(),
+        depth,
+        f32::NEG_INFINITY,
+        f32::INFINITY,
+        &mut cache,
+        &mut Vec::with_capacity(64),
+        &mut evaluated_states,
+    );
+
+    return (result, evaluated_states);
+}
+
+
+// Narrows the bounds of the value with null window searches around the guess until the exact value is known
+fn internal_mtdf_minimax<GS: GameState + SantoriniEval, const MIN_DEPTH_TO_SORT: usize, const MIN_DEPTH_TO_CACHE: usize>(
+    game_state: &GS,
+    depth: usize,
+    first_guess: f32,
+    cache: &mut MinimaxCache<GS, 100>,
+    evaluated_states: &mut usize,
+) -> f32 {
+    let mut guess = first_guess;
+    let mut lower_bound = f32::NEG_INFINITY;
+    let mut upper_bound = f32::INFINITY;
+
+    let mut reused_children_vec = Vec::with_capacity(64);
+    while lower_bound < upper_bound {
+        let beta = if guess == lower_bound { guess.next_up() } else { guess };
+        guess = internal_cached_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>(
+            game_state,
+            game_state.is_player_a_turn(),
+            depth,
+            0,
+            beta.next_down(),
+            beta,
+            cache,
+            &mut reused_children_vec,
+            evaluated_states,
+        );
+        if guess < beta {
+            upper_bound = guess;
+        } else {
+            lower_bound = guess;
+        }
+    }
+
+    return guess;
+}
+
+// MTD(f) with iterative deepening, the result of each depth is used as the first guess for the next depth
+pub fn mtdf_minimax<GS: GameState + SantoriniEval, const MIN_DEPTH_TO_SORT: usize, const MIN_DEPTH_TO_CACHE: usize>(game_state: &GS, depth: usize) -> (f32, usize) {
+    let mut evaluated_states = 0;
+    let mut cache = MinimaxCache::new();
+
+    let mut guess = 0.0;
+    for current_depth in 0..=depth {
+        guess = internal_mtdf_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>(game_state, current_depth, guess, &mut cache, &mut evaluated_states);
+        if is_decisive(guess) {
+            // Deeper searches can't change the result anymore
+            break;
+        }
+    }
+
+    return (guess, evaluated_states);
+}
+
+
+/**
+Iterative deepening where every depth is first searched with a window of the given size around the result of the previous depth.
+If the result falls outside the window, the search is repeated with the failing side of the window opened.
+ */
+pub fn aspiration_window_minimax<GS: GameState + SantoriniEval, const MIN_DEPTH_TO_SORT: usize, const MIN_DEPTH_TO_CACHE: usize>(game_state: &GS, depth: usize, window_size: f32) -> (f32, usize) {
+    let mut evaluated_states = 0;
+    let mut cache = MinimaxCache::new();
+    let mut reused_children_vec = Vec::with_capacity(64);
+
+    let mut guess = 0.0;
+    for current_depth in 0..=depth {
+        let mut alpha = guess - window_size;
+        let mut beta = guess + window_size;
+
+        loop {
+            let result = internal_cached_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>(
+                game_state,
+                game_state.is_player_a_turn(),
+                current_depth,
+                0,
+                alpha,
+                beta,
+                &mut cache,
+                &mut reused_children_vec,
+                &mut evaluated_states,
+            );
+
+            if result <= alpha && alpha != f32::NEG_INFINITY {
+                alpha = f32::NEG_INFINITY;
+            } else if result >= beta && beta != f32::INFINITY {
+                beta = f32::INFINITY;
+            } else {
+                guess = result;
+                break;
+            }
+        }
+
+        if is_decisive(guess) {
+            break;
+        }
+    }
+
+    return (guess, evaluated_states);
+}
+
+fn order_children_states_with_moves<GS: GameState + SantoriniEval>(
+    game_state: &GS,
+    ply: usize,
+    children_with_moves: &mut Vec<(GS, SantoriniMove)>,
+    maximizing: bool,
+    move_ordering: &MoveOrdering<GS>,
+) {
+    let mut children_priorities: Vec<_> = children_with_moves.iter()
+        .map(|child_with_move| (move_ordering.get_move_priority(game_state, ply, &child_with_move.1), child_with_move.0.get_child_evaluation(), *child_with_move))
+        .collect();
+    children_priorities.sort_unstable_by(|a, b| {
+        let evaluation_ordering = if maximizing { b.1.partial_cmp(&a.1) } else { a.1.partial_cmp(&b.1) };
+        return b.0.cmp(&a.0).then(evaluation_ordering.unwrap());
+    });
+    *children_with_moves = children_priorities.into_iter().map(|(_, _, child_with_move)| child_with_move).collect();
+}
+
+// Everything a move ordered search shares between its states
+struct MoveOrderedSearch<'a, GS: GameState> {
+    cache: &'a mut MinimaxCache<GS, 100>,
+    move_ordering: &'a mut MoveOrdering<GS>,
+    // The ply of a state is the distance to the root, i.e. root_depth - depth
+    root_depth: usize,
+    evaluated_states: usize,
+}
+
+fn internal_move_ordered_minimax<GS: GameState + SantoriniEval + MoveIdentity, const MIN_DEPTH_TO_SORT: usize, const MIN_DEPTH_TO_CACHE: usize>(
+    game_state: &GS,
+    maximizing_player: bool,
+    depth: usize,
+    mut alpha: f32,
+    mut beta: f32,
+    search: &mut MoveOrderedSearch<GS>,
+    reused_children_vec: &mut Vec<GS>,
+) -> f32 {
+    if depth < MIN_DEPTH_TO_SORT.max(1) {
+        // Below the sorting depth, the moves are not needed, so the faster cached minimax is used
+        return internal_cached_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>(game_state, maximizing_player, depth, 0, alpha, beta, search.cache, reused_children_vec, &mut search.evaluated_states);
+    }
+
+    search.evaluated_states += 1;
+    let ply = search.root_depth - depth;
+
+    if game_state.has_player_a_won() {
+        return WIN_SCORE;
+    } else if game_state.has_player_b_won() {
+        return -WIN_SCORE;
+    }
+
+    let mut reusable_vec_for_children = Vec::with_capacity(64);
+    let mut children_with_moves = Vec::with_capacity(64);
+
+    if maximizing_player {
+        let original_alpha = alpha;
+        let mut max_evaluation = -WIN_SCORE;
+
+        if depth >= MIN_DEPTH_TO_CACHE {
+            if let Some(cached_value) = search.cache.get_valuation_bounds(depth, game_state) {
+                if cached_value.alpha <= alpha && cached_value.beta >= beta {
+                    return cached_value.value;
+                }
+
+                if cached_value.value > cached_value.alpha && cached_value.value > alpha {
+                    if cached_value.value >= beta {
+                        return cached_value.value;
+                    }
+                    alpha = cached_value.value;
+                    max_evaluation = alpha;
+                }
+            }
+        }
+
+        game_state.get_children_states_with_moves_reuse_vec(reused_children_vec, &mut children_with_moves);
+        order_children_states_with_moves(game_state, ply, &mut children_with_moves, maximizing_player, search.move_ordering);
+
+        let mut best_move = None;
+        for (i, (child, child_move)) in children_with_moves.iter().enumerate() {
+            let evaluation = step_back(internal_move_ordered_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>(child, false, depth - 1, step_forward(alpha), step_forward(beta), search, &mut reusable_vec_for_children));
+            if evaluation > max_evaluation {
+                max_evaluation = evaluation;
+                best_move = Some(*child_move);
+                if max_evaluation >= beta {
+                    search.move_ordering.store_cutoff(ply, depth, *child_move, i == 0);
+                    break;
+                }
+                if max_evaluation > alpha {
+                    alpha = max_evaluation;
+                }
+            }
+        }
+
+        if let Some(best_move) = best_move {
+            search.move_ordering.store_best_move(game_state, best_move);
+        }
+        if depth >= MIN_DEPTH_TO_CACHE {
+            search.cache.insert_valuation_bounds(depth, *game_state, Bounds { value: max_evaluation, alpha: original_alpha, beta });
+        }
+        return max_evaluation;
+    } else {
+        let original_beta = beta;
+        let mut min_evaluation = WIN_SCORE;
+
+        if depth >= MIN_DEPTH_TO_CACHE {
+            if let Some(cached_value) = search.cache.get_valuation_bounds(depth, game_state) {
+                if cached_value.alpha <= alpha && cached_value.beta >= beta {
+                    return cached_value.value;
+                }
+
+                if cached_value.value < cached_value.beta && cached_value.value < beta {
+                    if cached_value.value <= alpha {
+                        return cached_value.value;
+                    }
+                    beta = cached_value.value;
+                    min_evaluation = beta;
+                }
+            }
+        }
+
+        game_state.get_children_states_with_moves_reuse_vec(reused_children_vec, &mut children_with_moves);
+        order_children_states_with_moves(game_state, ply, &mut children_with_moves, maximizing_player, search.move_ordering);
+
+        let mut best_move = None;
+        for (i, (child, child_move)) in children_with_moves.iter().enumerate() {
+            let evaluation = step_back(internal_move_ordered_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>(child, true, depth - 1, step_forward(alpha), step_forward(beta), search, &mut reusable_vec_for_children));
+            if evaluation < min_evaluation {
+                min_evaluation = evaluation;
+                best_move = Some(*child_move);
+                if min_evaluation <= alpha {
+                    search.move_ordering.store_cutoff(ply, depth, *child_move, i == 0);
+                    break;
+                }
+                if min_evaluation < beta {
+                    beta = min_evaluation;
+                }
+            }
+        }
+
+        if let Some(best_move) = best_move {
+            search.move_ordering.store_best_move(game_state, best_move);
+        }
+        if depth >= MIN_DEPTH_TO_CACHE {
+            search.cache.insert_valuation_bounds(depth, *game_state, Bounds { value: min_evaluation, alpha, beta: original_beta });
+        }
+        return min_evaluation;
+    }
+}
+
+// Cached minimax that orders the children with the best move, killer moves and history heuristic in addition to the static evaluation
+pub fn move_ordered_minimax<GS: GameState + SantoriniEval + MoveIdentity, const MIN_DEPTH_TO_SORT: usize, const MIN_DEPTH_TO_CACHE: usize>(game_state: &GS, depth: usize) -> (f32, usize) {
+    let mut move_ordering = MoveOrdering::new(MoveOrderingConfig::ALL);
+    return move_ordered_minimax_with_ordering::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>(game_state, depth, &mut move_ordering);
+}
+
+// The move ordering can be reused between searches and contains the cutoff statistics afterwards
+pub fn move_ordered_minimax_with_ordering<GS: GameState + SantoriniEval + MoveIdentity, const MIN_DEPTH_TO_SORT: usize, const MIN_DEPTH_TO_CACHE: usize>(
+    game_state: &GS,
+    depth: usize,
+    move_ordering: &mut MoveOrdering<GS>,
+) -> (f32, usize) {
+    let mut cache = MinimaxCache::new();
+    let mut search = MoveOrderedSearch {
+        cache: &mut cache,
+        move_ordering,
+        root_depth: depth,
+        evaluated_states: 0,
+    };
+
+    let result = internal_move_ordered_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>(
+        game_state,
+        game_state.is_player_a_turn(),
+        depth,
+        f32::NEG_INFINITY,
+        f32::INFINITY,
+        &mut search,
+        &mut Vec::with_capacity(64),
+    );
+
+    return (result, search.evaluated_states);
+}
+
 #[async_recursion::async_recursion]
 async fn internal_parallel_minimax<GS: GameState + SantoriniEval + 'static, const MIN_DEPTH_TO_SORT: usize, const MIN_DEPTH_TO_PARALLELIZE: usize>(
     game_state: GS,
@@ -376,9 +902,9 @@ async fn internal_parallel_minimax<GS: GameState + SantoriniEval + 'static, cons
     }
 
     if game_state.has_player_a_won() {
-        return f32::INFINITY;
+        return WIN_SCORE;
     } else if game_state.has_player_b_won() {
-        return f32::NEG_INFINITY;
+        return -WIN_SCORE;
     }
 
     if depth == 0 {
@@ -388,19 +914,19 @@ async fn internal_parallel_minimax<GS: GameState + SantoriniEval + 'static, cons
     let mut children_states = game_state.get_children_states();
     if children_states.is_empty() {
         return if maximizing_player {
-            f32::NEG_INFINITY
+            -WIN_SCORE
         } else {
-            f32::INFINITY
+            WIN_SCORE
         };
     } else if depth >= MIN_DEPTH_TO_SORT {
         order_children_states(&mut children_states, maximizing_player);
     }
 
     if maximizing_player {
-        let mut max_evaluation = f32::NEG_INFINITY;
+        let mut max_evaluation = -WIN_SCORE;
 
         let first_child = children_states.first().expect("It was just checked that the vector is not empty");
-        let first_evaluation = internal_parallel_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_PARALLELIZE>(*first_child, false, depth - 1, alpha, beta).await;
+        let first_evaluation = step_back(internal_parallel_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_PARALLELIZE>(*first_child, false, depth - 1, step_forward(alpha), step_forward(beta)).await);
         if first_evaluation > max_evaluation {
             max_evaluation = first_evaluation;
             if max_evaluation >= beta {
@@ -415,7 +941,7 @@ async fn internal_parallel_minimax<GS: GameState + SantoriniEval + 'static, cons
 
         for child in children_states.into_iter().skip(1) {
             tasks.push(tokio::spawn(async move {
-                return alpha_beta_sorted_minimax_internal::<GS, MIN_DEPTH_TO_SORT>(&child, false, depth - 1, alpha, beta, &mut Vec::with_capacity(64), &mut 0);
+                return step_back(alpha_beta_sorted_minimax_internal::<GS, MIN_DEPTH_TO_SORT>(&child, false, depth - 1, step_forward(alpha), step_forward(beta), &mut Vec::with_capacity(64), &mut 0));
             }));
         }
 
@@ -434,10 +960,10 @@ async fn internal_parallel_minimax<GS: GameState + SantoriniEval + 'static, cons
 
         return max_evaluation;
     } else {
-        let mut min_evaluation = f32::INFINITY;
+        let mut min_evaluation = WIN_SCORE;
 
         let first_child = children_states.first().expect("It was just checked that the vector is not empty");
-        let first_evaluation = internal_parallel_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_PARALLELIZE>(*first_child, true, depth - 1, alpha, beta).await;
+        let first_evaluation = step_back(internal_parallel_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_PARALLELIZE>(*first_child, true, depth - 1, step_forward(alpha), step_forward(beta)).await);
         if first_evaluation < min_evaluation {
             min_evaluation = first_evaluation;
             if min_evaluation <= alpha {
@@ -452,7 +978,7 @@ async fn internal_parallel_minimax<GS: GameState + SantoriniEval + 'static, cons
 
         for child in children_states.into_iter().skip(1) {
             tasks.push(tokio::spawn(async move {
-                return alpha_beta_sorted_minimax_internal::<GS, MIN_DEPTH_TO_SORT>(&child, true, depth - 1, alpha, beta, &mut Vec::with_capacity(64), &mut 0);
+                return step_back(alpha_beta_sorted_minimax_internal::<GS, MIN_DEPTH_TO_SORT>(&child, true, depth - 1, step_forward(alpha), step_forward(beta), &mut Vec::with_capacity(64), &mut 0));
             }));
         }
 
@@ -501,9 +1027,9 @@ fn internal_cached_minimax_no_count<GS: GameState + SantoriniEval, const MIN_DEP
     reused_children_vec: &mut Vec<GS>,
 ) -> f32 {
     if game_state.has_player_a_won() {
-        return f32::INFINITY;
+        return WIN_SCORE;
     } else if game_state.has_player_b_won() {
-        return f32::NEG_INFINITY;
+        return -WIN_SCORE;
     }
 
     if depth == 0 {
@@ -514,7 +1040,7 @@ fn internal_cached_minimax_no_count<GS: GameState + SantoriniEval, const MIN_DEP
 
     if maximizing_player {
         let original_alpha = alpha;
-        let mut max_evaluation = f32::NEG_INFINITY;
+        let mut max_evaluation = -WIN_SCORE;
 
         if depth >= MIN_DEPTH_TO_CACHE {
             if let Some(cached_value) = cache.get_valuation_bounds(depth, game_state) {
@@ -522,7 +1048,7 @@ fn internal_cached_minimax_no_count<GS: GameState + SantoriniEval, const MIN_DEP
                     return cached_value.value;
                 }
 
-                if cached_value.beta <= beta && cached_value.value > alpha {
+                if cached_value.value > cached_value.alpha && cached_value.value > alpha {
                     if cached_value.value >= beta {
                         return cached_value.value;
                     }
@@ -540,7 +1066,7 @@ fn internal_cached_minimax_no_count<GS: GameState + SantoriniEval, const MIN_DEP
         }
 
         for child in reused_children_vec {
-            let evaluation = internal_cached_minimax_no_count::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>(child, false, depth - 1, alpha, beta, cache, &mut reusable_vec_for_children);
+            let evaluation = step_back(internal_cached_minimax_no_count::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>(child, false, depth - 1, step_forward(alpha), step_forward(beta), cache, &mut reusable_vec_for_children));
             if evaluation > max_evaluation {
                 max_evaluation = evaluation;
                 if max_evaluation >= beta {
@@ -558,7 +1084,7 @@ fn internal_cached_minimax_no_count<GS: GameState + SantoriniEval, const MIN_DEP
         return max_evaluation;
     } else {
         let original_beta = beta;
-        let mut min_evaluation = f32::INFINITY;
+        let mut min_evaluation = WIN_SCORE;
 
         if depth >= MIN_DEPTH_TO_CACHE {
             if let Some(cached_value) = cache.get_valuation_bounds(depth, game_state) {
@@ -566,7 +1092,7 @@ fn internal_cached_minimax_no_count<GS: GameState + SantoriniEval, const MIN_DEP
                     return cached_value.value;
                 }
 
-                if cached_value.alpha >= alpha && cached_value.value < beta {
+                if cached_value.value < cached_value.beta && cached_value.value < beta {
                     if cached_value.value <= alpha {
                         return cached_value.value;
                     }
@@ -584,7 +1110,7 @@ fn internal_cached_minimax_no_count<GS: GameState + SantoriniEval, const MIN_DEP
         }
 
         for child in reused_children_vec {
-            let evaluation = internal_cached_minimax_no_count::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>(child, true, depth - 1, alpha, beta, cache, &mut reusable_vec_for_children);
+            let evaluation = step_back(internal_cached_minimax_no_count::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>(child, true, depth - 1, step_forward(alpha), step_forward(beta), cache, &mut reusable_vec_for_children));
             if evaluation < min_evaluation {
                 min_evaluation = evaluation;
                 if min_evaluation <= alpha {
@@ -616,39 +1142,65 @@ pub fn minimax<GS: GameState + SantoriniEval>(game_state: &GS, depth: usize, alp
 }
 
 
+// Everything a search with a custom heuristic shares between its states
+struct CustomHeuristicSearch<'a, GS: GameState, F: Fn(&GS) -> f32> {
+    cache: &'a mut MinimaxCache<GS, 100>,
+    heuristic_function: &'a F,
+    // Depth of the threat extension at the leaves, 0 disables the extension
+    max_extension_depth: usize,
+    stop: Option<&'a AtomicBool>,
+    evaluated_states: usize,
+}
+
+impl<GS: GameState, F: Fn(&GS) -> f32> CustomHeuristicSearch<'_, GS, F> {
+    // The value of a stopped search is meaningless, it is discarded by the caller and never cached
+    fn is_stopped(&self) -> bool {
+        return self.stop.is_some_and(|stop| stop.load(Ordering::Relaxed));
+    }
+}
+
 fn internal_cached_minimax_custom_heuristic<GS: GameState + SantoriniEval, F: Fn(&GS) -> f32>(
     game_state: &GS,
     maximizing_player: bool,
     depth: usize,
     mut alpha: f32,
     mut beta: f32,
-    cache: &mut MinimaxCache<GS, 100>,
+    search: &mut CustomHeuristicSearch<GS, F>,
     reused_children_vec: &mut Vec<GS>,
-    heuristic_function: &F,
 ) -> f32 {
+    search.evaluated_states += 1;
+
+    if search.is_stopped() {
+        return 0.0;
+    }
+
     if game_state.has_player_a_won() {
-        return f32::INFINITY;
+        return WIN_SCORE;
     } else if game_state.has_player_b_won() {
-        return f32::NEG_INFINITY;
+        return -WIN_SCORE;
     }
 
     if depth == 0 {
-        return heuristic_function(game_state);
+        let heuristic_function = search.heuristic_function;
+        if search.max_extension_depth > 0 {
+            return internal_threat_extension(game_state, maximizing_player, search.max_extension_depth, alpha, beta, &mut search.evaluated_states, &|state: &GS| clamp_heuristic(heuristic_function(state)));
+        }
+        return clamp_heuristic(heuristic_function(game_state));
     }
 
     let mut reusable_vec_for_children = Vec::with_capacity(64);
 
     if maximizing_player {
         let original_alpha = alpha;
-        let mut max_evaluation = f32::NEG_INFINITY;
+        let mut max_evaluation = -WIN_SCORE;
 
         if depth >= 3 {
-            if let Some(cached_value) = cache.get_valuation_bounds(depth, game_state) {
+            if let Some(cached_value) = search.cache.get_valuation_bounds(depth, game_state) {
                 if cached_value.alpha <= alpha && cached_value.beta >= beta {
                     return cached_value.value;
                 }
 
-                if cached_value.beta <= beta && cached_value.value > alpha {
+                if cached_value.value > cached_value.alpha && cached_value.value > alpha {
                     if cached_value.value >= beta {
                         return cached_value.value;
                     }
@@ -661,12 +1213,12 @@ fn internal_cached_minimax_custom_heuristic<GS: GameState + SantoriniEval, F: Fn
         game_state.get_children_states_reuse_vec(reused_children_vec);
         if !reused_children_vec.is_empty() {
             if depth >= 3 {
-                order_children_states_with_function(reused_children_vec, maximizing_player, heuristic_function);
+                order_children_states_with_function(reused_children_vec, maximizing_player, search.heuristic_function);
             }
         }
 
         for child in reused_children_vec {
-            let evaluation = internal_cached_minimax_custom_heuristic::<GS, F>(child, false, depth - 1, alpha, beta, cache, &mut reusable_vec_for_children, heuristic_function);
+            let evaluation = step_back(internal_cached_minimax_custom_heuristic::<GS, F>(child, false, depth - 1, step_forward(alpha), step_forward(beta), search, &mut reusable_vec_for_children));
             if evaluation > max_evaluation {
                 max_evaluation = evaluation;
                 if max_evaluation >= beta {
@@ -678,21 +1230,21 @@ fn internal_cached_minimax_custom_heuristic<GS: GameState + SantoriniEval, F: Fn
             }
         }
 
-        if depth >= 3 {
-            cache.insert_valuation_bounds(depth, *game_state, Bounds { value: max_evaluation, alpha: original_alpha, beta });
+        if depth >= 3 && !search.is_stopped() {
+            search.cache.insert_valuation_bounds(depth, *game_state, Bounds { value: max_evaluation, alpha: original_alpha, beta });
         }
         return max_evaluation;
     } else {
         let original_beta = beta;
-        let mut min_evaluation = f32::INFINITY;
+        let mut min_evaluation = WIN_SCORE;
 
         if depth >= 3 {
-            if let Some(cached_value) = cache.get_valuation_bounds(depth, game_state) {
+            if let Some(cached_value) = search.cache.get_valuation_bounds(depth, game_state) {
                 if cached_value.alpha <= alpha && cached_value.beta >= beta {
                     return cached_value.value;
                 }
 
-                if cached_value.alpha >= alpha && cached_value.value < beta {
+                if cached_value.value < cached_value.beta && cached_value.value < beta {
                     if cached_value.value <= alpha {
                         return cached_value.value;
                     }
@@ -705,12 +1257,12 @@ fn internal_cached_minimax_custom_heuristic<GS: GameState + SantoriniEval, F: Fn
         game_state.get_children_states_reuse_vec(reused_children_vec);
         if !reused_children_vec.is_empty() {
             if depth >= 3 {
-                order_children_states_with_function(reused_children_vec, maximizing_player, heuristic_function);
+                order_children_states_with_function(reused_children_vec, maximizing_player, search.heuristic_function);
             }
         }
 
         for child in reused_children_vec {
-            let evaluation = internal_cached_minimax_custom_heuristic::<GS, F>(child, true, depth - 1, alpha, beta, cache, &mut reusable_vec_for_children, heuristic_function);
+            let evaluation = step_back(internal_cached_minimax_custom_heuristic::<GS, F>(child, true, depth - 1, step_forward(alpha), step_forward(beta), search, &mut reusable_vec_for_children));
             if evaluation < min_evaluation {
                 min_evaluation = evaluation;
                 if min_evaluation <= alpha {
@@ -722,8 +1274,8 @@ fn internal_cached_minimax_custom_heuristic<GS: GameState + SantoriniEval, F: Fn
             }
         }
 
-        if depth >= 3 {
-            cache.insert_valuation_bounds(depth, *game_state, Bounds { value: min_evaluation, alpha, beta: original_beta });
+        if depth >= 3 && !search.is_stopped() {
+            search.cache.insert_valuation_bounds(depth, *game_state, Bounds { value: min_evaluation, alpha, beta: original_beta });
         }
         return min_evaluation;
     }
@@ -735,16 +1287,75 @@ pub fn minimax_custom_heuristic<GS: GameState + SantoriniEval, F: Fn(&GS) -> f32
     cache: &mut MinimaxCache<GS, 100>,
     heuristic_function: &F,
 ) -> f32 {
-    return internal_cached_minimax_custom_heuristic::<GS, F>(
+    return minimax_custom_heuristic_with_extension(game_state, depth, 0, cache, heuristic_function);
+}
+
+// The cache should not be shared with searches that use a different max_extension_depth, as the cached values differ
+pub fn minimax_custom_heuristic_with_extension<GS: GameState + SantoriniEval, F: Fn(&GS) -> f32>(
+    game_state: &GS,
+    depth: usize,
+    max_extension_depth: usize,
+    cache: &mut MinimaxCache<GS, 100>,
+    heuristic_function: &F,
+) -> f32 {
+    return minimax_custom_heuristic_with_count(game_state, depth, max_extension_depth, cache, heuristic_function).0;
+}
+
+// Also returns the amount of evaluated states
+pub fn minimax_custom_heuristic_with_count<GS: GameState + SantoriniEval, F: Fn(&GS) -> f32>(
+    game_state: &GS,
+    depth: usize,
+    max_extension_depth: usize,
+    cache: &mut MinimaxCache<GS, 100>,
+    heuristic_function: &F,
+) -> (f32, usize) {
+    let mut search = CustomHeuristicSearch {
+        cache,
+        heuristic_function,
+        max_extension_depth,
+        stop: None,
+        evaluated_states: 0,
+    };
+    let result = internal_cached_minimax_custom_heuristic::<GS, F>(
         game_state,
         game_state.is_player_a_turn(),
         depth,
         f32::NEG_INFINITY,
         f32::INFINITY,
-        cache,
+        &mut search,
         &mut Vec::with_capacity(64),
+    );
+    return (result, search.evaluated_states);
+}
+
+// Checks the stop flag at every state, returns None if the search was stopped before it finished
+pub fn minimax_custom_heuristic_with_stop<GS: GameState + SantoriniEval, F: Fn(&GS) -> f32>(
+    game_state: &GS,
+    depth: usize,
+    cache: &mut MinimaxCache<GS, 100>,
+    heuristic_function: &F,
+    stop: &AtomicBool,
+) -> Option<(f32, usize)> {
+    let mut search = CustomHeuristicSearch {
+        cache,
         heuristic_function,
+        max_extension_depth: 0,
+        stop: Some(stop),
+        evaluated_states: 0,
+    };
+    let result = internal_cached_minimax_custom_heuristic::<GS, F>(
+        game_state,
+        game_state.is_player_a_turn(),
+        depth,
+        f32::NEG_INFINITY,
+        f32::INFINITY,
+        &mut search,
+        &mut Vec::with_capacity(64),
     );
+    if search.is_stopped() {
+        return None;
+    }
+    return Some((result, search.evaluated_states));
 }
 
 
@@ -796,4 +1407,113 @@ fn infinite_depth_minimax_internal<GS: GameState + SantoriniEval>(
 pub fn infinite_depth_minimax<GS: GameState + SantoriniEval>(game_state: GS, solved_cache: &mut FnvHashMap<GS, bool>) -> bool {
     let mut reused_children_vec = Vec::with_capacity(64);
     return infinite_depth_minimax_internal(game_state, game_state.is_player_a_turn(), &mut reused_children_vec, solved_cache);
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+    use crate::game_state::{GameState, SantoriniEval};
+    use crate::game_state::game_state_5x5_binary_composite::GameState5x5BinaryComposite;
+    use crate::generic_game_state::generic_santorini_game_state::GenericSantoriniGameState;
+    use crate::generic_game_state::GenericGameState;
+    use crate::minimax::minimax_cache::{Bounds, MinimaxCache};
+    use crate::minimax::{aspiration_window_minimax, cached_minimax, cached_minimax_with_extension, minimax, minimax_custom_heuristic, minimax_custom_heuristic_with_extension, mtdf_minimax, principal_variation_minimax};
+    use crate::minimax::score::{step_back, win_score};
+
+    type GS = GameState5x5BinaryComposite;
+
+    fn get_random_states(count: usize) -> Vec<GS> {
+        let mut rng = rand::thread_rng();
+        let mut states = Vec::with_capacity(count);
+        while states.len() < count {
+            let block_count = rng.gen_range(20..=50);
+            let state = GS::from_generic_game_state(&GenericSantoriniGameState::<5, 5, 2>::generate_random_state_with_blocks_rng(&mut rng, block_count));
+            if !state.has_player_a_won() && !state.has_player_b_won() {
+                states.push(state);
+            }
+        }
+        return states;
+    }
+
+    /*
+    A cached value that failed low (value <= alpha) is only an upper bound, and one that failed high (value >= beta) only a lower bound.
+    The searches used to take the value of a failed low search as lower bound in maximizing states (and the value of a failed high
+    search as upper bound in minimizing states), which returned the cached bound instead of the exact value.
+     */
+    #[test]
+    fn test_cached_bounds_are_only_used_on_the_valid_side() {
+        let depth = 3;
+        let heuristic = |state: &GS| state.get_child_evaluation();
+        for state in get_random_states(50) {
+            let exact_value = minimax_custom_heuristic(&state, depth, &mut MinimaxCache::new(), &heuristic);
+            let exact_cached_minimax_value = minimax(&state, depth, f32::NEG_INFINITY, f32::INFINITY, &mut MinimaxCache::new());
+
+            let failed_bounds = |value: f32| if state.is_player_a_turn() {
+                // Failed low, so the true value is at most the cached value
+                Bounds { value: value + 10.0, alpha: value + 20.0, beta: value + 30.0 }
+            } else {
+                // Failed high, so the true value is at least the cached value
+                Bounds { value: value - 10.0, alpha: value - 30.0, beta: value - 20.0 }
+            };
+
+            let mut cache = MinimaxCache::new();
+            cache.insert_valuation_bounds(depth, state, failed_bounds(exact_value));
+            assert_eq!(minimax_custom_heuristic(&state, depth, &mut cache, &heuristic), exact_value, "{}", state.to_generic_game_state());
+
+            let mut cache = MinimaxCache::new();
+            cache.insert_valuation_bounds(depth, state, failed_bounds(exact_cached_minimax_value));
+            assert_eq!(minimax(&state, depth, f32::NEG_INFINITY, f32::INFINITY, &mut cache), exact_cached_minimax_value, "{}", state.to_generic_game_state());
+        }
+    }
+
+    // The null window searches use the same evaluation (0.0 for undecided states) as cached_minimax, so they must return the same values
+    #[test]
+    fn test_null_window_searches_match_minimax_custom_heuristic() {
+        let no_heuristic = |_: &GS| 0.0;
+        for state in get_random_states(100) {
+            for depth in 0..=4 {
+                let expected_value = minimax_custom_heuristic(&state, depth, &mut MinimaxCache::new(), &no_heuristic);
+                let position = state.to_generic_game_state().to_position_string();
+                assert_eq!(cached_minimax::<GS, 3, 3>(&state, depth).0, expected_value, "cached_minimax at depth {} in {}", depth, position);
+                assert_eq!(principal_variation_minimax::<GS, 3, 3>(&state, depth).0, expected_value, "principal_variation_minimax at depth {} in {}", depth, position);
+                assert_eq!(mtdf_minimax::<GS, 3, 3>(&state, depth).0, expected_value, "mtdf_minimax at depth {} in {}", depth, position);
+                assert_eq!(aspiration_window_minimax::<GS, 3, 3>(&state, depth, 1.0).0, expected_value, "aspiration_window_minimax at depth {} in {}", depth, position);
+            }
+        }
+    }
+
+    // A depth 1 search only sees the own move, the extension has to find the immediate win of the opponent behind it
+    #[test]
+    fn test_threat_extension_finds_immediate_wins_of_the_opponent_behind_the_horizon() {
+        let no_heuristic = |_: &GS| 0.0;
+        let mut threatened_states = 0;
+        for state in get_random_states(2000) {
+            let generic_state = state.to_generic_game_state();
+            let player_a_turn = state.is_player_a_turn();
+            let children_states = state.get_children_states();
+            if children_states.is_empty() || generic_state.can_player_win_immediately(player_a_turn) {
+                continue;
+            }
+            // The extension expands the children, so it also finds wins by blocking the opponent with the move
+            if children_states.iter().any(|child| child.get_children_states().is_empty()) {
+                continue;
+            }
+
+            // Every move of the player to move allows the opponent to win with the next move
+            let is_threatened = children_states.iter().all(|child| child.to_generic_game_state().can_player_win_immediately(!player_a_turn));
+            let expected_value = if is_threatened {
+                threatened_states += 1;
+                step_back(step_back(win_score(!player_a_turn)))
+            } else {
+                0.0
+            };
+
+            let position = generic_state.to_position_string();
+            assert_eq!(cached_minimax::<GS, 3, 3>(&state, 1).0, 0.0, "{}", position);
+            assert_eq!(minimax_custom_heuristic(&state, 1, &mut MinimaxCache::new(), &no_heuristic), 0.0, "{}", position);
+            assert_eq!(cached_minimax_with_extension::<GS, 3, 3>(&state, 1, 1).0, expected_value, "{}", position);
+            assert_eq!(minimax_custom_heuristic_with_extension(&state, 1, 1, &mut MinimaxCache::new(), &no_heuristic), expected_value, "{}", position);
+        }
+        assert!(threatened_states > 0);
+    }
+}
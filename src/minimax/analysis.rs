@@ -0,0 +1,173 @@
+use crate::game_state::{GameState, SantoriniEval};
+use crate::minimax::minimax_cache::MinimaxCache;
+use crate::minimax::minimax_custom_heuristic_with_count;
+use crate::minimax::order_children_states_with_function;
+use crate::minimax::score::step_back;
+
+#[derive(Clone, Debug)]
+pub struct RootMoveAnalysis<GS: GameState> {
+    // State after the move was played
+    pub child_state: GS,
+    // Score of the root state if this move is played, from the perspective of player A (see minimax::score)
+    pub score: f32,
+    // Depth of the search from the root state
+    pub depth: usize,
+    pub evaluated_states: usize,
+    // Starts with child_state, followed by the best responses of both players
+    pub principal_variation: Vec<GS>,
+}
+
+/**
+Evaluates every move of the root state and returns the best number_of_moves moves, best move first.
+Every child is searched with a full window, so the scores of all returned moves are exact for the given depth.
+Use a heuristic function that always returns 0.0 to only distinguish wins, losses and unknown outcomes.
+ */
+pub fn analyze_root_moves<GS: GameState + SantoriniEval, F: Fn(&GS) -> f32>(
+    game_state: &GS,
+    depth: usize,
+    number_of_moves: usize,
+    heuristic_function: &F,
+) -> Vec<RootMoveAnalysis<GS>> {
+    assert!((1..100).contains(&depth));
+    let maximizing_player = game_state.is_player_a_turn();
+    let mut cache = MinimaxCache::new();
+
+    let mut children_states = game_state.get_children_states();
+    order_children_states_with_function(&mut children_states, maximizing_player, heuristic_function);
+
+    let mut analyzed_moves = Vec::with_capacity(children_states.len());
+    for child_state in children_states {
+        let (child_score, evaluated_states) = minimax_custom_heuristic_with_count(&child_state, depth - 1, 0, &mut cache, heuristic_function);
+        analyzed_moves.push(RootMoveAnalysis {
+            child_state,
+            score: step_back(child_score),
+            depth,
+            evaluated_states,
+            principal_variation: Vec::new(),
+        });
+    }
+
+    // Stable sort, so moves with equal scores keep the order of the heuristic
+    if maximizing_player {
+        analyzed_moves.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+    } else {
+        analyzed_moves.sort_by(|a, b| a.score.partial_cmp(&b.score).unwrap());
+    }
+    analyzed_moves.truncate(number_of_moves);
+
+    for analyzed_move in analyzed_moves.iter_mut() {
+        let (principal_variation, evaluated_states) = get_principal_variation(analyzed_move.child_state, depth - 1, &mut cache, heuristic_function);
+        analyzed_move.principal_variation = principal_variation;
+        analyzed_move.evaluated_states += evaluated_states;
+    }
+
+    return analyzed_moves;
+}
+
+// Follows the children whose score matches the score of their parent, the cache makes the repeated searches cheap
+pub(crate) fn get_principal_variation<GS: GameState + SantoriniEval, F: Fn(&GS) -> f32>(
+    game_state: GS,
+    depth: usize,
+    cache: &mut MinimaxCache<GS, 100>,
+    heuristic_function: &F,
+) -> (Vec<GS>, usize) {
+    let mut principal_variation = vec![game_state];
+    let mut evaluated_states = 0;
+
+    let mut current_state = game_state;
+    let (mut current_score, states) = minimax_custom_heuristic_with_count(&current_state, depth, 0, cache, heuristic_function);
+    evaluated_states += states;
+
+    for remaining_depth in (0..depth).rev() {
+        if current_state.has_player_a_won() || current_state.has_player_b_won() {
+            break;
+        }
+
+        let mut children_states = current_state.get_children_states();
+        order_children_states_with_function(&mut children_states, current_state.is_player_a_turn(), heuristic_function);
+
+        let mut best_child = None;
+        for child_state in children_states {
+            let (child_score, states) = minimax_custom_heuristic_with_count(&child_state, remaining_depth, 0, cache, heuristic_function);
+            evaluated_states += states;
+            if step_back(child_score) == current_score {
+                best_child = Some((child_state, child_score));
+                break;
+            }
+        }
+
+        let Some((child_state, child_score)) = best_child else {
+            break;
+        };
+        principal_variation.push(child_state);
+        current_state = child_state;
+        current_score = child_score;
+    }
+
+    return (principal_variation, evaluated_states);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+    use crate::game_state::{GameState, SantoriniEval};
+    use crate::game_state::game_state_5x5_binary_composite::GameState5x5BinaryComposite;
+    use crate::generic_game_state::generic_santorini_game_state::GenericSantoriniGameState;
+    use crate::generic_game_state::GenericGameState;
+    use crate::minimax::analysis::analyze_root_moves;
+    use crate::minimax::minimax_cache::MinimaxCache;
+    use crate::minimax::minimax_custom_heuristic;
+    use crate::minimax::score::{step_back, win_score};
+    use crate::strategy::heuristics::dynamic_heuristic::{dynamic_heuristic, DynamicHeuristicParams};
+
+    type GS = GameState5x5BinaryComposite;
+
+    #[test]
+    fn test_root_move_scores_match_minimax_of_each_child() {
+        let mut rng = rand::thread_rng();
+        let heuristic = |state: &GS| state.get_child_evaluation();
+        let mut tested_states = 0;
+        while tested_states < 50 {
+            let block_count = rng.gen_range(20..=50);
+            let state = GS::from_generic_game_state(&GenericSantoriniGameState::<5, 5, 2>::generate_random_state_with_blocks_rng(&mut rng, block_count));
+            if state.has_player_a_won() || state.has_player_b_won() {
+                continue;
+            }
+            tested_states += 1;
+
+            let depth = rng.gen_range(1..=3);
+            let children_states = state.get_children_states();
+            let analyzed_moves = analyze_root_moves(&state, depth, usize::MAX, &heuristic);
+            assert_eq!(analyzed_moves.len(), children_states.len());
+
+            for analyzed_move in analyzed_moves.iter() {
+                assert!(children_states.contains(&analyzed_move.child_state));
+                assert_eq!(analyzed_move.score, step_back(minimax_custom_heuristic(&analyzed_move.child_state, depth - 1, &mut MinimaxCache::new(), &heuristic)));
+                assert_eq!(analyzed_move.principal_variation[0], analyzed_move.child_state);
+            }
+
+            // The best move has the score of the root state
+            if let Some(best_move) = analyzed_moves.first() {
+                assert_eq!(best_move.score, minimax_custom_heuristic(&state, depth, &mut MinimaxCache::new(), &heuristic));
+            }
+            let scores: Vec<f32> = analyzed_moves.iter().map(|analyzed_move| analyzed_move.score).collect();
+            if state.is_player_a_turn() {
+                assert!(scores.windows(2).all(|pair| pair[0] >= pair[1]));
+            } else {
+                assert!(scores.windows(2).all(|pair| pair[0] <= pair[1]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_won_children_are_not_evaluated_with_the_heuristic() {
+        // Player A wins with a1-b1, dynamic_heuristic doesn't support the won child state
+        let state = GS::from_generic_game_state(&GenericSantoriniGameState::<5, 5, 2>::from_position_string("2310000000000000000000000/0,24/12,18/A").unwrap());
+        let params = DynamicHeuristicParams::default();
+        let analyzed_moves = analyze_root_moves(&state, 2, 3, &|state: &GS| dynamic_heuristic(state, &params));
+        assert_eq!(analyzed_moves.len(), 3);
+        assert!(analyzed_moves[0].child_state.has_player_a_won());
+        assert_eq!(analyzed_moves[0].score, step_back(win_score(true)));
+    }
+}
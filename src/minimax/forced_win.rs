@@ -0,0 +1,227 @@
+use fnv::FnvHashMap;
+use crate::game_state::GameState;
+
+pub struct ForcedWinNode<GS: GameState> {
+    pub state: GS,
+    // Number of moves (plies) until the winning player has won from this state
+    pub moves_to_win: usize,
+    // For states where the winning player moves, this contains only the chosen winning move
+    // For states where the losing player moves, this contains every possible move
+    pub children: Vec<ForcedWinNode<GS>>,
+}
+
+pub struct ForcedWin<GS: GameState> {
+    pub player_a_wins: bool,
+    pub moves_to_win: usize,
+    pub tree: ForcedWinNode<GS>,
+}
+
+pub fn no_known_winner<GS: GameState>(_state: &GS) -> Option<bool> {
+    return None;
+}
+
+struct ForcedWinSearch<'a, GS: GameState, F: Fn(&GS) -> Option<bool>> {
+    player_a_wins: bool,
+    known_winner: &'a F,
+    // Smallest amount of remaining moves for which a forced win was found
+    win_cache: FnvHashMap<GS, usize>,
+    // Largest amount of remaining moves for which no forced win exists
+    no_win_cache: FnvHashMap<GS, usize>,
+}
+
+impl<'a, GS: GameState, F: Fn(&GS) -> Option<bool>> ForcedWinSearch<'a, GS, F> {
+    fn new(player_a_wins: bool, known_winner: &'a F) -> Self {
+        return ForcedWinSearch {
+            player_a_wins,
+            known_winner,
+            win_cache: FnvHashMap::default(),
+            no_win_cache: FnvHashMap::default(),
+        };
+    }
+
+    fn has_winner_won(&self, state: &GS) -> bool {
+        return if self.player_a_wins { state.has_player_a_won() } else { state.has_player_b_won() };
+    }
+
+    fn has_loser_won(&self, state: &GS) -> bool {
+        return if self.player_a_wins { state.has_player_b_won() } else { state.has_player_a_won() };
+    }
+
+    // Checks if the winning player can force a win in the given state within the remaining moves
+    fn wins_within(&mut self, state: &GS, remaining_moves: usize, reusable_child_states: &mut Vec<GS>) -> bool {
+        if self.has_winner_won(state) {
+            return true;
+        } else if self.has_loser_won(state) {
+            return false;
+        }
+
+        if let Some(player_a_wins) = (self.known_winner)(state) {
+            if player_a_wins != self.player_a_wins {
+                // Even with infinite moves, the winning player cannot force a win
+                return false;
+            }
+        }
+
+        if let Some(&cached_remaining_moves) = self.win_cache.get(state) {
+            if cached_remaining_moves <= remaining_moves {
+                return true;
+            }
+        }
+        if let Some(&cached_remaining_moves) = self.no_win_cache.get(state) {
+            if cached_remaining_moves >= remaining_moves {
+                return false;
+            }
+        }
+
+        state.get_children_states_reuse_vec(reusable_child_states);
+        let winner_to_move = state.is_player_a_turn() == self.player_a_wins;
+
+        let result = if reusable_child_states.is_empty() {
+            // The player that cannot move loses
+            !winner_to_move
+        } else if remaining_moves == 0 {
+            false
+        } else {
+            let mut reusable_vec_for_child_states = Vec::with_capacity(64);
+
+            if winner_to_move {
+                // Immediate wins are checked first, as they don't require any further search
+                reusable_child_states.iter().any(|child| self.has_winner_won(child)) ||
+                    reusable_child_states.iter().any(|child| self.wins_within(child, remaining_moves - 1, &mut reusable_vec_for_child_states))
+            } else {
+                reusable_child_states.iter().all(|child| self.wins_within(child, remaining_moves - 1, &mut reusable_vec_for_child_states))
+            }
+        };
+
+        if result {
+            self.win_cache.insert(*state, remaining_moves);
+        } else {
+            self.no_win_cache.insert(*state, remaining_moves);
+        }
+        return result;
+    }
+
+    fn get_moves_to_win(&mut self, state: &GS, max_moves: usize) -> Option<usize> {
+        let mut reusable_child_states = Vec::with_capacity(64);
+        return (0..=max_moves).find(|&moves| self.wins_within(state, moves, &mut reusable_child_states));
+    }
+
+    // Builds the forcing tree for a state that is known to be won within the given moves
+    fn build_tree(&mut self, state: &GS, moves_to_win: usize) -> ForcedWinNode<GS> {
+        if self.has_winner_won(state) {
+            return ForcedWinNode { state: *state, moves_to_win: 0, children: Vec::new() };
+        }
+
+        let children = state.get_children_states();
+        let winner_to_move = state.is_player_a_turn() == self.player_a_wins;
+
+        let mut child_nodes = Vec::new();
+        if winner_to_move {
+            // Choose the child with the fastest win
+            let mut best_child = None;
+            for child in children {
+                if let Some(child_moves_to_win) = self.get_moves_to_win(&child, moves_to_win - 1) {
+                    if best_child.is_none_or(|(_, best_moves)| child_moves_to_win < best_moves) {
+                        best_child = Some((child, child_moves_to_win));
+                    }
+                }
+            }
+            let (best_child, best_moves) = best_child.expect("State is known to be won, so a winning child must exist");
+            child_nodes.push(self.build_tree(&best_child, best_moves));
+        } else {
+            for child in children {
+                let child_moves_to_win = self.get_moves_to_win(&child, moves_to_win - 1)
+                    .expect("State is known to be won, so every child must be won as well");
+                child_nodes.push(self.build_tree(&child, child_moves_to_win));
+            }
+        }
+
+        return ForcedWinNode {
+            state: *state,
+            moves_to_win,
+            children: child_nodes,
+        };
+    }
+}
+
+
+/**
+Finds the shortest forced win for the given player from the given state, considering at most max_moves moves (plies).
+The known_winner function can be used to accelerate the search with precomputed winners (e.g. tablebases),
+it should return Some(true) if player A wins, Some(false) if player B wins and None if the winner is not known.
+ */
+pub fn shortest_forced_win<GS: GameState, F: Fn(&GS) -> Option<bool>>(
+    state: &GS,
+    player_a_wins: bool,
+    max_moves: usize,
+    known_winner: &F,
+) -> Option<ForcedWin<GS>> {
+    let mut search = ForcedWinSearch::new(player_a_wins, known_winner);
+
+    let moves_to_win = search.get_moves_to_win(state, max_moves)?;
+    let tree = search.build_tree(state, moves_to_win);
+
+    return Some(ForcedWin {
+        player_a_wins,
+        moves_to_win,
+        tree,
+    });
+}
+
+// Same as shortest_forced_win, but only returns the amount of moves, which avoids building the (potentially huge) forcing tree
+pub fn shortest_forced_win_moves<GS: GameState, F: Fn(&GS) -> Option<bool>>(
+    state: &GS,
+    player_a_wins: bool,
+    max_moves: usize,
+    known_winner: &F,
+) -> Option<usize> {
+    let mut search = ForcedWinSearch::new(player_a_wins, known_winner);
+    return search.get_moves_to_win(state, max_moves);
+}
+
+#[cfg(test)]
+mod tests {
+    use fnv::FnvHashMap;
+    use rand::Rng;
+    use crate::game_state::GameState;
+    use crate::game_state::game_state_5x5_binary_composite::GameState5x5BinaryComposite;
+    use crate::generic_game_state::generic_santorini_game_state::GenericSantoriniGameState;
+    use crate::generic_game_state::GenericGameState;
+    use crate::minimax::forced_win::{no_known_winner, shortest_forced_win, shortest_forced_win_moves, ForcedWinNode};
+    use crate::minimax::infinite_depth_minimax;
+
+    // Every leaf of the forcing tree has to be won by the winning player (or the losing player can't move) within the moves to win
+    fn check_forced_win_tree<GS: GameState>(node: &ForcedWinNode<GS>, player_a_wins: bool, remaining_moves: usize) {
+        assert!(node.moves_to_win <= remaining_moves);
+        if node.children.is_empty() {
+            let winner_has_won = if player_a_wins { node.state.has_player_a_won() } else { node.state.has_player_b_won() };
+            assert!(winner_has_won || (node.state.is_player_a_turn() != player_a_wins && node.state.get_children_states().is_empty()));
+            return;
+        }
+        for child in &node.children {
+            check_forced_win_tree(child, player_a_wins, node.moves_to_win - 1);
+        }
+    }
+
+    #[test]
+    fn test_shortest_forced_win_matches_infinite_depth_minimax() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let block_count = rng.gen_range(70..=85);
+            let generic_state = GenericSantoriniGameState::<5, 5, 2>::generate_random_state_with_blocks_rng(&mut rng, block_count);
+            let state = GameState5x5BinaryComposite::from_generic_game_state(&generic_state);
+            if state.has_player_a_won() || state.has_player_b_won() {
+                continue;
+            }
+            let player_a_wins = infinite_depth_minimax(state, &mut FnvHashMap::default());
+
+            // Every move builds a block, so no game from this state lasts longer than the remaining blocks
+            let max_moves = 100 - block_count + 1;
+            let forced_win = shortest_forced_win(&state, player_a_wins, max_moves, &no_known_winner)
+                .unwrap_or_else(|| panic!("No forced win found in {}", generic_state.to_position_string()));
+            assert_eq!(shortest_forced_win_moves(&state, player_a_wins, max_moves, &no_known_winner), Some(forced_win.moves_to_win));
+            assert_eq!(shortest_forced_win_moves(&state, !player_a_wins, max_moves, &no_known_winner), None);
+            check_forced_win_tree(&forced_win.tree, player_a_wins, forced_win.moves_to_win);
+        }
+    }
+}
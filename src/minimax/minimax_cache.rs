@@ -3,6 +3,7 @@ use std::collections::hash_map::Entry::{Occupied, Vacant};
 use crate::game_state::GameState;
 use fnv::FnvHashMap;
 
+// Scores (see minimax::score) are relative to the cached state, so wins keep their distance when the state is reached again
 #[derive(Copy, Clone, Debug)]
 pub struct Bounds {
     pub value: f32,
@@ -0,0 +1,123 @@
+use fnv::FnvHashMap;
+use crate::game_state::{GameState, SantoriniMove};
+
+// Tiles 0-24 are used directly, every other value (e.g. SantoriniMove::NO_TILE) uses the last index
+const TILE_INDEX_COUNT: usize = 26;
+const KILLER_MOVES_PER_PLY: usize = 2;
+
+#[derive(Copy, Clone, Debug)]
+pub struct MoveOrderingConfig {
+    // Search the best move that was found for the state in a previous search first
+    pub use_best_move: bool,
+    // Search moves that caused a cutoff in another state on the same ply first
+    pub use_killer_moves: bool,
+    // Prefer moves that caused many cutoffs anywhere in the search tree
+    pub use_history: bool,
+}
+
+impl MoveOrderingConfig {
+    pub const STATIC_ONLY: MoveOrderingConfig = MoveOrderingConfig { use_best_move: false, use_killer_moves: false, use_history: false };
+    pub const ALL: MoveOrderingConfig = MoveOrderingConfig { use_best_move: true, use_killer_moves: true, use_history: true };
+}
+
+/**
+Dynamic move ordering information that is collected during a search.
+Moves are ordered by: best move of the state, killer moves of the ply, history score and finally the static child evaluation.
+ */
+pub struct MoveOrdering<GS: GameState> {
+    pub config: MoveOrderingConfig,
+    best_moves: FnvHashMap<GS, SantoriniMove>,
+    killer_moves: Vec<[Option<SantoriniMove>; KILLER_MOVES_PER_PLY]>,
+    history: Vec<u32>,
+
+    // Amount of states that were cut off, and how many of those were cut off by the first searched child
+    pub cutoff_states: usize,
+    pub first_move_cutoff_states: usize,
+}
+
+impl<GS: GameState> MoveOrdering<GS> {
+    pub fn new(config: MoveOrderingConfig) -> Self {
+        return MoveOrdering {
+            config,
+            best_moves: FnvHashMap::default(),
+            killer_moves: Vec::new(),
+            history: vec![0; TILE_INDEX_COUNT * TILE_INDEX_COUNT * TILE_INDEX_COUNT],
+            cutoff_states: 0,
+            first_move_cutoff_states: 0,
+        };
+    }
+
+    // Keeps the collected ordering information, so only the statistics of the next search are counted
+    pub fn reset_cutoff_statistics(&mut self) {
+        self.cutoff_states = 0;
+        self.first_move_cutoff_states = 0;
+    }
+
+    pub fn get_first_move_cutoff_rate(&self) -> f32 {
+        if self.cutoff_states == 0 {
+            return 0.0;
+        }
+        return self.first_move_cutoff_states as f32 / self.cutoff_states as f32;
+    }
+
+    fn get_tile_index(tile: u8) -> usize {
+        return (tile as usize).min(TILE_INDEX_COUNT - 1);
+    }
+
+    fn get_history_index(santorini_move: &SantoriniMove) -> usize {
+        return (Self::get_tile_index(santorini_move.worker_from) * TILE_INDEX_COUNT + Self::get_tile_index(santorini_move.worker_to)) * TILE_INDEX_COUNT
+            + Self::get_tile_index(santorini_move.build);
+    }
+
+    // Higher priorities are searched first, the static evaluation is only used if the priorities are equal
+    pub(crate) fn get_move_priority(&self, game_state: &GS, ply: usize, santorini_move: &SantoriniMove) -> (u8, u32) {
+        if self.config.use_best_move && self.best_moves.get(game_state) == Some(santorini_move) {
+            return (KILLER_MOVES_PER_PLY as u8 + 1, 0);
+        }
+
+        if self.config.use_killer_moves {
+            if let Some(killer_moves) = self.killer_moves.get(ply) {
+                for (i, killer_move) in killer_moves.iter().enumerate() {
+                    if killer_move.as_ref() == Some(santorini_move) {
+                        return ((KILLER_MOVES_PER_PLY - i) as u8, 0);
+                    }
+                }
+            }
+        }
+
+        if self.config.use_history {
+            return (0, self.history[Self::get_history_index(santorini_move)]);
+        }
+        return (0, 0);
+    }
+
+    pub(crate) fn store_best_move(&mut self, game_state: &GS, santorini_move: SantoriniMove) {
+        if self.config.use_best_move {
+            self.best_moves.insert(*game_state, santorini_move);
+        }
+    }
+
+    pub(crate) fn store_cutoff(&mut self, ply: usize, depth: usize, santorini_move: SantoriniMove, was_first_move: bool) {
+        self.cutoff_states += 1;
+        if was_first_move {
+            self.first_move_cutoff_states += 1;
+        }
+
+        if self.config.use_killer_moves {
+            if self.killer_moves.len() <= ply {
+                self.killer_moves.resize(ply + 1, [None; KILLER_MOVES_PER_PLY]);
+            }
+            let killer_moves = &mut self.killer_moves[ply];
+            if killer_moves[0] != Some(santorini_move) {
+                killer_moves.copy_within(0..KILLER_MOVES_PER_PLY - 1, 1);
+                killer_moves[0] = Some(santorini_move);
+            }
+        }
+
+        if self.config.use_history {
+            // Cutoffs close to the root save more work, so they are weighted higher
+            let history_index = Self::get_history_index(&santorini_move);
+            self.history[history_index] = self.history[history_index].saturating_add((depth * depth) as u32);
+        }
+    }
+}
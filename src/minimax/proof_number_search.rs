@@ -0,0 +1,277 @@
+use std::hash::Hasher;
+use fnv::FnvHasher;
+use crate::game_state::GameState;
+
+const INFINITE_PROOF_NUMBER: u64 = u64::MAX / 2;
+
+/*
+The proof and disproof numbers are stored from the perspective of the player to move (phi/delta formulation):
+phi is the proof number of the node, i.e. how many nodes need to be proven at least for the player to move to win,
+delta is the disproof number of the node, i.e. how many nodes need to be proven at least for the player to move to lose.
+ */
+#[derive(Copy, Clone)]
+struct TableEntry<GS: GameState> {
+    state: GS,
+    phi: u64,
+    delta: u64,
+    // Number of nodes expanded while searching this state, used as replacement priority
+    work: u64,
+}
+
+/**
+Fixed-size transposition table for the proof-number search.
+Every bucket holds two entries: One is replaced only by entries with more search work behind them, the other one is always replaced.
+ */
+pub struct ProofNumberTable<GS: GameState> {
+    buckets: Vec<[Option<TableEntry<GS>>; 2]>,
+    bucket_mask: usize,
+}
+
+impl<GS: GameState> ProofNumberTable<GS> {
+    // The amount of buckets is rounded up to the next power of two
+    pub fn new(bucket_count: usize) -> Self {
+        let bucket_count = bucket_count.max(1).next_power_of_two();
+        return ProofNumberTable {
+            buckets: vec![[None; 2]; bucket_count],
+            bucket_mask: bucket_count - 1,
+        };
+    }
+
+    pub fn clear(&mut self) {
+        self.buckets.fill([None; 2]);
+    }
+
+    pub fn get_memory_size(&self) -> usize {
+        return self.buckets.len() * size_of::<[Option<TableEntry<GS>>; 2]>();
+    }
+
+    fn get_bucket_index(&self, state: &GS) -> usize {
+        let mut hasher = FnvHasher::default();
+        state.hash(&mut hasher);
+        return hasher.finish() as usize & self.bucket_mask;
+    }
+
+    fn get(&self, state: &GS) -> Option<(u64, u64)> {
+        let bucket = &self.buckets[self.get_bucket_index(state)];
+        for entry in bucket.iter().flatten() {
+            if entry.state == *state {
+                return Some((entry.phi, entry.delta));
+            }
+        }
+        return None;
+    }
+
+    fn store(&mut self, state: &GS, phi: u64, delta: u64, work: u64) {
+        let bucket_index = self.get_bucket_index(state);
+        let bucket = &mut self.buckets[bucket_index];
+        let new_entry = Some(TableEntry { state: *state, phi, delta, work });
+
+        for entry in bucket.iter_mut() {
+            if entry.is_some_and(|entry| entry.state == *state) {
+                *entry = new_entry;
+                return;
+            }
+        }
+
+        if bucket[0].is_none_or(|entry| entry.work <= work) {
+            bucket[1] = bucket[0];
+            bucket[0] = new_entry;
+        } else {
+            bucket[1] = new_entry;
+        }
+    }
+}
+
+
+struct ProofNumberSearch<'a, GS: GameState> {
+    table: &'a mut ProofNumberTable<GS>,
+    expanded_nodes: usize,
+    max_expanded_nodes: usize,
+}
+
+impl<'a, GS: GameState> ProofNumberSearch<'a, GS> {
+    // Returns (phi, delta) of the given state, initializing unknown states with (1, 1)
+    fn get_proof_numbers(&self, state: &GS) -> (u64, u64) {
+        if let Some(terminal_proof_numbers) = get_terminal_proof_numbers(state) {
+            return terminal_proof_numbers;
+        }
+        return self.table.get(state).unwrap_or((1, 1));
+    }
+
+    // Searches the state until its phi or delta reaches the respective threshold, returns the amount of expanded nodes
+    fn multiple_iterative_deepening(&mut self, state: &GS, phi_threshold: u64, delta_threshold: u64) -> u64 {
+        self.expanded_nodes += 1;
+        let mut work = 1u64;
+
+        let children = state.get_children_states();
+        if children.is_empty() {
+            // The player that cannot move loses
+            self.table.store(state, INFINITE_PROOF_NUMBER, 0, work);
+            return work;
+        }
+
+        loop {
+            // phi of this state is the minimum delta of the children, delta is the sum of phi of the children
+            let mut min_child_delta = INFINITE_PROOF_NUMBER;
+            let mut second_min_child_delta = INFINITE_PROOF_NUMBER;
+            let mut sum_child_phi = 0u64;
+            let mut best_child_index = 0;
+            let mut best_child_phi = 0;
+
+            for (index, child) in children.iter().enumerate() {
+                let (child_phi, child_delta) = self.get_proof_numbers(child);
+                sum_child_phi = sum_child_phi.saturating_add(child_phi).min(INFINITE_PROOF_NUMBER);
+                if child_delta < min_child_delta {
+                    second_min_child_delta = min_child_delta;
+                    min_child_delta = child_delta;
+                    best_child_index = index;
+                    best_child_phi = child_phi;
+                } else if child_delta < second_min_child_delta {
+                    second_min_child_delta = child_delta;
+                }
+            }
+
+            let phi = min_child_delta;
+            let delta = sum_child_phi;
+            if phi >= phi_threshold || delta >= delta_threshold {
+                self.table.store(state, phi, delta, work);
+                return work;
+            }
+
+            // The search is aborted once the node budget is used up, the stored proof numbers are still valid bounds
+            if self.expanded_nodes >= self.max_expanded_nodes {
+                self.table.store(state, phi, delta, work);
+                return work;
+            }
+
+            let child_phi_threshold = (delta_threshold - delta).saturating_add(best_child_phi).min(INFINITE_PROOF_NUMBER);
+            let child_delta_threshold = phi_threshold.min(second_min_child_delta.saturating_add(1));
+            work = work.saturating_add(self.multiple_iterative_deepening(&children[best_child_index], child_phi_threshold, child_delta_threshold));
+        }
+    }
+}
+
+// Returns (phi, delta) for states in which a player has already won
+fn get_terminal_proof_numbers<GS: GameState>(state: &GS) -> Option<(u64, u64)> {
+    let player_to_move_won = if state.is_player_a_turn() { state.has_player_a_won() } else { state.has_player_b_won() };
+    let player_to_move_lost = if state.is_player_a_turn() { state.has_player_b_won() } else { state.has_player_a_won() };
+
+    if player_to_move_won {
+        return Some((0, INFINITE_PROOF_NUMBER));
+    } else if player_to_move_lost {
+        return Some((INFINITE_PROOF_NUMBER, 0));
+    }
+    return None;
+}
+
+
+/**
+Solves the given state with a depth-first proof-number search (df-pn).
+Unlike infinite_depth_minimax, the memory usage is bounded by the size of the given table.
+Returns whether player A wins and the amount of expanded nodes, or None if the search stops making progress,
+which can happen if the table is too small to keep the proof numbers of the searched states.
+ */
+pub fn proof_number_search<GS: GameState>(game_state: GS, table: &mut ProofNumberTable<GS>) -> Option<(bool, usize)> {
+    return proof_number_search_with_budget(game_state, table, usize::MAX);
+}
+
+// Same as proof_number_search, but also gives up (returns None) after expanding max_expanded_nodes nodes
+pub fn proof_number_search_with_budget<GS: GameState>(game_state: GS, table: &mut ProofNumberTable<GS>, max_expanded_nodes: usize) -> Option<(bool, usize)> {
+    let player_a_turn = game_state.is_player_a_turn();
+    if let Some((phi, _)) = get_terminal_proof_numbers(&game_state) {
+        return Some((player_a_turn == (phi == 0), 0));
+    }
+
+    let mut search = ProofNumberSearch {
+        table,
+        expanded_nodes: 0,
+        max_expanded_nodes,
+    };
+
+    /*
+    The root call only returns unsolved if the proof numbers reach the infinite threshold without a proof (saturated sums),
+    or if the node budget is used up. The root entry is stored last, so it can't be evicted before it is read here.
+     */
+    let mut previous_proof_numbers = None;
+    loop {
+        search.multiple_iterative_deepening(&game_state, INFINITE_PROOF_NUMBER, INFINITE_PROOF_NUMBER);
+        let proof_numbers = search.table.get(&game_state);
+        if let Some((phi, delta)) = proof_numbers {
+            if phi == 0 || delta == 0 {
+                let player_to_move_wins = phi == 0;
+                return Some((player_a_turn == player_to_move_wins, search.expanded_nodes));
+            }
+        }
+        if search.expanded_nodes >= max_expanded_nodes || proof_numbers == previous_proof_numbers {
+            return None;
+        }
+        previous_proof_numbers = proof_numbers;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use fnv::FnvHashMap;
+    use rand::Rng;
+    use crate::game_state::GameState;
+    use crate::game_state::game_state_5x5_binary_composite::GameState5x5BinaryComposite;
+    use crate::generic_game_state::generic_santorini_game_state::GenericSantoriniGameState;
+    use crate::generic_game_state::GenericGameState;
+    use crate::minimax::infinite_depth_minimax;
+    use crate::minimax::proof_number_search::{proof_number_search, proof_number_search_with_budget, ProofNumberTable};
+
+    #[test]
+    fn test_proof_number_search_matches_infinite_depth_minimax() {
+        let mut rng = rand::thread_rng();
+        let mut table = ProofNumberTable::new(1 << 16);
+        for _ in 0..200 {
+            let block_count = rng.gen_range(70..=85);
+            let generic_state = GenericSantoriniGameState::<5, 5, 2>::generate_random_state_with_blocks_rng(&mut rng, block_count);
+            let state = GameState5x5BinaryComposite::from_generic_game_state(&generic_state);
+            let player_a_wins = infinite_depth_minimax(state, &mut FnvHashMap::default());
+
+            table.clear();
+            let (proof_number_player_a_wins, _) = proof_number_search(state, &mut table)
+                .unwrap_or_else(|| panic!("df-pn didn't solve {}", generic_state.to_position_string()));
+            assert_eq!(proof_number_player_a_wins, player_a_wins, "Wrong winner in {}", generic_state.to_position_string());
+        }
+    }
+
+    #[test]
+    fn test_proof_number_search_terminates_with_tiny_table() {
+        let mut rng = rand::thread_rng();
+        let mut table = ProofNumberTable::new(1);
+        for _ in 0..50 {
+            let block_count = rng.gen_range(40..=60);
+            let generic_state = GenericSantoriniGameState::<5, 5, 2>::generate_random_state_with_blocks_rng(&mut rng, block_count);
+            let state = GameState5x5BinaryComposite::from_generic_game_state(&generic_state);
+
+            // The table can't hold the proof numbers, so the search has to give up instead of running forever
+            table.clear();
+            if let Some((player_a_wins, expanded_nodes)) = proof_number_search_with_budget(state, &mut table, 10000) {
+                assert!(expanded_nodes <= 10000);
+                assert_eq!(player_a_wins, infinite_depth_minimax(state, &mut FnvHashMap::default()), "Wrong winner in {}", generic_state.to_position_string());
+            }
+        }
+    }
+
+    #[test]
+    fn test_proof_number_search_proves_win_with_small_table() {
+        // Player B to move wins in 5 moves
+        let tile_heights = [[0, 2, 1, 1, 1], [1, 0, 0, 1, 3], [2, 4, 4, 1, 1], [0, 0, 3, 4, 4], [3, 3, 0, 2, 4]];
+        let generic_state = GenericSantoriniGameState::<5, 5, 2>::new(Some([10, 22]), Some([0, 5]), tile_heights, false).unwrap();
+        let state = GameState5x5BinaryComposite::from_generic_game_state(&generic_state);
+
+        // Much smaller than the table of the other tests, but still large enough to keep the proof numbers of the search
+        let mut table = ProofNumberTable::new(1 << 14);
+        let (player_a_wins, expanded_nodes) = proof_number_search(state, &mut table).unwrap();
+        assert!(!player_a_wins);
+
+        // The budget is respected, even if the win can't be proven within it
+        table.clear();
+        assert_eq!(proof_number_search_with_budget(state, &mut table, expanded_nodes - 1), None);
+        table.clear();
+        assert_eq!(proof_number_search_with_budget(state, &mut table, expanded_nodes), Some((false, expanded_nodes)));
+    }
+}
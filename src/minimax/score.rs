@@ -0,0 +1,74 @@
+/*
+Scores are f32 values from the perspective of player A.
+Decisive scores encode the distance to the win: A score of WIN_SCORE - n means that player A wins in n moves (plies),
+and -WIN_SCORE + n means that player B wins in n moves. Every other score is a heuristic evaluation.
+
+Scores returned by a search are relative to the searched state, so they have to be moved one ply further away
+(step_back) when passed to the parent state, and search windows have to be moved one ply closer (step_forward)
+when passed to a child state.
+ */
+
+pub const WIN_SCORE: f32 = 1_000_000.0;
+// No game lasts longer than this amount of moves, so every decisive score is above WIN_SCORE - MAX_WIN_DISTANCE
+pub const MAX_WIN_DISTANCE: f32 = 1_000.0;
+// Heuristic evaluations are clamped to this value, so they can't be mistaken for decisive scores
+pub const MAX_HEURISTIC_SCORE: f32 = WIN_SCORE / 2.0;
+
+const DECISIVE_THRESHOLD: f32 = WIN_SCORE - MAX_WIN_DISTANCE;
+
+
+// Returns the score of a state in which the given player has already won
+#[inline(always)]
+pub fn win_score(player_a_wins: bool) -> f32 {
+    return if player_a_wins { WIN_SCORE } else { -WIN_SCORE };
+}
+
+// Converts a score of a child state to the score of the parent state
+#[inline(always)]
+pub fn step_back(score: f32) -> f32 {
+    return if score >= DECISIVE_THRESHOLD {
+        score - 1.0
+    } else if score <= -DECISIVE_THRESHOLD {
+        score + 1.0
+    } else {
+        score
+    };
+}
+
+// Converts an alpha or beta bound of a parent state to the bound for a child state (inverse of step_back)
+#[inline(always)]
+pub fn step_forward(bound: f32) -> f32 {
+    return if bound >= DECISIVE_THRESHOLD - 1.0 {
+        bound + 1.0
+    } else if bound <= -DECISIVE_THRESHOLD + 1.0 {
+        bound - 1.0
+    } else {
+        bound
+    };
+}
+
+#[inline(always)]
+pub fn clamp_heuristic(score: f32) -> f32 {
+    return score.clamp(-MAX_HEURISTIC_SCORE, MAX_HEURISTIC_SCORE);
+}
+
+// Also accepts f32::INFINITY and f32::NEG_INFINITY, which were used for wins in older data
+pub fn is_player_a_win(score: f32) -> bool {
+    return score >= DECISIVE_THRESHOLD;
+}
+
+pub fn is_player_b_win(score: f32) -> bool {
+    return score <= -DECISIVE_THRESHOLD;
+}
+
+pub fn is_decisive(score: f32) -> bool {
+    return is_player_a_win(score) || is_player_b_win(score);
+}
+
+// Returns the number of moves until the game is won for decisive scores
+pub fn get_moves_to_win(score: f32) -> Option<usize> {
+    if !is_decisive(score) || score.is_infinite() {
+        return None;
+    }
+    return Some((WIN_SCORE - score.abs()) as usize);
+}
@@ -1,33 +1,94 @@
+pub mod opening_suite;
+pub mod game_record;
+pub mod terminal_game;
+pub mod game_replay;
+pub mod game_annotation;
+pub mod puzzle_set;
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use anyhow::anyhow;
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use tokio::sync::Semaphore;
 use crate::game_state::GameState;
 use crate::generic_game_state::GenericGameState;
-use crate::strategy::Strategy;
+use crate::play_game::opening_suite::load_opening_suite;
+use crate::strategy::game_context::{GameContext, TimeControl};
+use crate::strategy::StatefulStrategy;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GameEndReason {
+    // A worker of the winner moved up to height 3
+    ReachedHeightThree,
+    // The loser had no possible moves
+    NoPossibleMoves,
+    // The strategy of the loser returned an error or an invalid move
+    StrategyError(String),
+    // The loser exceeded the time control
+    Timeout,
+}
 
-pub struct GameResult {
+pub struct GameResult<GS: GameState> {
     pub player_a_wins: bool,
     pub num_turns: usize,
+    pub end_reason: GameEndReason,
+    pub context: GameContext<GS>,
 }
 
-// Returns true if player A wins, false if player B wins
 pub fn play_game<
-    S1: Strategy<GameState=GS>,
-    S2: Strategy<GameState=GS>,
+    S1: StatefulStrategy<GameState=GS>,
+    S2: StatefulStrategy<GameState=GS>,
+    GS: GameState
+>(player_a_strategy: &mut S1, player_b_strategy: &mut S2, initial_game_state: GS) -> GameResult<GS> {
+    return play_game_with_time_control(player_a_strategy, player_b_strategy, initial_game_state, TimeControl::Unlimited);
+}
+
+/*
+Strategies are not interrupted, so the time control is enforced after every move:
+A player who took longer than the time they had left for the move loses the game.
+ */
+pub fn play_game_with_time_control<
+    S1: StatefulStrategy<GameState=GS>,
+    S2: StatefulStrategy<GameState=GS>,
+    GS: GameState
+>(player_a_strategy: &mut S1, player_b_strategy: &mut S2, initial_game_state: GS, time_control: TimeControl) -> GameResult<GS> {
+    return play_game_with_context(player_a_strategy, player_b_strategy, GameContext::with_time_control(initial_game_state, time_control));
+}
+
+// Plays the game from the current state of the context, which also defines the time control and the seed of the strategies
+pub fn play_game_with_context<
+    S1: StatefulStrategy<GameState=GS>,
+    S2: StatefulStrategy<GameState=GS>,
     GS: GameState
->(player_a_strategy: &S1, player_b_strategy: &S2, initial_game_state: GS) -> GameResult {
-    let mut current_game_state = initial_game_state;
-    let mut player_a_turn = initial_game_state.is_player_a_turn();
+>(player_a_strategy: &mut S1, player_b_strategy: &mut S2, mut context: GameContext<GS>) -> GameResult<GS> {
+    let mut player_a_turn = context.get_current_state().is_player_a_turn();
+
+    let strategy_error = |context: GameContext<GS>, player_a_failed: bool, num_turns: usize, error: anyhow::Error| {
+        return GameResult {
+            player_a_wins: !player_a_failed,
+            num_turns,
+            end_reason: GameEndReason::StrategyError(error.to_string()),
+            context,
+        };
+    };
+
+    if let Err(error) = player_a_strategy.new_game(true, &context) {
+        return strategy_error(context, true, 0, error);
+    }
+    if let Err(error) = player_b_strategy.new_game(false, &context) {
+        return strategy_error(context, false, 0, error);
+    }
 
     let mut num_turns = 0;
     loop {
-        if current_game_state.has_player_a_won() {
+        let current_game_state = *context.get_current_state();
+        if current_game_state.has_player_a_won() || current_game_state.has_player_b_won() {
             return GameResult {
-                player_a_wins: true,
-                num_turns,
-            };
-        }
-        if current_game_state.has_player_b_won() {
-            return GameResult {
-                player_a_wins: false,
+                player_a_wins: current_game_state.has_player_a_won(),
                 num_turns,
+                end_reason: GameEndReason::ReachedHeightThree,
+                context,
             };
         }
 
@@ -36,92 +97,427 @@ pub fn play_game<
             return GameResult {
                 player_a_wins: !player_a_turn,
                 num_turns,
+                end_reason: GameEndReason::NoPossibleMoves,
+                context,
             };
         }
 
         num_turns += 1;
+        let start = Instant::now();
         let chosen_move_index = if player_a_turn {
-            player_a_strategy.choose_move(true, &current_game_state, &possible_next_states)
+            player_a_strategy.choose_move_in_context(true, &context, &possible_next_states)
         } else {
-            player_b_strategy.choose_move(false, &current_game_state, &possible_next_states)
+            player_b_strategy.choose_move_in_context(false, &context, &possible_next_states)
+        };
+        let time_used = start.elapsed();
+
+        let chosen_move_index = match chosen_move_index {
+            Ok(index) if index < possible_next_states.len() => index,
+            Ok(index) => return strategy_error(context, player_a_turn, num_turns, anyhow!("Invalid move index {} for {} possible moves", index, possible_next_states.len())),
+            Err(error) => return strategy_error(context, player_a_turn, num_turns, error),
         };
 
-        current_game_state = possible_next_states[chosen_move_index];
+        if !context.apply_move(possible_next_states[chosen_move_index], time_used) {
+            return GameResult {
+                player_a_wins: !player_a_turn,
+                num_turns,
+                end_reason: GameEndReason::Timeout,
+                context,
+            };
+        }
         player_a_turn = !player_a_turn;
+
+        let notification_result = if player_a_turn {
+            player_a_strategy.opponent_moved(&context)
+        } else {
+            player_b_strategy.opponent_moved(&context)
+        };
+        if let Err(error) = notification_result {
+            return strategy_error(context, player_a_turn, num_turns, error);
+        }
     }
 }
 
 pub struct SimulationGameResult {
     pub player_a_wins: bool,
     pub num_turns: usize,
+    pub end_reason: GameEndReason,
     pub player_a_is_strategy_1: bool,
+    pub move_times: Vec<Duration>,
+    // Seed of the game context, replaying the initial state with this seed reproduces the game
+    pub seed: u64,
+    // Index into SimulationResult::openings
+    pub opening_index: usize,
+}
+
+pub struct OpeningResult {
+    pub position_string: String,
+    pub num_games: usize,
+    pub strategy_1_wins: usize,
+    pub player_a_wins: usize,
+}
+
+impl OpeningResult {
+    // True if the opening was played from both sides and the same player won every game
+    pub fn is_decided_regardless_of_strategy(&self) -> bool {
+        return self.num_games > 1 && self.strategy_1_wins > 0 && self.strategy_1_wins < self.num_games
+            && (self.player_a_wins == 0 || self.player_a_wins == self.num_games);
+    }
 }
 
 pub struct SimulationResult {
     pub strategy_1_wins: usize,
     pub strategy_2_wins: usize,
     pub raw_games: Vec<SimulationGameResult>,
+    pub openings: Vec<OpeningResult>,
+    pub seed: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct MatchConfig {
+    // Number of games that are played, the openings of an opening suite are repeated if the suite has fewer openings
+    pub num_games: usize,
+    pub initial_block_count: usize,
+    // Play every opening twice, so both strategies play it once as player A (the last opening only once if num_games is odd)
+    pub paired_openings: bool,
+    // File with one position string per line (see GenericGameState::to_position_string) that replaces the random openings
+    pub opening_suite_path: Option<String>,
+    pub time_control: TimeControl,
+    // Games running in parallel compete for the CPU, so time controlled matches should not exceed the number of cores
+    pub max_parallel_games: usize,
+    // Initial states and the random number generators of the strategies are derived from this seed, a random seed is used if None
+    pub seed: Option<u64>,
+}
+
+impl MatchConfig {
+    pub fn new(num_games: usize, initial_block_count: usize) -> MatchConfig {
+        return MatchConfig {
+            num_games,
+            initial_block_count,
+            paired_openings: true,
+            opening_suite_path: None,
+            time_control: TimeControl::Unlimited,
+            max_parallel_games: std::thread::available_parallelism().map(|threads| threads.get()).unwrap_or(1),
+            seed: None,
+        };
+    }
 }
 
 pub async fn simulate_random_games<
-    S1: Strategy<GameState=GS> + 'static,
-    S2: Strategy<GameState=GS> + 'static,
+    S1: StatefulStrategy<GameState=GS> + Clone + 'static,
+    S2: StatefulStrategy<GameState=GS> + Clone + 'static,
     GS: GameState + 'static
->(strategy_1: &S1, strategy_2: &S2, num_games: usize, initial_block_count: usize) -> SimulationResult {
-    let mut tasks = Vec::with_capacity(num_games);
+>(strategy_1: &S1, strategy_2: &S2, num_games: usize, initial_block_count: usize, seed: Option<u64>) -> SimulationResult {
+    let mut match_config = MatchConfig::new(num_games, initial_block_count);
+    match_config.seed = seed;
+    return simulate_games(strategy_1, strategy_2, &match_config).await
+        .expect("Matches with random openings can't fail");
+}
 
-    let mut rng = rand::thread_rng();
-    let random_states = (0..num_games).map(|_| {
-        GS::from_generic_game_state(&GenericGameState::generate_random_state_with_blocks_rng(&mut rng, initial_block_count))
-    }).collect::<Vec<_>>();
+pub async fn simulate_games<
+    S1: StatefulStrategy<GameState=GS> + Clone + 'static,
+    S2: StatefulStrategy<GameState=GS> + Clone + 'static,
+    GS: GameState + 'static
+>(strategy_1: &S1, strategy_2: &S2, match_config: &MatchConfig) -> anyhow::Result<SimulationResult> {
+    let parallel_games = Arc::new(Semaphore::new(match_config.max_parallel_games.max(1)));
 
-    for (i, initial_state) in random_states.into_iter().enumerate() {
-        let player_a_is_strategy_1 = i % 2 == 0;
+    // Everything random is derived before the games are started, so the results don't depend on the scheduling of the games
+    let seed = match_config.seed.unwrap_or_else(|| rand::thread_rng().next_u64());
+    let mut rng = StdRng::seed_from_u64(seed);
 
-        let strategy_1 = strategy_1.clone();
-        let strategy_2 = strategy_2.clone();
+    let games_per_opening = if match_config.paired_openings { 2 } else { 1 };
+    let openings: Vec<GS> = match &match_config.opening_suite_path {
+        Some(opening_suite_path) => load_opening_suite(opening_suite_path)?,
+        None => {
+            (0..match_config.num_games.div_ceil(games_per_opening)).map(|_| {
+                GS::from_generic_game_state(&GenericGameState::generate_random_state_with_blocks_rng(&mut rng, match_config.initial_block_count))
+            }).collect()
+        }
+    };
+    if openings.is_empty() && match_config.num_games > 0 {
+        return Err(anyhow!("The opening suite doesn't contain any openings"));
+    }
+
+    // Strategy 1 alternates between player A and B, so paired openings are played once from each side
+    let mut scheduled_games = Vec::with_capacity(match_config.num_games);
+    for game_index in 0..match_config.num_games {
+        let opening_index = (game_index / games_per_opening) % openings.len();
+        scheduled_games.push((opening_index, openings[opening_index], game_index % 2 == 0, rng.next_u64()));
+    }
+
+    let num_games = scheduled_games.len();
+    let mut tasks = Vec::with_capacity(num_games);
+    for (i, (opening_index, initial_state, player_a_is_strategy_1, game_seed)) in scheduled_games.into_iter().enumerate() {
+        // Every game uses fresh copies of the strategies, so the games can be played in parallel
+        let mut strategy_1 = strategy_1.clone();
+        let mut strategy_2 = strategy_2.clone();
+        let time_control = match_config.time_control;
+        let parallel_games = parallel_games.clone();
 
         let task = tokio::spawn(async move {
-            let result = if player_a_is_strategy_1 {
-                play_game(&strategy_1, &strategy_2, initial_state)
-            } else {
-                play_game(&strategy_2, &strategy_1, initial_state)
-            };
-            println!("Game {} finished", i);
-            return SimulationGameResult {
-                player_a_wins: result.player_a_wins,
-                num_turns: result.num_turns,
-                player_a_is_strategy_1,
-            };
+            let _permit = parallel_games.acquire_owned().await.expect("Semaphore is never closed");
+            // The game blocks until it is finished, so it is played on the blocking thread pool instead of an async worker
+            return tokio::task::spawn_blocking(move || {
+                let context = GameContext::with_seed(initial_state, time_control, game_seed);
+                let result = if player_a_is_strategy_1 {
+                    play_game_with_context(&mut strategy_1, &mut strategy_2, context)
+                } else {
+                    play_game_with_context(&mut strategy_2, &mut strategy_1, context)
+                };
+                println!("Game {} finished", i);
+                return SimulationGameResult {
+                    player_a_wins: result.player_a_wins,
+                    num_turns: result.num_turns,
+                    end_reason: result.end_reason,
+                    player_a_is_strategy_1,
+                    move_times: result.context.move_times,
+                    seed: game_seed,
+                    opening_index,
+                };
+            }).await;
         });
 
         tasks.push(task);
     }
 
+    let mut opening_results: Vec<OpeningResult> = openings.iter().map(|opening| OpeningResult {
+        position_string: opening.to_generic_game_state().to_position_string(),
+        num_games: 0,
+        strategy_1_wins: 0,
+        player_a_wins: 0,
+    }).collect();
+
     let mut strategy_1_wins = 0;
     let mut strategy_2_wins = 0;
     let mut raw_games = Vec::with_capacity(num_games);
     for task in tasks {
-        let result = task.await.unwrap();
-        if result.player_a_wins {
-            if result.player_a_is_strategy_1 {
-                strategy_1_wins += 1;
-            } else {
-                strategy_2_wins += 1;
-            }
+        let result = task.await??;
+        let strategy_1_won = result.player_a_wins == result.player_a_is_strategy_1;
+        if strategy_1_won {
+            strategy_1_wins += 1;
         } else {
-            if result.player_a_is_strategy_1 {
-                strategy_2_wins += 1;
-            } else {
-                strategy_1_wins += 1;
-            }
+            strategy_2_wins += 1;
+        }
+
+        let opening_result = &mut opening_results[result.opening_index];
+        opening_result.num_games += 1;
+        if strategy_1_won {
+            opening_result.strategy_1_wins += 1;
+        }
+        if result.player_a_wins {
+            opening_result.player_a_wins += 1;
         }
         raw_games.push(result);
     }
 
-    return SimulationResult {
+    return Ok(SimulationResult {
         strategy_1_wins,
         strategy_2_wins,
         raw_games,
-    };
-}
\ No newline at end of file
+        openings: opening_results,
+        seed,
+    });
+}
+
+// Plays random moves from the initial setup state until the game is over, the states start with the initial state
+#[cfg(test)]
+pub(crate) fn play_random_game<GS: GameState>() -> Vec<GS> {
+    use rand::Rng;
+    let mut rng = rand::thread_rng();
+    let mut states = vec![crate::game_state::get_initial_setup_state::<GS>()];
+    loop {
+        let current_state = states.last().unwrap();
+        if current_state.has_player_a_won() || current_state.has_player_b_won() {
+            return states;
+        }
+        let children_states = current_state.get_children_states();
+        if children_states.is_empty() {
+            return states;
+        }
+        states.push(children_states[rng.gen_range(0..children_states.len())]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::game_state_5x5_binary_composite::GameState5x5BinaryComposite;
+    use crate::game_state::{GameState, SantoriniEval};
+    use crate::generic_game_state::generic_santorini_game_state::GenericSantoriniGameState;
+    use std::time::Duration;
+    use anyhow::{anyhow, Result};
+    use crate::play_game::{play_game_with_context, simulate_games, simulate_random_games, GameEndReason, MatchConfig};
+    use crate::play_game::opening_suite::save_opening_suite;
+    use crate::strategy::game_context::{GameContext, TimeControl};
+    use crate::strategy::heuristic_minimax_strategy::HeuristicMinimaxStrategy;
+    use crate::strategy::random_strategy::RandomStrategy;
+    use crate::strategy::StatefulStrategy;
+
+    type GS = GameState5x5BinaryComposite;
+
+    // Plays the first move, fails after the given amount of own moves and counts the notifications about opponent moves
+    struct FailingStrategy {
+        moves_until_failure: usize,
+        invalid_move: bool,
+        opponent_moves: usize,
+    }
+
+    impl StatefulStrategy for FailingStrategy {
+        type GameState = GS;
+
+        fn opponent_moved(&mut self, _context: &GameContext<GS>) -> Result<()> {
+            self.opponent_moves += 1;
+            return Ok(());
+        }
+
+        fn choose_move_in_context(&mut self, _is_player_a: bool, _context: &GameContext<GS>, possible_next_states: &Vec<GS>) -> Result<usize> {
+            if self.moves_until_failure > 0 {
+                self.moves_until_failure -= 1;
+                return Ok(0);
+            }
+            if self.invalid_move {
+                return Ok(possible_next_states.len());
+            }
+            return Err(anyhow!("Failed to choose a move"));
+        }
+    }
+
+    fn child_evaluation(state: &GS) -> f32 {
+        return state.get_child_evaluation();
+    }
+
+    fn get_initial_state() -> GS {
+        return GS::from_generic_game_state(&GenericSantoriniGameState::<5, 5, 2>::new(Some([6, 8]), Some([16, 18]), [[0; 5]; 5], true).unwrap());
+    }
+
+    // Takes the given time for every move
+    #[derive(Clone)]
+    struct SlowStrategy {
+        time_per_move: Duration,
+    }
+
+    impl StatefulStrategy for SlowStrategy {
+        type GameState = GS;
+
+        fn choose_move_in_context(&mut self, _is_player_a: bool, _context: &GameContext<GS>, _possible_next_states: &Vec<GS>) -> Result<usize> {
+            std::thread::sleep(self.time_per_move);
+            return Ok(0);
+        }
+    }
+
+    // Only the order of the events is checked, as the time a move takes depends on the load of the machine.
+    // Sleeping takes at least the given time, and the fast strategy has plenty of time for its moves
+    #[test]
+    fn test_time_control_overrun_loses_the_game() {
+        let mut fast_strategy = SlowStrategy { time_per_move: Duration::ZERO };
+        let mut slow_strategy = SlowStrategy { time_per_move: Duration::from_millis(600) };
+
+        // The overrunning move is not applied
+        let fixed_time_control = TimeControl::FixedPerMove(Duration::from_millis(500));
+        let result = play_game_with_context(&mut fast_strategy, &mut slow_strategy, GameContext::with_seed(get_initial_state(), fixed_time_control, 0));
+        assert_eq!(result.end_reason, GameEndReason::Timeout);
+        assert!(result.player_a_wins);
+        assert_eq!(result.context.get_move_number(), 1);
+
+        // The slow strategy runs out of its total time after at most two moves
+        let mut slow_strategy = SlowStrategy { time_per_move: Duration::from_millis(200) };
+        let initial = Duration::from_millis(500);
+        let incremental_time_control = TimeControl::Incremental { initial, increment: Duration::ZERO };
+        let result = play_game_with_context(&mut slow_strategy, &mut fast_strategy, GameContext::with_seed(get_initial_state(), incremental_time_control, 0));
+        assert_eq!(result.end_reason, GameEndReason::Timeout);
+        assert!(!result.player_a_wins);
+        assert!(result.context.get_current_state().is_player_a_turn());
+        let slow_clock = result.context.player_a_clock;
+        assert!(slow_clock.moves_made <= 2);
+        assert_eq!(result.context.get_move_number(), slow_clock.moves_made * 2);
+        assert_eq!(slow_clock.remaining.unwrap(), initial - slow_clock.time_used);
+    }
+
+    #[test]
+    fn test_strategy_errors_and_invalid_moves_forfeit_the_game() {
+        for (player_a_fails, invalid_move) in [(true, false), (false, false), (true, true), (false, true)] {
+            let mut failing_strategy = FailingStrategy { moves_until_failure: 2, invalid_move, opponent_moves: 0 };
+            let mut random_strategy = RandomStrategy::<GS>::new();
+            let context = GameContext::with_seed(get_initial_state(), TimeControl::Unlimited, 0);
+            let result = if player_a_fails {
+                play_game_with_context(&mut failing_strategy, &mut random_strategy, context)
+            } else {
+                play_game_with_context(&mut random_strategy, &mut failing_strategy, context)
+            };
+
+            // The random opponent can't end the game within 5 moves from the initial state
+            assert_eq!(result.player_a_wins, !player_a_fails);
+            assert!(matches!(result.end_reason, GameEndReason::StrategyError(_)));
+            assert_eq!(result.context.get_move_number(), if player_a_fails { 4 } else { 5 });
+            assert_eq!(failing_strategy.opponent_moves, if player_a_fails { 2 } else { 3 });
+        }
+    }
+
+    #[test]
+    fn test_seeded_context_makes_games_reproducible() {
+        let mut random_strategy = RandomStrategy::<GS>::new();
+        let mut heuristic_strategy = HeuristicMinimaxStrategy::<GS>::new(0, child_evaluation);
+
+        for seed in 0..10 {
+            let result = play_game_with_context(&mut random_strategy, &mut heuristic_strategy, GameContext::with_seed(get_initial_state(), TimeControl::Unlimited, seed));
+            let replayed_result = play_game_with_context(&mut random_strategy, &mut heuristic_strategy, GameContext::with_seed(get_initial_state(), TimeControl::Unlimited, seed));
+            assert_eq!(result.context.history, replayed_result.context.history);
+            assert_eq!(result.player_a_wins, replayed_result.player_a_wins);
+            assert_eq!(result.end_reason, replayed_result.end_reason);
+        }
+
+        // Random moves with different seeds should not result in the same game
+        let result = play_game_with_context(&mut random_strategy, &mut RandomStrategy::new(), GameContext::with_seed(get_initial_state(), TimeControl::Unlimited, 0));
+        let other_result = play_game_with_context(&mut random_strategy, &mut RandomStrategy::new(), GameContext::with_seed(get_initial_state(), TimeControl::Unlimited, 1));
+        assert_ne!(result.context.history, other_result.context.history);
+    }
+
+    #[tokio::test]
+    async fn test_seeded_random_games_are_reproducible() {
+        let random_strategy = RandomStrategy::<GS>::new();
+        let heuristic_strategy = HeuristicMinimaxStrategy::<GS>::new(0, child_evaluation);
+
+        let result = simulate_random_games(&random_strategy, &heuristic_strategy, 10, 10, Some(42)).await;
+        let replayed_result = simulate_random_games(&random_strategy, &heuristic_strategy, 10, 10, Some(42)).await;
+        assert_eq!(result.seed, 42);
+        assert_eq!(result.strategy_1_wins, replayed_result.strategy_1_wins);
+        for (game, replayed_game) in result.raw_games.iter().zip(replayed_result.raw_games.iter()) {
+            assert_eq!(game.seed, replayed_game.seed);
+            assert_eq!(game.num_turns, replayed_game.num_turns);
+            assert_eq!(game.player_a_wins, replayed_game.player_a_wins);
+            assert_eq!(game.end_reason, replayed_game.end_reason);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_matches_play_exactly_the_configured_number_of_games() {
+        let random_strategy = RandomStrategy::<GS>::new();
+
+        let mut match_config = MatchConfig::new(7, 10);
+        match_config.seed = Some(0);
+
+        // Every opening is played from both sides by default, except the last one
+        assert!(match_config.paired_openings);
+        let result = simulate_games(&random_strategy, &random_strategy, &match_config).await.unwrap();
+        assert_eq!(result.raw_games.len(), 7);
+        assert_eq!(result.openings.iter().map(|opening| opening.num_games).collect::<Vec<usize>>(), vec![2, 2, 2, 1]);
+        for games in result.raw_games.chunks(2) {
+            assert!(games.iter().all(|game| game.opening_index == games[0].opening_index));
+            assert!(games.len() == 1 || games[0].player_a_is_strategy_1 != games[1].player_a_is_strategy_1);
+        }
+
+        match_config.paired_openings = false;
+        let result = simulate_games(&random_strategy, &random_strategy, &match_config).await.unwrap();
+        assert_eq!(result.raw_games.len(), 7);
+        assert_eq!(result.openings.len(), 7);
+
+        // The openings of a suite are repeated until the number of games is reached
+        let opening_suite_path = std::env::temp_dir().join("santorini_test_opening_suite.txt").to_string_lossy().to_string();
+        save_opening_suite(&opening_suite_path, &[get_initial_state()]).unwrap();
+        match_config.opening_suite_path = Some(opening_suite_path.clone());
+        let result = simulate_games(&random_strategy, &random_strategy, &match_config).await.unwrap();
+        std::fs::remove_file(&opening_suite_path).unwrap();
+        assert_eq!(result.raw_games.len(), 7);
+        assert_eq!(result.openings.len(), 1);
+        assert_eq!(result.openings[0].num_games, 7);
+    }
+}
@@ -0,0 +1,456 @@
+use std::sync::Arc;
+use anyhow::{anyhow, bail, Result};
+use crate::game_state::{ContinuousBlockId, GameState, SantoriniEval, SimplifiedState};
+use crate::generic_game_state::move_notation::get_move_notation;
+use crate::generic_game_state::GenericGameState;
+use crate::minimax::minimax_cache::MinimaxCache;
+use crate::minimax::minimax_custom_heuristic;
+use crate::minimax::score::{get_moves_to_win, is_decisive, is_player_a_win, is_player_b_win, step_back, win_score};
+use crate::play_game::game_record::GameRecord;
+use crate::precompute_state_winner::WinnerTablebase;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MoveQuality {
+    Good,
+    // Lost at least the mistake threshold compared to the best move
+    Mistake,
+    // Lost at least the blunder threshold, or changed the theoretical/engine outcome
+    Blunder,
+}
+
+impl MoveQuality {
+    pub fn get_name(&self) -> &'static str {
+        return match self {
+            MoveQuality::Good => "good",
+            MoveQuality::Mistake => "mistake",
+            MoveQuality::Blunder => "blunder",
+        };
+    }
+}
+
+/*
+Phase of the game before a move, based on the amount of blocks relative to the amount of tiles.
+The opening lasts until half as many blocks as tiles are built (12 on 5x5, 8 on 4x4), the middlegame until there are as many blocks as tiles.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GamePhase {
+    Placement,
+    Opening,
+    Middlegame,
+    Endgame,
+}
+
+impl GamePhase {
+    pub const ALL: [GamePhase; 4] = [GamePhase::Placement, GamePhase::Opening, GamePhase::Middlegame, GamePhase::Endgame];
+
+    pub fn from_state<GGS: GenericGameState>(state: &GGS) -> GamePhase {
+        let tile_count = GGS::ROWS * GGS::COLUMNS;
+        let has_unplaced_workers = (0..tile_count).filter(|&tile| state.get_character_on_tile(tile) != ' ').count() < 2 * GGS::WORKERS_PER_PLAYER;
+        if has_unplaced_workers {
+            return GamePhase::Placement;
+        }
+
+        let block_count: usize = (0..tile_count).map(|tile| state.get_tile_height(tile) as usize).sum();
+        return if block_count < tile_count / 2 {
+            GamePhase::Opening
+        } else if block_count < tile_count {
+            GamePhase::Middlegame
+        } else {
+            GamePhase::Endgame
+        };
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        return match self {
+            GamePhase::Placement => "Placement",
+            GamePhase::Opening => "Opening",
+            GamePhase::Middlegame => "Middlegame",
+            GamePhase::Endgame => "Endgame",
+        };
+    }
+
+    fn get_index(&self) -> usize {
+        return GamePhase::ALL.iter().position(|phase| phase == self).unwrap();
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MoveAnnotation {
+    pub notation: String,
+    pub player_a_moved: bool,
+    pub phase: GamePhase,
+    pub quality: MoveQuality,
+    // Scores from player A's perspective, relative to the state before the move
+    pub played_score: f32,
+    pub best_score: f32,
+    pub best_move: String,
+}
+
+pub struct GameAnnotation {
+    pub record: GameRecord,
+    pub moves: Vec<MoveAnnotation>,
+}
+
+impl GameAnnotation {
+    // The record with a comment after every move that is not good, so it can still be loaded by GameRecord::parse
+    pub fn to_annotated_record_string(&self) -> String {
+        let mut lines: Vec<String> = self.record.to_record_string().lines().map(|line| line.to_string()).collect();
+        let first_move_line = lines.iter().position(|line| line.starts_with("move ")).unwrap_or(lines.len());
+
+        // Inserted from the back, so the line indices of the earlier moves stay valid
+        for (move_index, annotation) in self.moves.iter().enumerate().rev() {
+            if annotation.quality == MoveQuality::Good {
+                continue;
+            }
+            let comment = format!(
+                "# {} {}: {} ({}), best {} ({})",
+                if annotation.player_a_moved { "A" } else { "B" },
+                annotation.quality.get_name(),
+                annotation.notation,
+                format_annotation_score(annotation.played_score),
+                annotation.best_move,
+                format_annotation_score(annotation.best_score),
+            );
+            lines.insert(first_move_line + move_index + 1, comment);
+        }
+
+        let mut content = lines.join("\n");
+        content.push('\n');
+        return content;
+    }
+}
+
+fn format_annotation_score(score: f32) -> String {
+    return match get_moves_to_win(score) {
+        Some(moves_to_win) => format!("{} wins in {}", if is_player_a_win(score) { "A" } else { "B" }, moves_to_win),
+        None => format!("{:.2}", score),
+    };
+}
+
+type EvaluationFunction<GS> = Box<dyn FnMut(&GS) -> Result<f32>>;
+
+/**
+Evaluates every position of a finished game and classifies the moves by comparing them to the best move.
+The evaluation function gets a child state and returns its score from player A's perspective (see minimax::score),
+e.g. from a search or a tablebase. A move is a blunder if it changes the outcome, so if a forced win is given up or a forced loss
+is allowed that the best move would have avoided. Otherwise, the difference between the heuristic scores decides.
+Moves in already lost positions and moves that keep a forced win (even if it takes longer) are always good.
+ */
+pub struct GameAnnotator<GS: GameState> {
+    evaluation_function: EvaluationFunction<GS>,
+    mistake_threshold: f32,
+    blunder_threshold: f32,
+}
+
+impl<GS: GameState + 'static> GameAnnotator<GS> {
+    pub fn new<F: FnMut(&GS) -> Result<f32> + 'static>(evaluation_function: F) -> GameAnnotator<GS> {
+        return GameAnnotator {
+            evaluation_function: Box::new(evaluation_function),
+            mistake_threshold: 1.5,
+            blunder_threshold: 4.0,
+        };
+    }
+
+    // Evaluates every child with a minimax search of the given depth
+    pub fn with_search<F: Fn(&GS) -> f32 + 'static>(depth: usize, heuristic_function: F) -> GameAnnotator<GS> where GS: SantoriniEval {
+        let mut cache = MinimaxCache::new();
+        return GameAnnotator::new(move |state: &GS| Ok(minimax_custom_heuristic(state, depth, &mut cache, &heuristic_function)));
+    }
+
+    /*
+    Exact evaluation for the 4x4 game, so the tablebase has to contain every block count of the game.
+    The tablebase only stores the winners, so only moves that change the winner are detected (as blunders),
+    and the distances of the decisive scores are meaningless.
+     */
+    pub fn with_tablebase(tablebase: Arc<WinnerTablebase<GS>>) -> GameAnnotator<GS> where GS: SimplifiedState + ContinuousBlockId {
+        return GameAnnotator::new(move |state: &GS| {
+            let player_a_wins = tablebase.get_winner(state)
+                .ok_or_else(|| anyhow!("Block count {} is not contained in the tablebase", state.get_block_count()))?;
+            return Ok(win_score(player_a_wins));
+        });
+    }
+
+    pub fn with_thresholds(mut self, mistake_threshold: f32, blunder_threshold: f32) -> Self {
+        self.mistake_threshold = mistake_threshold;
+        self.blunder_threshold = blunder_threshold;
+        return self;
+    }
+
+    pub fn annotate_record(&mut self, record: &GameRecord) -> Result<GameAnnotation> {
+        let states = record.get_states::<GS>()?;
+        let moves = self.annotate_states(&states)?;
+        return Ok(GameAnnotation { record: record.clone(), moves });
+    }
+
+    // The states start with the initial state, every following state has to be a child of the previous one
+    pub fn annotate_states(&mut self, states: &[GS]) -> Result<Vec<MoveAnnotation>> {
+        let mut annotations = Vec::with_capacity(states.len().saturating_sub(1));
+        for states in states.windows(2) {
+            annotations.push(self.annotate_move(&states[0], &states[1])?);
+        }
+        return Ok(annotations);
+    }
+
+    fn evaluate_child(&mut self, child: &GS) -> Result<f32> {
+        if child.has_player_a_won() || child.has_player_b_won() {
+            return Ok(step_back(win_score(child.has_player_a_won())));
+        }
+        return Ok(step_back((self.evaluation_function)(child)?));
+    }
+
+    fn annotate_move(&mut self, state: &GS, played_child: &GS) -> Result<MoveAnnotation> {
+        let generic_state = state.to_generic_game_state();
+        let player_a_moved = state.is_player_a_turn();
+
+        let mut best: Option<(GS, f32)> = None;
+        let mut played_score = None;
+        for child in state.get_children_states() {
+            let score = self.evaluate_child(&child)?;
+            if child == *played_child {
+                played_score = Some(score);
+            }
+            let is_better = match best {
+                None => true,
+                Some((_, best_score)) => if player_a_moved { score > best_score } else { score < best_score },
+            };
+            if is_better {
+                best = Some((child, score));
+            }
+        }
+        let Some(played_score) = played_score else {
+            bail!("The played move is not a legal move");
+        };
+        let (best_child, best_score) = best.unwrap();
+
+        return Ok(MoveAnnotation {
+            notation: get_move_notation(state, played_child)?,
+            player_a_moved,
+            phase: GamePhase::from_state(&generic_state),
+            quality: self.get_move_quality(player_a_moved, played_score, best_score),
+            played_score,
+            best_score,
+            best_move: get_move_notation(state, &best_child)?,
+        });
+    }
+
+    fn get_move_quality(&self, player_a_moved: bool, played_score: f32, best_score: f32) -> MoveQuality {
+        let is_win = |score: f32| if player_a_moved { is_player_a_win(score) } else { is_player_b_win(score) };
+        let is_loss = |score: f32| if player_a_moved { is_player_b_win(score) } else { is_player_a_win(score) };
+
+        if is_loss(best_score) || is_win(played_score) {
+            return MoveQuality::Good;
+        }
+        if is_win(best_score) || is_loss(played_score) {
+            return MoveQuality::Blunder;
+        }
+        debug_assert!(!is_decisive(played_score) && !is_decisive(best_score));
+
+        let score_loss = if player_a_moved { best_score - played_score } else { played_score - best_score };
+        return if score_loss >= self.blunder_threshold {
+            MoveQuality::Blunder
+        } else if score_loss >= self.mistake_threshold {
+            MoveQuality::Mistake
+        } else {
+            MoveQuality::Good
+        };
+    }
+}
+
+pub struct PhaseSummary {
+    pub moves: usize,
+    pub mistakes: usize,
+    pub blunders: usize,
+}
+
+impl PhaseSummary {
+    pub fn get_blunder_rate(&self) -> f32 {
+        return if self.moves == 0 { 0.0 } else { self.blunders as f32 / self.moves as f32 };
+    }
+
+    pub fn get_mistake_rate(&self) -> f32 {
+        return if self.moves == 0 { 0.0 } else { self.mistakes as f32 / self.moves as f32 };
+    }
+}
+
+pub struct StrategySummary {
+    pub strategy: String,
+    pub games: usize,
+    // Indexed like GamePhase::ALL
+    pub phases: [PhaseSummary; 4],
+}
+
+/*
+Summarizes the annotations per strategy, using the player names of the records ("A" and "B" if a record has no names).
+The strategies are in the order of their first game.
+ */
+pub fn summarize_annotations(annotations: &[GameAnnotation]) -> Vec<StrategySummary> {
+    let mut summaries: Vec<StrategySummary> = Vec::new();
+    for annotation in annotations {
+        for player_a in [true, false] {
+            let strategy = if player_a { &annotation.record.player_a } else { &annotation.record.player_b };
+            let strategy = strategy.clone().unwrap_or(if player_a { "A" } else { "B" }.to_string());
+
+            let summary_index = match summaries.iter().position(|summary| summary.strategy == strategy) {
+                Some(summary_index) => summary_index,
+                None => {
+                    summaries.push(StrategySummary {
+                        strategy,
+                        games: 0,
+                        phases: std::array::from_fn(|_| PhaseSummary { moves: 0, mistakes: 0, blunders: 0 }),
+                    });
+                    summaries.len() - 1
+                }
+            };
+
+            let summary = &mut summaries[summary_index];
+            summary.games += 1;
+            for move_annotation in annotation.moves.iter().filter(|move_annotation| move_annotation.player_a_moved == player_a) {
+                let phase_summary = &mut summary.phases[move_annotation.phase.get_index()];
+                phase_summary.moves += 1;
+                match move_annotation.quality {
+                    MoveQuality::Good => {}
+                    MoveQuality::Mistake => phase_summary.mistakes += 1,
+                    MoveQuality::Blunder => phase_summary.blunders += 1,
+                }
+            }
+        }
+    }
+    return summaries;
+}
+
+pub fn format_annotation_summary(summaries: &[StrategySummary]) -> String {
+    let mut report = "Strategy | Games | Phase | Moves | Mistakes | Blunders | Blunder rate\n".to_string();
+    for summary in summaries {
+        for phase in GamePhase::ALL {
+            let phase_summary = &summary.phases[phase.get_index()];
+            if phase_summary.moves == 0 {
+                continue;
+            }
+            report.push_str(&format!(
+                "{} | {} | {} | {} | {} | {} | {:.1}%\n",
+                summary.strategy,
+                summary.games,
+                phase.get_name(),
+                phase_summary.moves,
+                phase_summary.mistakes,
+                phase_summary.blunders,
+                phase_summary.get_blunder_rate() * 100.0
+            ));
+        }
+    }
+    return report;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::game_state_5x5_binary_composite::GameState5x5BinaryComposite;
+    use crate::game_state::game_state_5x5_struct::GameState5x5Struct;
+    use crate::game_state::SantoriniEval;
+    use crate::generic_game_state::generic_santorini_game_state::GenericSantoriniGameState;
+    use crate::generic_game_state::GenericGameState;
+    use crate::minimax::score::{step_back, win_score};
+    use crate::play_game::game_annotation::{summarize_annotations, GameAnnotation, GameAnnotator, GamePhase, MoveAnnotation, MoveQuality};
+    use crate::play_game::game_record::GameRecord;
+    use crate::play_game::play_random_game;
+
+    fn new_move_annotation(player_a_moved: bool, phase: GamePhase, quality: MoveQuality) -> MoveAnnotation {
+        return MoveAnnotation {
+            notation: "a1-a2+a3".to_string(),
+            player_a_moved,
+            phase,
+            quality,
+            played_score: 0.0,
+            best_score: 0.0,
+            best_move: "a1-a2+a3".to_string(),
+        };
+    }
+
+    #[test]
+    fn test_annotations_are_the_same_for_every_representation() {
+        let mut struct_annotator = GameAnnotator::<GameState5x5Struct>::with_search(1, |state| state.get_child_evaluation());
+        let mut binary_annotator = GameAnnotator::<GameState5x5BinaryComposite>::with_search(1, |state| state.get_child_evaluation());
+        for _ in 0..10 {
+            let record = GameRecord::from_states(&play_random_game::<GameState5x5BinaryComposite>()).unwrap();
+            let struct_annotation = struct_annotator.annotate_record(&record).unwrap();
+            let binary_annotation = binary_annotator.annotate_record(&record).unwrap();
+            assert_eq!(struct_annotation.to_annotated_record_string(), binary_annotation.to_annotated_record_string());
+        }
+    }
+
+    #[test]
+    fn test_annotated_record_is_parsed_to_the_original_record() {
+        let mut annotator = GameAnnotator::<GameState5x5BinaryComposite>::with_search(1, |state| state.get_child_evaluation());
+        for _ in 0..10 {
+            let record = GameRecord::from_states(&play_random_game::<GameState5x5BinaryComposite>()).unwrap().with_players("Player A", "Player B");
+            let annotation = annotator.annotate_record(&record).unwrap();
+            assert_eq!(annotation.moves.len(), record.moves.len());
+            assert_eq!(GameRecord::parse(&annotation.to_annotated_record_string()).unwrap(), record);
+        }
+    }
+
+    #[test]
+    fn test_played_moves_are_never_better_than_the_best_move() {
+        let mut annotator = GameAnnotator::<GameState5x5BinaryComposite>::with_search(1, |state| state.get_child_evaluation());
+        for _ in 0..10 {
+            for annotation in annotator.annotate_states(&play_random_game::<GameState5x5BinaryComposite>()).unwrap() {
+                let score_loss = if annotation.player_a_moved { annotation.best_score - annotation.played_score } else { annotation.played_score - annotation.best_score };
+                assert!(score_loss >= 0.0, "Move {} is better than the best move {}", annotation.notation, annotation.best_move);
+            }
+        }
+    }
+
+    #[test]
+    fn test_moves_are_classified_by_score_loss_and_outcome() {
+        let annotator = GameAnnotator::<GameState5x5BinaryComposite>::with_search(1, |state| state.get_child_evaluation()).with_thresholds(1.5, 4.0);
+        let a_wins = step_back(win_score(true));
+        let b_wins = step_back(win_score(false));
+
+        assert_eq!(annotator.get_move_quality(true, 1.0, 2.0), MoveQuality::Good);
+        assert_eq!(annotator.get_move_quality(true, 0.0, 2.0), MoveQuality::Mistake);
+        assert_eq!(annotator.get_move_quality(true, -2.0, 2.0), MoveQuality::Blunder);
+        assert_eq!(annotator.get_move_quality(false, 0.0, -2.0), MoveQuality::Mistake);
+        assert_eq!(annotator.get_move_quality(false, 0.0, 2.0), MoveQuality::Good);
+
+        // Giving up a forced win or allowing a forced loss is a blunder, keeping a slower win or moving in a lost position is not
+        assert_eq!(annotator.get_move_quality(true, 3.0, a_wins), MoveQuality::Blunder);
+        assert_eq!(annotator.get_move_quality(true, 0.0, b_wins), MoveQuality::Good);
+        assert_eq!(annotator.get_move_quality(true, step_back(a_wins), a_wins), MoveQuality::Good);
+        assert_eq!(annotator.get_move_quality(false, a_wins, 0.0), MoveQuality::Blunder);
+    }
+
+    #[test]
+    fn test_game_phases_are_based_on_placed_workers_and_blocks() {
+        let phase = |position_string: &str| GamePhase::from_state(&GenericSantoriniGameState::<5, 5, 2>::from_position_string(position_string).unwrap());
+        assert_eq!(phase("0000000000000000000000000/-/-/A"), GamePhase::Placement);
+        assert_eq!(phase("0000000000000000000000000/0,6/-/B"), GamePhase::Placement);
+        assert_eq!(phase("1111111111100000000000000/0,6/12,18/B"), GamePhase::Opening);
+        assert_eq!(phase("1111111111110000000000000/0,6/12,18/A"), GamePhase::Middlegame);
+        assert_eq!(phase("2222222222221000000000000/0,6/12,18/B"), GamePhase::Endgame);
+    }
+
+    #[test]
+    fn test_summaries_count_the_moves_of_each_strategy_per_phase() {
+        let first_record = GameRecord::default().with_players("minimax", "random");
+        let second_record = GameRecord::default().with_players("random", "minimax");
+        let annotations = vec![
+            GameAnnotation { record: first_record, moves: vec![
+                new_move_annotation(true, GamePhase::Placement, MoveQuality::Good),
+                new_move_annotation(false, GamePhase::Placement, MoveQuality::Mistake),
+                new_move_annotation(true, GamePhase::Opening, MoveQuality::Good),
+                new_move_annotation(false, GamePhase::Opening, MoveQuality::Blunder),
+            ] },
+            GameAnnotation { record: second_record, moves: vec![
+                new_move_annotation(true, GamePhase::Opening, MoveQuality::Blunder),
+                new_move_annotation(false, GamePhase::Opening, MoveQuality::Mistake),
+            ] },
+        ];
+
+        let summaries = summarize_annotations(&annotations);
+        assert_eq!(summaries.iter().map(|summary| summary.strategy.as_str()).collect::<Vec<&str>>(), vec!["minimax", "random"]);
+        let (minimax, random) = (&summaries[0], &summaries[1]);
+        assert_eq!((minimax.games, random.games), (2, 2));
+        assert_eq!((minimax.phases[0].moves, minimax.phases[1].moves, minimax.phases[1].mistakes, minimax.phases[1].blunders), (1, 2, 1, 0));
+        assert_eq!((random.phases[0].mistakes, random.phases[1].moves, random.phases[1].blunders), (1, 2, 2));
+        assert_eq!(random.phases[1].get_blunder_rate(), 1.0);
+    }
+}
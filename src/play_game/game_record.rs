@@ -0,0 +1,165 @@
+use std::fs;
+use anyhow::{bail, ensure, Context, Result};
+use crate::game_state::GameState;
+use crate::generic_game_state::move_notation::{find_move, get_move_notation};
+use crate::generic_game_state::GenericGameState;
+use crate::play_game::GameResult;
+
+/**
+Moves of a game in notation (see generic_game_state::move_notation), so games can be saved, replayed and analyzed
+independently of the game state representation.
+
+A record file has one "<key> <value>" pair per line: "position" with the initial position string,
+"player_a" and "player_b" with the names of the players, "result" with the winner ("A" or "B", missing while the game is running),
+and one "move <move>" line per move in the order they were played. Empty lines and lines starting with '#' are ignored.
+ */
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GameRecord {
+    pub initial_position: String,
+    pub player_a: Option<String>,
+    pub player_b: Option<String>,
+    // None if the game is not over
+    pub player_a_wins: Option<bool>,
+    pub moves: Vec<String>,
+}
+
+impl GameRecord {
+    pub fn new<GS: GameState>(initial_state: &GS) -> GameRecord {
+        return GameRecord {
+            initial_position: initial_state.to_generic_game_state().to_position_string(),
+            ..GameRecord::default()
+        };
+    }
+
+    // The states start with the initial state, every following state has to be a child of the previous one
+    pub fn from_states<GS: GameState>(states: &[GS]) -> Result<GameRecord> {
+        let Some(initial_state) = states.first() else {
+            bail!("A game record needs at least the initial state");
+        };
+        let mut record = GameRecord::new(initial_state);
+        for states in states.windows(2) {
+            record.moves.push(get_move_notation(&states[0], &states[1])?);
+        }
+        return Ok(record);
+    }
+
+    pub fn from_game_result<GS: GameState>(game_result: &GameResult<GS>) -> Result<GameRecord> {
+        let mut record = GameRecord::from_states(&game_result.context.history)?;
+        record.player_a_wins = Some(game_result.player_a_wins);
+        return Ok(record);
+    }
+
+    pub fn with_players(mut self, player_a: &str, player_b: &str) -> Self {
+        self.player_a = Some(player_a.to_string());
+        self.player_b = Some(player_b.to_string());
+        return self;
+    }
+
+    pub fn get_initial_state<GS: GameState>(&self) -> Result<GS> {
+        let generic_game_state = GS::GenericGameState::from_position_string(&self.initial_position)?;
+        return Ok(GS::from_generic_game_state(&generic_game_state));
+    }
+
+    // Replays the moves, the states start with the initial state
+    pub fn get_states<GS: GameState>(&self) -> Result<Vec<GS>> {
+        let mut states = Vec::with_capacity(self.moves.len() + 1);
+        states.push(self.get_initial_state::<GS>()?);
+        for (move_index, notation) in self.moves.iter().enumerate() {
+            let (_, next_state) = find_move(states.last().unwrap(), notation)
+                .with_context(|| format!("Invalid move {} of the game record", move_index + 1))?;
+            states.push(next_state);
+        }
+        return Ok(states);
+    }
+
+    pub fn load(path: &str) -> Result<GameRecord> {
+        let content = fs::read_to_string(path).with_context(|| format!("Could not read game record {}", path))?;
+        return GameRecord::parse(&content);
+    }
+
+    pub fn parse(content: &str) -> Result<GameRecord> {
+        let mut record = GameRecord::default();
+        let mut has_position = false;
+        for (line_index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line.split_once(' ').map(|(key, value)| (key, value.trim())).unwrap_or((line, ""));
+            ensure!(!value.is_empty(), "Line {} of the game record has no value", line_index + 1);
+
+            match key {
+                "position" => {
+                    record.initial_position = value.to_string();
+                    has_position = true;
+                }
+                "player_a" => record.player_a = Some(value.to_string()),
+                "player_b" => record.player_b = Some(value.to_string()),
+                "result" => record.player_a_wins = match value {
+                    "A" => Some(true),
+                    "B" => Some(false),
+                    _ => bail!("Invalid result '{}' in line {} of the game record, expected A or B", value, line_index + 1),
+                },
+                "move" => record.moves.push(value.to_string()),
+                _ => bail!("Unknown key '{}' in line {} of the game record", key, line_index + 1),
+            }
+        }
+        ensure!(has_position, "The game record has no position");
+        return Ok(record);
+    }
+
+    pub fn to_record_string(&self) -> String {
+        let mut content = format!("position {}\n", self.initial_position);
+        if let Some(player_a) = &self.player_a {
+            content.push_str(&format!("player_a {}\n", player_a));
+        }
+        if let Some(player_b) = &self.player_b {
+            content.push_str(&format!("player_b {}\n", player_b));
+        }
+        if let Some(player_a_wins) = self.player_a_wins {
+            content.push_str(&format!("result {}\n", if player_a_wins { "A" } else { "B" }));
+        }
+        for notation in &self.moves {
+            content.push_str(&format!("move {}\n", notation));
+        }
+        return content;
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        fs::write(path, self.to_record_string()).with_context(|| format!("Could not write game record {}", path))?;
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::game_state_5x5_binary_composite::GameState5x5BinaryComposite;
+    use crate::game_state::GameState;
+    use crate::play_game::game_record::GameRecord;
+    use crate::play_game::play_random_game;
+
+    #[test]
+    fn test_record_string_is_parsed_to_the_same_game() {
+        for _ in 0..100 {
+            let states = play_random_game::<GameState5x5BinaryComposite>();
+            let mut record = GameRecord::from_states(&states).unwrap().with_players("Player A", "Player B");
+            record.player_a_wins = Some(states.last().unwrap().has_player_a_won());
+
+            let parsed_record = GameRecord::parse(&record.to_record_string()).unwrap();
+            assert_eq!(record, parsed_record);
+            assert!(parsed_record.get_states::<GameState5x5BinaryComposite>().unwrap() == states);
+        }
+    }
+
+    #[test]
+    fn test_invalid_records_are_rejected() {
+        assert!(GameRecord::parse("# No position\nmove a1,b2\n").is_err());
+        assert!(GameRecord::parse("position 0000000000000000000000000/-/-/A\nresult C\n").is_err());
+        assert!(GameRecord::parse("position 0000000000000000000000000/-/-/A\nwinner A\n").is_err());
+
+        // Moves are only checked when the game is replayed
+        let record = GameRecord::parse("position 0000000000000000000000000/-/-/A\nmove a1,b2\nmove a1,c3\n").unwrap();
+        let error = record.get_states::<GameState5x5BinaryComposite>().unwrap_err();
+        assert_eq!(error.to_string(), "Invalid move 2 of the game record");
+    }
+}
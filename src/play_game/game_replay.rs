@@ -0,0 +1,339 @@
+use std::fs;
+use anyhow::{ensure, Result};
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use crate::game_state::{GameState, SantoriniEval};
+use crate::generic_game_state::generic_santorini_game_state::{GenericSantoriniGameState, PLAYER_A_COLOR, PLAYER_B_COLOR};
+use crate::minimax::minimax_cache::MinimaxCache;
+use crate::minimax::minimax_custom_heuristic;
+use crate::minimax::score::{get_moves_to_win, is_decisive, is_player_a_win};
+use crate::play_game::game_record::GameRecord;
+
+const EVALUATION_BAR_WIDTH: i32 = 40;
+const BUILD_COLOR: RGBColor = RGBColor(46, 160, 67);
+
+/**
+Renders a whole game with the images of GenericSantoriniGameState::draw_image, so game records can be reviewed visually.
+Every frame shows the position after a move, with an arrow for the moved worker, the build location and the placed workers highlighted,
+and the move in the caption. With evaluations, an evaluation bar is drawn next to the board, filled with the color of player A
+according to the evaluation (from player A's perspective).
+ */
+pub struct GameReplay<const ROWS: usize, const COLUMNS: usize, const WORKERS_PER_PLAYER: usize> {
+    states: Vec<GenericSantoriniGameState<ROWS, COLUMNS, WORKERS_PER_PLAYER>>,
+    notations: Vec<String>,
+    // One evaluation per state
+    evaluations: Option<Vec<f32>>,
+    // Evaluation at which the bar is filled to about 88% for the better player
+    evaluation_scale: f32,
+}
+
+impl<const ROWS: usize, const COLUMNS: usize, const WORKERS_PER_PLAYER: usize> GameReplay<ROWS, COLUMNS, WORKERS_PER_PLAYER> {
+    // The states start with the initial state, every following state has to be a child of the previous one
+    pub fn new(states: Vec<GenericSantoriniGameState<ROWS, COLUMNS, WORKERS_PER_PLAYER>>) -> Result<Self> {
+        ensure!(!states.is_empty(), "A game replay needs at least the initial state");
+        let mut notations = Vec::with_capacity(states.len() - 1);
+        for states in states.windows(2) {
+            notations.push(states[0].format_move_notation(&states[1])?);
+        }
+        return Ok(GameReplay {
+            states,
+            notations,
+            evaluations: None,
+            evaluation_scale: 5.0,
+        });
+    }
+
+    pub fn from_states<GS: GameState<GenericGameState=GenericSantoriniGameState<ROWS, COLUMNS, WORKERS_PER_PLAYER>>>(states: &[GS]) -> Result<Self> {
+        return GameReplay::new(states.iter().map(|state| state.to_generic_game_state()).collect());
+    }
+
+    pub fn from_record<GS: GameState<GenericGameState=GenericSantoriniGameState<ROWS, COLUMNS, WORKERS_PER_PLAYER>>>(record: &GameRecord) -> Result<Self> {
+        return GameReplay::from_states(&record.get_states::<GS>()?);
+    }
+
+    // One evaluation per state, e.g. from evaluate_game_states
+    pub fn with_evaluations(mut self, evaluations: Vec<f32>) -> Result<Self> {
+        ensure!(evaluations.len() == self.states.len(), "Expected {} evaluations, got {}", self.states.len(), evaluations.len());
+        self.evaluations = Some(evaluations);
+        return Ok(self);
+    }
+
+    pub fn with_evaluation_scale(mut self, evaluation_scale: f32) -> Self {
+        self.evaluation_scale = evaluation_scale;
+        return self;
+    }
+
+    pub fn get_frame_count(&self) -> usize {
+        return self.states.len();
+    }
+
+    pub fn get_frame_size(&self) -> (u32, u32) {
+        let (width, height) = GenericSantoriniGameState::<ROWS, COLUMNS, WORKERS_PER_PLAYER>::get_image_size();
+        if self.evaluations.is_some() {
+            return (width + EVALUATION_BAR_WIDTH as u32 + 5, height);
+        }
+        return (width, height);
+    }
+
+    // Writes one image per state to the folder, named move_000.svg, move_001.svg, ...
+    pub fn draw_sequence(&self, folder_path: &str) -> Result<Vec<String>> {
+        fs::create_dir_all(folder_path)?;
+        let mut paths = Vec::with_capacity(self.states.len());
+        for frame in 0..self.states.len() {
+            let path = format!("{}/move_{:03}.svg", folder_path, frame);
+            {
+                let root = SVGBackend::new(&path, self.get_frame_size()).into_drawing_area();
+                self.draw_frame(&root, frame)?;
+                root.present()?;
+            }
+            paths.push(path);
+        }
+        return Ok(paths);
+    }
+
+    // Draws all frames into a single image, row by row
+    pub fn draw_sheet(&self, path: &str, columns: usize) -> Result<()> {
+        ensure!(columns > 0, "The sheet needs at least one column");
+        let rows = self.states.len().div_ceil(columns);
+        let (frame_width, frame_height) = self.get_frame_size();
+        let size = (frame_width * columns as u32, frame_height * rows as u32);
+
+        let root = SVGBackend::new(path, size).into_drawing_area();
+        root.fill(&WHITE)?;
+        for (frame, area) in root.split_evenly((rows, columns)).iter().take(self.states.len()).enumerate() {
+            self.draw_frame(area, frame)?;
+        }
+        root.present()?;
+        return Ok(());
+    }
+
+    /*
+    Writes a single animated SVG that shows the frames one after another and starts again after the last one.
+    The frames are nested SVG images, which are shown and hidden with SMIL animations.
+     */
+    pub fn draw_animation(&self, path: &str, seconds_per_move: f32) -> Result<()> {
+        ensure!(seconds_per_move > 0.0, "The time per move must be positive");
+        let (width, height) = self.get_frame_size();
+        let frame_count = self.states.len();
+        let duration = seconds_per_move * frame_count as f32;
+
+        let mut content = format!("<svg width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" xmlns=\"http://www.w3.org/2000/svg\">\n", width, height, width, height);
+        for frame in 0..frame_count {
+            let mut frame_content = String::new();
+            {
+                let root = SVGBackend::with_string(&mut frame_content, (width, height)).into_drawing_area();
+                root.fill(&WHITE)?;
+                self.draw_frame(&root, frame)?;
+                root.present()?;
+            }
+
+            let start = frame as f32 / frame_count as f32;
+            let end = (frame + 1) as f32 / frame_count as f32;
+            // Discrete animations keep each value until the next key time
+            let (values, key_times) = if frame_count == 1 {
+                ("1".to_string(), "0".to_string())
+            } else if frame == 0 {
+                ("1;0".to_string(), format!("0;{}", end))
+            } else if frame == frame_count - 1 {
+                ("0;1".to_string(), format!("0;{}", start))
+            } else {
+                ("0;1;0".to_string(), format!("0;{};{}", start, end))
+            };
+
+            content.push_str(&format!("<g opacity=\"{}\">\n", if frame == 0 { 1 } else { 0 }));
+            content.push_str(&format!(
+                "<animate attributeName=\"opacity\" calcMode=\"discrete\" values=\"{}\" keyTimes=\"{}\" dur=\"{}s\" repeatCount=\"indefinite\"/>\n",
+                values, key_times, duration
+            ));
+            content.push_str(&frame_content);
+            content.push_str("\n</g>\n");
+        }
+        content.push_str("</svg>\n");
+
+        fs::write(path, content)?;
+        return Ok(());
+    }
+
+    fn draw_frame<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>, frame: usize) -> Result<()> where DB::ErrorType: 'static {
+        let state = &self.states[frame];
+        let caption = if frame == 0 {
+            "Start".to_string()
+        } else {
+            format!("Move {}: {}", frame, self.notations[frame - 1])
+        };
+        state.draw_on(root, &caption)?;
+
+        if frame > 0 {
+            let previous_state = &self.states[frame - 1];
+            let player_color = if previous_state.player_a_turn { PLAYER_A_COLOR } else { PLAYER_B_COLOR };
+            let move_tiles = previous_state.get_move_tiles(state)?;
+
+            for tile in move_tiles.placed {
+                GenericSantoriniGameState::<ROWS, COLUMNS, WORKERS_PER_PLAYER>::draw_tile_highlight(root, tile, player_color.stroke_width(4))?;
+            }
+            if let Some(build) = move_tiles.build {
+                GenericSantoriniGameState::<ROWS, COLUMNS, WORKERS_PER_PLAYER>::draw_tile_highlight(root, build, BUILD_COLOR.stroke_width(4))?;
+            }
+            if let (Some(from), Some(to)) = (move_tiles.from, move_tiles.to) {
+                draw_arrow::<DB, ROWS, COLUMNS, WORKERS_PER_PLAYER>(root, from, to, player_color)?;
+            }
+        }
+
+        if let Some(evaluations) = &self.evaluations {
+            self.draw_evaluation_bar(root, evaluations[frame])?;
+        }
+        return Ok(());
+    }
+
+    fn draw_evaluation_bar<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>, evaluation: f32) -> Result<()> where DB::ErrorType: 'static {
+        let (board_width, _) = GenericSantoriniGameState::<ROWS, COLUMNS, WORKERS_PER_PLAYER>::get_image_size();
+        let top = GenericSantoriniGameState::<ROWS, COLUMNS, WORKERS_PER_PLAYER>::get_tile_position((ROWS - 1) * COLUMNS).1;
+        let bottom = GenericSantoriniGameState::<ROWS, COLUMNS, WORKERS_PER_PLAYER>::get_tile_position(0).1 + 100;
+        let left = board_width as i32;
+        let right = left + EVALUATION_BAR_WIDTH;
+
+        let player_a_share = if is_decisive(evaluation) {
+            if is_player_a_win(evaluation) { 1.0 } else { 0.0 }
+        } else {
+            0.5 + 0.5 * (evaluation / self.evaluation_scale).tanh()
+        };
+        // Player A fills the bar from the bottom, like player A's workers start at the bottom rows
+        let split = bottom - ((bottom - top) as f32 * player_a_share).round() as i32;
+
+        root.draw(&Rectangle::new([(left, top), (right, split)], PLAYER_B_COLOR.filled()))?;
+        root.draw(&Rectangle::new([(left, split), (right, bottom)], PLAYER_A_COLOR.filled()))?;
+        root.draw(&Rectangle::new([(left, top), (right, bottom)], BLACK.stroke_width(2)))?;
+
+        let text = match get_moves_to_win(evaluation) {
+            Some(moves_to_win) => format!("{}#{}", if is_player_a_win(evaluation) { "A" } else { "B" }, moves_to_win),
+            None => format!("{:+.1}", evaluation),
+        };
+        root.draw(&Text::new(
+            text,
+            ((left + right) / 2, bottom + 25),
+            ("Arial", 16.0).into_font().color(&BLACK).pos(Pos::new(HPos::Center, VPos::Center)),
+        ))?;
+        return Ok(());
+    }
+}
+
+fn get_tile_center<const ROWS: usize, const COLUMNS: usize, const WORKERS_PER_PLAYER: usize>(tile: usize) -> (i32, i32) {
+    let (x, y) = GenericSantoriniGameState::<ROWS, COLUMNS, WORKERS_PER_PLAYER>::get_tile_position(tile);
+    return (x + 50, y + 50);
+}
+
+fn draw_arrow<DB: DrawingBackend, const ROWS: usize, const COLUMNS: usize, const WORKERS_PER_PLAYER: usize>(
+    root: &DrawingArea<DB, Shift>,
+    from: usize,
+    to: usize,
+    color: RGBColor,
+) -> Result<()> where DB::ErrorType: 'static {
+    let (from_x, from_y) = get_tile_center::<ROWS, COLUMNS, WORKERS_PER_PLAYER>(from);
+    let (to_x, to_y) = get_tile_center::<ROWS, COLUMNS, WORKERS_PER_PLAYER>(to);
+    let (dx, dy) = ((to_x - from_x) as f32, (to_y - from_y) as f32);
+    let length = (dx * dx + dy * dy).sqrt();
+    let (ux, uy) = (dx / length, dy / length);
+
+    // Shortened on both ends, so the heights in the centers of the tiles stay readable
+    let start = (from_x as f32 + ux * 25.0, from_y as f32 + uy * 25.0);
+    let tip = (to_x as f32 - ux * 25.0, to_y as f32 - uy * 25.0);
+    let head_base = (tip.0 - ux * 20.0, tip.1 - uy * 20.0);
+    let to_point = |(x, y): (f32, f32)| (x.round() as i32, y.round() as i32);
+
+    let style = color.mix(0.8);
+    root.draw(&PathElement::new(vec![to_point(start), to_point(head_base)], style.stroke_width(6)))?;
+    root.draw(&Polygon::new(vec![
+        to_point(tip),
+        to_point((head_base.0 - uy * 12.0, head_base.1 + ux * 12.0)),
+        to_point((head_base.0 + uy * 12.0, head_base.1 - ux * 12.0)),
+    ], style.filled()))?;
+    return Ok(());
+}
+
+// Evaluations of the states for GameReplay::with_evaluations, from player A's perspective
+// Won states are scored by the search, so the heuristic function doesn't have to support them (like dynamic_heuristic)
+pub fn evaluate_game_states<GS: GameState + SantoriniEval, F: Fn(&GS) -> f32>(states: &[GS], depth: usize, heuristic_function: &F) -> Vec<f32> {
+    let mut cache = MinimaxCache::new();
+    return states.iter().map(|state| minimax_custom_heuristic(state, depth, &mut cache, heuristic_function)).collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use crate::game_state::game_state_4x4_binary_3bit::GameState4x4Binary3Bit;
+    use crate::game_state::game_state_5x5_binary_composite::GameState5x5BinaryComposite;
+    use crate::game_state::{get_initial_setup_state, GameState, SantoriniEval};
+    use crate::generic_game_state::move_notation::apply_moves;
+    use crate::minimax::score::{step_back, win_score};
+    use crate::play_game::game_replay::{evaluate_game_states, GameReplay};
+    use crate::play_game::play_random_game;
+    use crate::strategy::heuristics::dynamic_heuristic::{dynamic_heuristic, DynamicHeuristicParams};
+
+    fn get_test_game() -> Vec<GameState4x4Binary3Bit> {
+        let moves = ["a1", "d4", "a1-b2+b1", "d4-c3+c4"];
+        let mut states = vec![get_initial_setup_state::<GameState4x4Binary3Bit>()];
+        for notation in moves {
+            states.push(apply_moves(states.last().unwrap(), &[notation]).unwrap());
+        }
+        return states;
+    }
+
+    #[test]
+    fn test_replay_writes_one_image_per_state_a_sheet_and_an_animation() {
+        let states = get_test_game();
+        let evaluations = evaluate_game_states(&states, 1, &|state: &GameState4x4Binary3Bit| state.get_child_evaluation());
+        let replay = GameReplay::from_states(&states).unwrap().with_evaluations(evaluations).unwrap();
+        let folder_path = std::env::temp_dir().join("santorini_test_game_replay").to_string_lossy().to_string();
+        let _ = fs::remove_dir_all(&folder_path);
+
+        let paths = replay.draw_sequence(&folder_path).unwrap();
+        assert_eq!(paths.len(), states.len());
+        for path in &paths {
+            assert!(fs::read_to_string(path).unwrap().starts_with("<svg"));
+        }
+
+        let sheet_path = format!("{}/sheet.svg", folder_path);
+        replay.draw_sheet(&sheet_path, 2).unwrap();
+        assert!(fs::read_to_string(&sheet_path).unwrap().starts_with("<svg"));
+
+        let animation_path = format!("{}/animation.svg", folder_path);
+        replay.draw_animation(&animation_path, 1.0).unwrap();
+        let animation = fs::read_to_string(&animation_path).unwrap();
+        // The outer image and one nested image per frame
+        assert_eq!(animation.matches("<svg").count(), states.len() + 1);
+        fs::remove_dir_all(&folder_path).unwrap();
+    }
+
+    #[test]
+    fn test_evaluations_have_to_match_the_states() {
+        let states = get_test_game();
+        assert!(GameReplay::from_states(&states).unwrap().with_evaluations(vec![0.0; states.len() - 1]).is_err());
+        assert!(GameReplay::from_states::<GameState4x4Binary3Bit>(&[]).is_err());
+    }
+
+    #[test]
+    fn test_finished_5x5_game_is_replayed_with_dynamic_heuristic() {
+        // Games that end because a player has no moves left never reach a position with a winning move
+        let states = loop {
+            let states = play_random_game::<GameState5x5BinaryComposite>();
+            let last_state = states.last().unwrap();
+            if last_state.has_player_a_won() || last_state.has_player_b_won() {
+                break states;
+            }
+        };
+        let player_a_wins = states.last().unwrap().has_player_a_won();
+
+        let params = DynamicHeuristicParams::default();
+        let evaluations = evaluate_game_states(&states, 3, &|state: &GameState5x5BinaryComposite| dynamic_heuristic(state, &params));
+        assert_eq!(evaluations.len(), states.len());
+        assert_eq!(evaluations[states.len() - 1], win_score(player_a_wins));
+        assert_eq!(evaluations[states.len() - 2], step_back(win_score(player_a_wins)));
+
+        let replay = GameReplay::from_states(&states).unwrap().with_evaluations(evaluations).unwrap();
+        let folder_path = std::env::temp_dir().join("santorini_test_game_replay_5x5").to_string_lossy().to_string();
+        let _ = fs::remove_dir_all(&folder_path);
+        assert_eq!(replay.draw_sequence(&folder_path).unwrap().len(), states.len());
+        fs::remove_dir_all(&folder_path).unwrap();
+    }
+}
@@ -0,0 +1,33 @@
+use std::fs;
+use anyhow::{Context, Result};
+use crate::game_state::GameState;
+use crate::generic_game_state::GenericGameState;
+
+/*
+An opening suite is a text file with one position string (see GenericGameState::to_position_string) per line.
+Empty lines and lines starting with '#' are ignored.
+ */
+pub fn load_opening_suite<GS: GameState>(path: &str) -> Result<Vec<GS>> {
+    let content = fs::read_to_string(path).with_context(|| format!("Could not read opening suite {}", path))?;
+    return parse_opening_suite(&content);
+}
+
+pub fn parse_opening_suite<GS: GameState>(content: &str) -> Result<Vec<GS>> {
+    let mut openings = Vec::new();
+    for (line_index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let generic_game_state = GS::GenericGameState::from_position_string(line)
+            .with_context(|| format!("Invalid position in line {} of the opening suite", line_index + 1))?;
+        openings.push(GS::from_generic_game_state(&generic_game_state));
+    }
+    return Ok(openings);
+}
+
+pub fn save_opening_suite<GS: GameState>(path: &str, openings: &[GS]) -> Result<()> {
+    let content: String = openings.iter().map(|opening| opening.to_generic_game_state().to_position_string() + "\n").collect();
+    fs::write(path, content).with_context(|| format!("Could not write opening suite {}", path))?;
+    return Ok(());
+}
@@ -0,0 +1,299 @@
+use std::collections::HashSet;
+use std::fs;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use anyhow::{bail, ensure, Context, Result};
+use fnv::FnvHashMap;
+use crate::game_state::{ContinuousBlockId, GameState, SantoriniEval, SimplifiedState};
+use crate::generic_game_state::move_notation::{find_move, get_move_notation};
+use crate::generic_game_state::GenericGameState;
+use crate::minimax::forced_win::{no_known_winner, shortest_forced_win_moves};
+use crate::minimax::infinite_depth_minimax;
+use crate::play_game::game_record::GameRecord;
+use crate::precompute_state_winner::WinnerTablebase;
+
+/*
+A position in which the player to move has exactly one move that forces a win in moves_to_win moves (plies).
+The solution is the main line of the forced win: the unique first move, then the longest defense of the opponent and the fastest win,
+so it ends with the win after moves_to_win moves.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Puzzle {
+    pub position: String,
+    pub moves_to_win: usize,
+    pub solution: Vec<String>,
+}
+
+impl Puzzle {
+    pub fn get_state<GS: GameState>(&self) -> Result<GS> {
+        let generic_game_state = GS::GenericGameState::from_position_string(&self.position)?;
+        return Ok(GS::from_generic_game_state(&generic_game_state));
+    }
+}
+
+/*
+A puzzle set is a text file with one puzzle per line: the position string, the amount of moves to win and the moves of the solution,
+separated by spaces. Empty lines and lines starting with '#' are ignored.
+ */
+pub fn load_puzzle_set(path: &str) -> Result<Vec<Puzzle>> {
+    let content = fs::read_to_string(path).with_context(|| format!("Could not read puzzle set {}", path))?;
+    return parse_puzzle_set(&content);
+}
+
+pub fn parse_puzzle_set(content: &str) -> Result<Vec<Puzzle>> {
+    let mut puzzles = Vec::new();
+    for (line_index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        let position = parts.next().unwrap().to_string();
+        let Some(moves_to_win) = parts.next().and_then(|moves_to_win| moves_to_win.parse::<usize>().ok()) else {
+            bail!("Missing amount of moves to win in line {} of the puzzle set", line_index + 1);
+        };
+        let solution: Vec<String> = parts.map(|notation| notation.to_string()).collect();
+        ensure!(solution.len() == moves_to_win, "The solution in line {} of the puzzle set has {} moves instead of {}", line_index + 1, solution.len(), moves_to_win);
+        puzzles.push(Puzzle { position, moves_to_win, solution });
+    }
+    return Ok(puzzles);
+}
+
+pub fn format_puzzle_set(puzzles: &[Puzzle]) -> String {
+    return puzzles.iter()
+        .map(|puzzle| format!("{} {} {}\n", puzzle.position, puzzle.moves_to_win, puzzle.solution.join(" ")))
+        .collect();
+}
+
+pub fn save_puzzle_set(path: &str, puzzles: &[Puzzle]) -> Result<()> {
+    fs::write(path, format_puzzle_set(puzzles)).with_context(|| format!("Could not write puzzle set {}", path))?;
+    return Ok(());
+}
+
+type KnownWinnerFunction<GS> = Box<dyn Fn(&GS) -> Option<bool>>;
+
+/**
+Finds puzzles with a forced win in exactly moves_to_win moves (plies), either in positions of played games or in random positions.
+The forced wins are searched exhaustively, so every found puzzle is correct as long as the search is.
+To verify the winners independently, a tablebase (4x4) or infinite_depth_minimax can be used, which have to agree with every puzzle.
+infinite_depth_minimax solves the whole game, so it is only feasible for positions with many blocks.
+ */
+pub struct PuzzleGenerator<GS: GameState> {
+    moves_to_win: usize,
+    known_winner: KnownWinnerFunction<GS>,
+    verify_with_minimax: bool,
+}
+
+impl<GS: GameState + SantoriniEval + 'static> PuzzleGenerator<GS> {
+    pub fn new(moves_to_win: usize) -> PuzzleGenerator<GS> {
+        return PuzzleGenerator {
+            moves_to_win,
+            known_winner: Box::new(no_known_winner),
+            verify_with_minimax: false,
+        };
+    }
+
+    // The tablebase accelerates the search and verifies the winner, it has to contain the block counts of the positions
+    pub fn with_tablebase(mut self, tablebase: Arc<WinnerTablebase<GS>>) -> Self where GS: SimplifiedState + ContinuousBlockId {
+        self.known_winner = Box::new(move |state: &GS| tablebase.get_winner(state));
+        return self;
+    }
+
+    pub fn with_minimax_verification(mut self) -> Self {
+        self.verify_with_minimax = true;
+        return self;
+    }
+
+    fn get_moves_to_win(&self, state: &GS, player_a_wins: bool, max_moves: usize) -> Option<usize> {
+        return shortest_forced_win_moves(state, player_a_wins, max_moves, &self.known_winner);
+    }
+
+    // Returns the puzzle if the state is one, an error means that the verification disagrees with the forced win search
+    pub fn check_position(&self, state: &GS) -> Result<Option<Puzzle>> {
+        if self.moves_to_win == 0 || state.has_player_a_won() || state.has_player_b_won() {
+            return Ok(None);
+        }
+        let player_a_wins = state.is_player_a_turn();
+        if self.moves_to_win > 1 && state.to_generic_game_state().can_player_win_immediately(player_a_wins) {
+            return Ok(None);
+        }
+        if self.get_moves_to_win(state, player_a_wins, self.moves_to_win) != Some(self.moves_to_win) {
+            return Ok(None);
+        }
+
+        let mut winning_children = state.get_children_states().into_iter()
+            .filter(|child| self.get_moves_to_win(child, player_a_wins, self.moves_to_win - 1).is_some());
+        let (Some(first_move), None) = (winning_children.next(), winning_children.next()) else {
+            return Ok(None);
+        };
+
+        if let Some(known_player_a_wins) = (self.known_winner)(state) {
+            ensure!(known_player_a_wins == player_a_wins, "The known winner contradicts the forced win in {}", state.to_generic_game_state().to_position_string());
+        }
+        if self.verify_with_minimax {
+            let mut solved_cache = FnvHashMap::default();
+            ensure!(infinite_depth_minimax(*state, &mut solved_cache) == player_a_wins, "infinite_depth_minimax contradicts the forced win in {}", state.to_generic_game_state().to_position_string());
+        }
+
+        return Ok(Some(Puzzle {
+            position: state.to_generic_game_state().to_position_string(),
+            moves_to_win: self.moves_to_win,
+            solution: self.get_solution(state, first_move, player_a_wins)?,
+        }));
+    }
+
+    fn get_solution(&self, state: &GS, first_move: GS, player_a_wins: bool) -> Result<Vec<String>> {
+        let mut solution = vec![get_move_notation(state, &first_move)?];
+        let mut current_state = first_move;
+        let mut remaining_moves = self.moves_to_win - 1;
+
+        while remaining_moves > 0 {
+            let winner_to_move = current_state.is_player_a_turn() == player_a_wins;
+            let mut best_child: Option<(GS, usize)> = None;
+            for child in current_state.get_children_states() {
+                let Some(child_moves_to_win) = self.get_moves_to_win(&child, player_a_wins, remaining_moves - 1) else {
+                    continue;
+                };
+                // The winner chooses the fastest win, the loser the longest defense
+                let is_better = best_child.is_none_or(|(_, best_moves)| {
+                    if winner_to_move { child_moves_to_win < best_moves } else { child_moves_to_win > best_moves }
+                });
+                if is_better {
+                    best_child = Some((child, child_moves_to_win));
+                }
+            }
+
+            let Some((child, child_moves_to_win)) = best_child else {
+                bail!("The forced win could not be followed");
+            };
+            solution.push(get_move_notation(&current_state, &child)?);
+            current_state = child;
+            remaining_moves = child_moves_to_win;
+        }
+        return Ok(solution);
+    }
+
+    // Every position is only checked once, positions that are symmetric to each other count as the same position
+    pub fn find_puzzles(&self, states: &[GS]) -> Result<Vec<Puzzle>> {
+        let mut checked_positions = HashSet::new();
+        let mut puzzles = Vec::new();
+        for state in states {
+            if !checked_positions.insert(state.to_generic_game_state().to_canonical_position_string()) {
+                continue;
+            }
+            if let Some(puzzle) = self.check_position(state)? {
+                puzzles.push(puzzle);
+            }
+        }
+        return Ok(puzzles);
+    }
+
+    // Checks every position of the games, e.g. from self-play
+    pub fn find_puzzles_in_games(&self, records: &[GameRecord]) -> Result<Vec<Puzzle>> {
+        let mut states = Vec::new();
+        for record in records {
+            states.extend(record.get_states::<GS>()?);
+        }
+        return self.find_puzzles(&states);
+    }
+
+    // Checks random positions with the given block counts until puzzle_count puzzles are found or max_tries positions were checked
+    pub fn find_random_puzzles<RNG: rand::Rng>(&self, rng: &mut RNG, block_counts: RangeInclusive<usize>, puzzle_count: usize, max_tries: usize) -> Result<Vec<Puzzle>> {
+        let mut checked_positions = HashSet::new();
+        let mut puzzles = Vec::new();
+        for _ in 0..max_tries {
+            if puzzles.len() >= puzzle_count {
+                break;
+            }
+            let block_count = rng.gen_range(block_counts.clone());
+            let state = GS::from_generic_game_state(&GS::GenericGameState::generate_random_state_with_blocks_rng(rng, block_count));
+            if !checked_positions.insert(state.to_generic_game_state().to_canonical_position_string()) {
+                continue;
+            }
+            if let Some(puzzle) = self.check_position(&state)? {
+                puzzles.push(puzzle);
+            }
+        }
+        return Ok(puzzles);
+    }
+}
+
+// Checks that the solution of the puzzle is legal and ends with the win of the player to move
+pub fn verify_puzzle_solution<GS: GameState>(puzzle: &Puzzle) -> Result<()> {
+    let initial_state = puzzle.get_state::<GS>()?;
+    let player_a_wins = initial_state.is_player_a_turn();
+    let mut state = initial_state;
+    for notation in &puzzle.solution {
+        ensure!(!state.has_player_a_won() && !state.has_player_b_won(), "The solution continues after the game is over");
+        state = find_move(&state, notation)?.1;
+    }
+
+    let has_won = if player_a_wins { state.has_player_a_won() } else { state.has_player_b_won() };
+    let has_lost = if player_a_wins { state.has_player_b_won() } else { state.has_player_a_won() };
+    // The player that cannot move loses
+    let loser_is_stuck = !has_won && !has_lost && state.is_player_a_turn() != player_a_wins && state.get_children_states().is_empty();
+    ensure!(has_won || loser_is_stuck, "The solution doesn't end with the win of the player to move");
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+    use crate::game_state::game_state_5x5_binary_composite::GameState5x5BinaryComposite;
+    use crate::game_state::game_state_5x5_struct::GameState5x5Struct;
+    use crate::game_state::GameState;
+    use crate::generic_game_state::generic_santorini_game_state::GenericSantoriniGameState;
+    use crate::generic_game_state::move_notation::find_move;
+    use crate::generic_game_state::GenericGameState;
+    use crate::minimax::forced_win::{no_known_winner, shortest_forced_win_moves};
+    use crate::play_game::puzzle_set::{format_puzzle_set, parse_puzzle_set, verify_puzzle_solution, PuzzleGenerator};
+
+    #[test]
+    fn test_puzzles_are_found_for_every_representation() {
+        let mut rng = rand::thread_rng();
+        let struct_generator = PuzzleGenerator::<GameState5x5Struct>::new(3);
+        let binary_generator = PuzzleGenerator::<GameState5x5BinaryComposite>::new(3);
+        for _ in 0..200 {
+            let block_count = rng.gen_range(25..=45);
+            let generic_state = GenericSantoriniGameState::<5, 5, 2>::generate_random_state_with_blocks_rng(&mut rng, block_count);
+            let struct_puzzle = struct_generator.check_position(&GameState5x5Struct::from_generic_game_state(&generic_state)).unwrap();
+            let binary_puzzle = binary_generator.check_position(&GameState5x5BinaryComposite::from_generic_game_state(&generic_state)).unwrap();
+            assert_eq!(struct_puzzle, binary_puzzle, "Puzzle discrepancy in {}", generic_state.to_position_string());
+        }
+    }
+
+    #[test]
+    fn test_puzzles_have_a_unique_winning_move_and_a_winning_solution() {
+        let generator = PuzzleGenerator::<GameState5x5BinaryComposite>::new(3).with_minimax_verification();
+        let puzzles = generator.find_random_puzzles(&mut rand::thread_rng(), 55..=75, 5, 5000).unwrap();
+        assert!(!puzzles.is_empty());
+        for puzzle in &puzzles {
+            verify_puzzle_solution::<GameState5x5BinaryComposite>(puzzle).unwrap();
+            assert_eq!(puzzle.solution.len(), puzzle.moves_to_win);
+
+            let state = puzzle.get_state::<GameState5x5BinaryComposite>().unwrap();
+            let player_a_wins = state.is_player_a_turn();
+            let winning_children: Vec<GameState5x5BinaryComposite> = state.get_children_states().into_iter()
+                .filter(|child| shortest_forced_win_moves(child, player_a_wins, puzzle.moves_to_win - 1, &no_known_winner).is_some())
+                .collect();
+            assert!(winning_children == vec![find_move(&state, &puzzle.solution[0]).unwrap().1], "{}", puzzle.position);
+
+            // Without the last move, the solution doesn't reach the win
+            let mut incomplete_puzzle = puzzle.clone();
+            incomplete_puzzle.solution.pop();
+            assert!(verify_puzzle_solution::<GameState5x5BinaryComposite>(&incomplete_puzzle).is_err());
+        }
+    }
+
+    #[test]
+    fn test_puzzle_set_is_parsed_to_the_same_puzzles() {
+        let generator = PuzzleGenerator::<GameState5x5BinaryComposite>::new(1);
+        let puzzles = generator.find_random_puzzles(&mut rand::thread_rng(), 30..=60, 5, 5000).unwrap();
+        assert!(!puzzles.is_empty());
+        assert_eq!(parse_puzzle_set(&format_puzzle_set(&puzzles)).unwrap(), puzzles);
+
+        let content = format!("# Comment\n\n{} 2 {}\n", puzzles[0].position, puzzles[0].solution[0]);
+        assert!(parse_puzzle_set(&content).is_err());
+        assert!(parse_puzzle_set(&format!("{}\n", puzzles[0].position)).is_err());
+    }
+}
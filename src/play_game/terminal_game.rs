@@ -0,0 +1,470 @@
+use std::io::{BufRead, Write};
+use std::time::Duration;
+use anyhow::{anyhow, bail, ensure, Result};
+use crate::engine_protocol::format_score;
+use crate::game_state::{GameState, SantoriniEval};
+use crate::generic_game_state::move_notation::{find_move, get_move_notation};
+use crate::generic_game_state::GenericGameState;
+use crate::minimax::analysis::analyze_root_moves;
+use crate::play_game::game_record::GameRecord;
+use crate::strategy::game_context::{GameContext, TimeControl};
+use crate::strategy::StatefulStrategy;
+
+pub const DEFAULT_HINT_DEPTH: usize = 4;
+const MAX_HINT_DEPTH: usize = 10;
+const HINT_MOVES: usize = 3;
+const HUMAN_NAME: &str = "Human";
+
+const HELP: &str = "Commands:
+  <move>         Play a move, e.g. \"a1,c3\" to place workers or \"b2-c3+d4\" to move from b2 to c3 and build on d4
+  <partial move> List the possible continuations, e.g. \"b2\" for the moves of the worker on b2
+  moves          List all legal moves
+  board          Show the board
+  undo, redo     Take back a move or play it again
+  hint [depth]   Show the best moves according to the engine
+  save <file>    Save the game
+  load <file>    Load a saved game
+  help           Show this help
+  quit           End the game";
+
+// Heights are 0 to 3, 4 is a dome, A and B are the workers of the players. The first row is at the bottom, like in chess
+pub fn format_board<GS: GameState>(state: &GS) -> String {
+    let generic_state = state.to_generic_game_state();
+    let rows = GS::GenericGameState::ROWS;
+    let columns = GS::GenericGameState::COLUMNS;
+    let separator = format!("  +{}\n", "---+".repeat(columns));
+
+    let mut board = "   ".to_string();
+    for column in 0..columns {
+        board.push_str(&format!(" {}  ", (b'a' + column as u8) as char));
+    }
+    board.truncate(board.trim_end().len());
+    board.push('\n');
+    board.push_str(&separator);
+    for row in (0..rows).rev() {
+        board.push_str(&format!("{} |", row + 1));
+        for column in 0..columns {
+            let tile_id = row * columns + column;
+            let worker = generic_state.get_character_on_tile(tile_id);
+            board.push_str(&format!(" {}{}|", generic_state.get_tile_height(tile_id), worker));
+        }
+        board.push('\n');
+        board.push_str(&separator);
+    }
+    return board;
+}
+
+// Winner of the game (true for player A), None if the game is not over
+fn get_winner<GS: GameState>(state: &GS) -> Option<bool> {
+    if state.has_player_a_won() || state.has_player_b_won() {
+        return Some(state.has_player_a_won());
+    }
+    if state.get_children_states().is_empty() {
+        return Some(!state.is_player_a_turn());
+    }
+    return None;
+}
+
+fn get_legal_moves<GS: GameState>(state: &GS) -> Result<Vec<String>> {
+    if get_winner(state).is_some() {
+        return Ok(Vec::new());
+    }
+    return state.get_children_states().iter().map(|child_state| get_move_notation(state, child_state)).collect();
+}
+
+// Shortens the legal moves that start with the partial move to the next part, e.g. "b2" to "b2-c3" and "b2-c3" to "b2-c3+d4"
+// Worker placements are listed completely if they contain the partial move, as their tiles are sorted
+fn get_continuations(legal_moves: &[String], partial_move: &str) -> Vec<String> {
+    let is_continuation = |legal_move: &&String| legal_move.starts_with(partial_move) || legal_move.split(',').skip(1).any(|tile| tile == partial_move);
+    let mut continuations: Vec<String> = Vec::new();
+    for legal_move in legal_moves.iter().filter(is_continuation) {
+        let next_separator = legal_move.char_indices()
+            .skip(partial_move.len() + 1)
+            .find(|(_, character)| *character == '-' || *character == '+')
+            .map(|(index, _)| index);
+        let continuation = next_separator.map(|index| &legal_move[..index]).unwrap_or(legal_move).to_string();
+        if !continuations.contains(&continuation) {
+            continuations.push(continuation);
+        }
+    }
+    return continuations;
+}
+
+struct Opponent<GS: GameState> {
+    name: String,
+    plays_player_a: bool,
+    strategy: Box<dyn StatefulStrategy<GameState=GS>>,
+    // Set when the moves of the game were changed by undo, redo or load, so the strategy has to start over
+    needs_new_game: bool,
+}
+
+/**
+Interactive game in the terminal against another human or a strategy, with moves entered in notation.
+Hints are computed by searching the current position with the hint heuristic.
+ */
+pub struct TerminalGame<GS: GameState + SantoriniEval> {
+    // Starts with the initial state and ends with the current state
+    states: Vec<GS>,
+    // States taken back by undo, the most recently taken back state is last
+    undone_states: Vec<GS>,
+    opponent: Option<Opponent<GS>>,
+    hint_heuristic: Box<dyn Fn(&GS) -> f32>,
+    hint_depth: usize,
+    // Seed of the game context of the opponent
+    seed: u64,
+}
+
+enum CommandResult {
+    Continue,
+    Quit,
+}
+
+impl<GS: GameState + SantoriniEval> TerminalGame<GS> {
+    pub fn new<F: Fn(&GS) -> f32 + 'static>(initial_state: GS, hint_heuristic: F) -> TerminalGame<GS> {
+        return TerminalGame {
+            states: vec![initial_state],
+            undone_states: Vec::new(),
+            opponent: None,
+            hint_heuristic: Box::new(hint_heuristic),
+            hint_depth: DEFAULT_HINT_DEPTH,
+            seed: 0,
+        };
+    }
+
+    pub fn with_opponent<S: StatefulStrategy<GameState=GS> + 'static>(mut self, name: &str, strategy: S, plays_player_a: bool) -> Self {
+        self.opponent = Some(Opponent {
+            name: name.to_string(),
+            plays_player_a,
+            strategy: Box::new(strategy),
+            needs_new_game: true,
+        });
+        return self;
+    }
+
+    pub fn with_hint_depth(mut self, hint_depth: usize) -> Self {
+        self.hint_depth = hint_depth;
+        return self;
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        return self;
+    }
+
+    pub fn get_states(&self) -> &[GS] {
+        return &self.states;
+    }
+
+    pub fn get_record(&self) -> Result<GameRecord> {
+        let player_name = |player_a: bool| match &self.opponent {
+            Some(opponent) if opponent.plays_player_a == player_a => opponent.name.clone(),
+            _ => HUMAN_NAME.to_string(),
+        };
+        let mut record = GameRecord::from_states(&self.states)?.with_players(&player_name(true), &player_name(false));
+        record.player_a_wins = get_winner(self.get_current_state());
+        return Ok(record);
+    }
+
+    // Handles commands until "quit" or the end of the input, the opponent moves as soon as it is its turn
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, mut output: W) -> Result<()> {
+        writeln!(output, "{}\n", HELP)?;
+        self.write_position(&mut output)?;
+
+        let mut lines = input.lines();
+        loop {
+            if self.is_opponent_to_move() {
+                let notation = self.play_opponent_move()?;
+                writeln!(output, "{} played {}", self.opponent.as_ref().unwrap().name, notation)?;
+                self.write_position(&mut output)?;
+                continue;
+            }
+
+            write!(output, "{}> ", self.get_player_name())?;
+            output.flush()?;
+            let Some(line) = lines.next() else {
+                break;
+            };
+            let line = line?;
+            match self.handle_command(line.trim(), &mut output) {
+                Ok(CommandResult::Continue) => {}
+                Ok(CommandResult::Quit) => break,
+                Err(error) => writeln!(output, "Error: {}", error)?,
+            }
+        }
+        return Ok(());
+    }
+
+    fn get_current_state(&self) -> &GS {
+        return self.states.last().unwrap();
+    }
+
+    fn get_player_name(&self) -> String {
+        return format!("Player {}", if self.get_current_state().is_player_a_turn() { "A" } else { "B" });
+    }
+
+    fn is_opponent_turn(&self, state: &GS) -> bool {
+        return self.opponent.as_ref().is_some_and(|opponent| opponent.plays_player_a == state.is_player_a_turn());
+    }
+
+    fn is_opponent_to_move(&self) -> bool {
+        let current_state = self.get_current_state();
+        return get_winner(current_state).is_none() && self.is_opponent_turn(current_state);
+    }
+
+    fn write_position<W: Write>(&self, output: &mut W) -> Result<()> {
+        let current_state = self.get_current_state();
+        writeln!(output, "\n{}", format_board(current_state))?;
+        match get_winner(current_state) {
+            Some(player_a_wins) => writeln!(output, "Player {} won the game", if player_a_wins { "A" } else { "B" })?,
+            None => writeln!(output, "Move {}, {} to move", self.states.len(), self.get_player_name())?,
+        }
+        return Ok(());
+    }
+
+    fn handle_command<W: Write>(&mut self, line: &str, output: &mut W) -> Result<CommandResult> {
+        let (command, argument) = line.split_once(' ').map(|(command, argument)| (command, Some(argument.trim()))).unwrap_or((line, None));
+        match command {
+            "" => {}
+            "quit" | "exit" => return Ok(CommandResult::Quit),
+            "help" => writeln!(output, "{}", HELP)?,
+            "board" => self.write_position(output)?,
+            "moves" => writeln!(output, "{}", get_legal_moves(self.get_current_state())?.join(" "))?,
+            "undo" => {
+                self.undo()?;
+                self.write_position(output)?;
+            }
+            "redo" => {
+                self.redo()?;
+                self.write_position(output)?;
+            }
+            "hint" => {
+                let depth = match argument {
+                    Some(depth) => depth.parse::<usize>()?,
+                    None => self.hint_depth,
+                };
+                self.write_hint(depth, output)?;
+            }
+            "save" => {
+                let path = argument.ok_or_else(|| anyhow!("Missing file name"))?;
+                self.get_record()?.save(path)?;
+                writeln!(output, "Saved the game to {}", path)?;
+            }
+            "load" => {
+                let path = argument.ok_or_else(|| anyhow!("Missing file name"))?;
+                self.load(&GameRecord::load(path)?)?;
+                writeln!(output, "Loaded the game from {}", path)?;
+                self.write_position(output)?;
+            }
+            _ => self.play_input_move(line, output)?,
+        }
+        return Ok(CommandResult::Continue);
+    }
+
+    fn play_input_move<W: Write>(&mut self, input: &str, output: &mut W) -> Result<()> {
+        let current_state = *self.get_current_state();
+        ensure!(get_winner(&current_state).is_none(), "The game is over, use undo or load to continue");
+
+        match find_move(&current_state, input) {
+            Ok((_, next_state)) => {
+                self.states.push(next_state);
+                self.undone_states.clear();
+                self.write_position(output)?;
+            }
+            Err(error) => {
+                let continuations = get_continuations(&get_legal_moves(&current_state)?, &input.to_lowercase());
+                if continuations.is_empty() {
+                    bail!("{} (enter \"moves\" to list the legal moves or \"help\" for the commands)", error);
+                }
+                writeln!(output, "Possible continuations: {}", continuations.join(" "))?;
+            }
+        }
+        return Ok(());
+    }
+
+    fn play_opponent_move(&mut self) -> Result<String> {
+        let current_state = *self.get_current_state();
+        let mut context = GameContext::with_seed(self.states[0], TimeControl::Unlimited, self.seed);
+        context.history = self.states.clone();
+        context.move_times = vec![Duration::ZERO; self.states.len() - 1];
+
+        let opponent = self.opponent.as_mut().unwrap();
+        if opponent.needs_new_game {
+            opponent.strategy.new_game(opponent.plays_player_a, &context)?;
+            opponent.needs_new_game = false;
+        }
+        let possible_next_states = current_state.get_children_states();
+        let index = opponent.strategy.choose_move_in_context(opponent.plays_player_a, &context, &possible_next_states)?;
+        let next_state = *possible_next_states.get(index)
+            .ok_or_else(|| anyhow!("{} chose the invalid move index {}", opponent.name, index))?;
+
+        self.states.push(next_state);
+        return get_move_notation(&current_state, &next_state);
+    }
+
+    // Against an opponent, its reply is taken back too, so the human is to move again
+    fn undo(&mut self) -> Result<()> {
+        let after_one_move_is_opponent_turn = self.states.len() >= 2 && self.is_opponent_turn(&self.states[self.states.len() - 2]);
+        let moves_to_undo = if after_one_move_is_opponent_turn { 2 } else { 1 };
+        ensure!(self.states.len() > moves_to_undo, "There is no move to undo");
+
+        for _ in 0..moves_to_undo {
+            self.undone_states.push(self.states.pop().unwrap());
+        }
+        self.reset_opponent();
+        return Ok(());
+    }
+
+    fn redo(&mut self) -> Result<()> {
+        let next_state = self.undone_states.pop().ok_or_else(|| anyhow!("There is no move to redo"))?;
+        self.states.push(next_state);
+        if self.is_opponent_to_move() {
+            if let Some(reply) = self.undone_states.pop() {
+                self.states.push(reply);
+            }
+        }
+        self.reset_opponent();
+        return Ok(());
+    }
+
+    fn load(&mut self, record: &GameRecord) -> Result<()> {
+        self.states = record.get_states::<GS>()?;
+        self.undone_states.clear();
+        self.reset_opponent();
+        return Ok(());
+    }
+
+    fn reset_opponent(&mut self) {
+        if let Some(opponent) = self.opponent.as_mut() {
+            opponent.needs_new_game = true;
+        }
+    }
+
+    fn write_hint<W: Write>(&self, depth: usize, output: &mut W) -> Result<()> {
+        ensure!((1..=MAX_HINT_DEPTH).contains(&depth), "The depth must be between 1 and {}", MAX_HINT_DEPTH);
+        let current_state = *self.get_current_state();
+        ensure!(get_winner(&current_state).is_none(), "The game is over");
+
+        let hint_heuristic = |state: &GS| (self.hint_heuristic)(state);
+        let analyzed_moves = analyze_root_moves(&current_state, depth, HINT_MOVES, &hint_heuristic);
+        for (i, analyzed_move) in analyzed_moves.iter().enumerate() {
+            let mut principal_variation = Vec::with_capacity(analyzed_move.principal_variation.len());
+            let mut previous_state = current_state;
+            for pv_state in &analyzed_move.principal_variation {
+                principal_variation.push(get_move_notation(&previous_state, pv_state)?);
+                previous_state = *pv_state;
+            }
+            writeln!(output, "{}. {} (score {}, line {})",
+                     i + 1,
+                     principal_variation[0],
+                     format_score(analyzed_move.score, current_state.is_player_a_turn()),
+                     principal_variation.join(" "))?;
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::game_state_5x5_binary_composite::GameState5x5BinaryComposite;
+    use crate::game_state::{get_initial_setup_state, GameState};
+    use crate::generic_game_state::generic_santorini_game_state::GenericSantoriniGameState;
+    use crate::generic_game_state::move_notation::apply_moves;
+    use crate::generic_game_state::GenericGameState;
+    use crate::play_game::terminal_game::TerminalGame;
+    use crate::strategy::heuristics::dynamic_heuristic::{dynamic_heuristic, DynamicHeuristicParams};
+    use crate::strategy::random_strategy::RandomStrategy;
+
+    type GS = GameState5x5BinaryComposite;
+
+    // Player A has workers on a1 and e5, player B on c3 and d4, all tiles are empty
+    const POSITION: &str = "0000000000000000000000000/0,24/12,18/A";
+
+    fn get_state(position: &str) -> GS {
+        return GS::from_generic_game_state(&GenericSantoriniGameState::<5, 5, 2>::from_position_string(position).unwrap());
+    }
+
+    fn new_game(initial_state: GS) -> TerminalGame<GS> {
+        let params = DynamicHeuristicParams::default();
+        return TerminalGame::new(initial_state, move |state| dynamic_heuristic(state, &params));
+    }
+
+    // Runs the game with the given input and returns the output
+    fn run(game: &mut TerminalGame<GS>, input: &str) -> String {
+        let mut output = Vec::new();
+        game.run(input.as_bytes(), &mut output).unwrap();
+        return String::from_utf8(output).unwrap();
+    }
+
+    fn get_continuations(output: &str) -> Vec<Vec<&str>> {
+        return output.lines()
+            .filter_map(|line| line.split_once("Possible continuations: "))
+            .map(|(_, continuations)| {
+                let mut continuations: Vec<&str> = continuations.split(' ').collect();
+                continuations.sort_unstable();
+                continuations
+            })
+            .collect();
+    }
+
+    #[test]
+    fn test_moves_are_played_and_partial_moves_list_continuations() {
+        let initial_state = get_state(POSITION);
+        let mut game = new_game(initial_state);
+        let output = run(&mut game, "a1\na1-b1\na1-b1+a1\n");
+
+        assert_eq!(get_continuations(&output), vec![
+            vec!["a1-a2", "a1-b1", "a1-b2"],
+            vec!["a1-b1+a1", "a1-b1+a2", "a1-b1+b2", "a1-b1+c1", "a1-b1+c2"],
+        ]);
+        assert_eq!(game.get_states(), &[initial_state, apply_moves(&initial_state, &["a1-b1+a1"]).unwrap()]);
+        assert!(output.contains("Move 2, Player B to move"));
+    }
+
+    #[test]
+    fn test_undo_against_an_opponent_takes_back_its_reply() {
+        let mut game = new_game(get_state(POSITION)).with_opponent("Random", RandomStrategy::<GS>::new(), false);
+        let output = run(&mut game, "a1-b1+a1\n");
+        assert!(output.contains("Random played "));
+        let states = game.get_states().to_vec();
+        assert_eq!(states.len(), 3);
+
+        let output = run(&mut game, "undo\nundo\n");
+        assert_eq!(game.get_states(), &states[..1]);
+        assert!(output.contains("Error: There is no move to undo"));
+        assert!(!output.contains("Random played "));
+
+        let output = run(&mut game, "redo\nredo\n");
+        assert_eq!(game.get_states(), &states[..]);
+        assert!(output.contains("Error: There is no move to redo"));
+        assert!(!output.contains("Random played "));
+    }
+
+    #[test]
+    fn test_saved_game_is_loaded_with_the_same_states() {
+        let path = std::env::temp_dir().join("santorini_test_terminal_game.txt").to_string_lossy().to_string();
+        let mut game = new_game(get_initial_setup_state());
+        let output = run(&mut game, &format!("a1,b2\nd4,e5\na1-a2+a1\nsave {}\n", path));
+        assert!(output.contains(&format!("Saved the game to {}", path)));
+        assert_eq!(game.get_states().len(), 4);
+
+        let mut loaded_game = new_game(get_initial_setup_state());
+        let output = run(&mut loaded_game, &format!("load {}\n", path));
+        assert!(output.contains(&format!("Loaded the game from {}", path)));
+        assert!(output.contains("Move 4, Player B to move"));
+        assert_eq!(loaded_game.get_states(), game.get_states());
+        assert_eq!(loaded_game.get_record().unwrap(), game.get_record().unwrap());
+    }
+
+    #[test]
+    fn test_hint_shows_the_winning_move() {
+        // Player A wins by moving from a1 (height 2) to b1 (height 3)
+        let mut game = new_game(get_state("2310000000000000000000000/0,24/12,18/A"));
+        let output = run(&mut game, "hint 2\n");
+        let hints: Vec<&str> = output.lines().filter(|line| line.contains(" (score ")).collect();
+        assert_eq!(hints.len(), 3);
+        for (i, hint) in hints.iter().enumerate() {
+            assert!(hint.contains(&format!("{}. a1-b1+", i + 1)));
+            assert!(hint.contains("(score win 1, line a1-b1+"));
+        }
+    }
+}
@@ -0,0 +1,730 @@
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use anyhow::{anyhow, bail, ensure, Result};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use crate::engine_protocol::format_score;
+use crate::game_state::{get_initial_setup_state, GameState, SantoriniEval};
+use crate::generic_game_state::move_notation::{find_move, get_move_notation};
+use crate::generic_game_state::GenericGameState;
+use crate::minimax::analysis::analyze_root_moves;
+use crate::play_game::GameEndReason;
+use crate::strategy::game_context::{GameContext, TimeControl};
+use crate::strategy::StatefulStrategy;
+
+/*
+HTTP interface to play against the bots, games are only kept in memory. Requests and responses are JSON,
+moves use the notation of generic_game_state::move_notation and positions use position strings (see GenericGameState).
+
+- GET /bots: Names of the bots
+- POST /games {"player_a": <bot name>, "player_b": <bot name>, "position": <position string>, "move_time_ms": <ms>, "seed": <seed>}
+  Creates a game, every field is optional. Players without a bot are humans, the default position is the empty board.
+- GET /games/:id: State of the game (GameView)
+- DELETE /games/:id: Removes the game. Without deleting, the oldest games are removed once there are more than max_games games
+- GET /games/:id/legal-moves: Possible moves and the positions they lead to
+- POST /games/:id/moves {"move": <move>}: Plays the move of the human player to move
+- POST /games/:id/bot-move: Lets the bot of the player to move play a move
+- GET /games/:id/analysis?depth=<depth>&moves=<number of moves>: Best moves of the current position, scores as in engine_protocol
+- GET /games/:id/ws: WebSocket, see ClientMessage and ServerMessage.
+  The game is sent after connecting. After a human move, the bots reply immediately, and every bot move is sent when it is played.
+ */
+
+pub const DEFAULT_ANALYSIS_DEPTH: usize = 4;
+// Analysis runs on the server, so the depth is limited to keep requests from blocking it for minutes
+pub const MAX_ANALYSIS_DEPTH: usize = 8;
+// Games kept in memory before the oldest ones are removed
+pub const DEFAULT_MAX_GAMES: usize = 1000;
+
+pub type BotFactory<GS> = Arc<dyn Fn() -> Box<dyn StatefulStrategy<GameState=GS>> + Send + Sync>;
+type HeuristicFunction<GS> = Arc<dyn Fn(&GS) -> f32 + Send + Sync>;
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct CreateGameRequest {
+    pub player_a: Option<String>,
+    pub player_b: Option<String>,
+    pub position: Option<String>,
+    pub move_time_ms: Option<u64>,
+    pub seed: Option<u64>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct MoveRequest {
+    #[serde(rename = "move")]
+    pub notation: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct AnalysisQuery {
+    pub depth: Option<usize>,
+    pub moves: Option<usize>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GameView {
+    pub id: u64,
+    pub position: String,
+    // "A" or "B"
+    pub player_to_move: String,
+    // Bot names, None for human players
+    pub player_a: Option<String>,
+    pub player_b: Option<String>,
+    pub moves: Vec<String>,
+    pub legal_moves: Vec<String>,
+    // "A" or "B" once the game is over
+    pub winner: Option<String>,
+    pub end_reason: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LegalMoveView {
+    #[serde(rename = "move")]
+    pub notation: String,
+    pub position: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct AnalyzedMoveView {
+    #[serde(rename = "move")]
+    pub notation: String,
+    // From the perspective of the player to move, see engine_protocol::format_score
+    pub score: String,
+    pub principal_variation: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct AnalysisView {
+    pub depth: usize,
+    pub moves: Vec<AnalyzedMoveView>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Move {
+        #[serde(rename = "move")]
+        notation: String,
+    },
+    // Bots play until a human is to move or the game is over
+    BotMoves,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Game { game: GameView },
+    BotMove {
+        #[serde(rename = "move")]
+        notation: String,
+        game: GameView,
+    },
+    Error { message: String },
+}
+
+#[derive(Clone, Debug, Serialize)]
+struct ErrorView {
+    error: String,
+}
+
+pub struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn not_found(message: String) -> ApiError {
+        return ApiError { status: StatusCode::NOT_FOUND, message };
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(error: anyhow::Error) -> ApiError {
+        return ApiError { status: StatusCode::BAD_REQUEST, message: error.to_string() };
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        return (self.status, Json(ErrorView { error: self.message })).into_response();
+    }
+}
+
+struct Bot<GS: GameState> {
+    name: String,
+    strategy: Box<dyn StatefulStrategy<GameState=GS>>,
+}
+
+struct ServerGame<GS: GameState> {
+    id: u64,
+    context: GameContext<GS>,
+    player_a_bot: Option<Bot<GS>>,
+    player_b_bot: Option<Bot<GS>>,
+    // Winner (true for player A) and reason, once the game is over
+    result: Option<(bool, GameEndReason)>,
+}
+
+impl<GS: GameState> ServerGame<GS> {
+    fn new(id: u64, context: GameContext<GS>, player_a_bot: Option<Bot<GS>>, player_b_bot: Option<Bot<GS>>) -> Result<ServerGame<GS>> {
+        let mut game = ServerGame { id, context, player_a_bot, player_b_bot, result: None };
+        if let Some(bot) = game.player_a_bot.as_mut() {
+            bot.strategy.new_game(true, &game.context)?;
+        }
+        if let Some(bot) = game.player_b_bot.as_mut() {
+            bot.strategy.new_game(false, &game.context)?;
+        }
+        game.update_result();
+        return Ok(game);
+    }
+
+    fn is_bot_to_move(&self) -> bool {
+        let bot = if self.context.get_current_state().is_player_a_turn() { &self.player_a_bot } else { &self.player_b_bot };
+        return self.result.is_none() && bot.is_some();
+    }
+
+    fn update_result(&mut self) {
+        let current_state = self.context.get_current_state();
+        if current_state.has_player_a_won() || current_state.has_player_b_won() {
+            self.result = Some((current_state.has_player_a_won(), GameEndReason::ReachedHeightThree));
+        } else if current_state.get_children_states().is_empty() {
+            self.result = Some((!current_state.is_player_a_turn(), GameEndReason::NoPossibleMoves));
+        }
+    }
+
+    // Like in play_game, the bot of the player to move is informed about the move, and loses if it returns an error
+    fn apply_move(&mut self, next_state: GS, time_used: Duration) {
+        let player_a_moved = self.context.get_current_state().is_player_a_turn();
+        if !self.context.apply_move(next_state, time_used) {
+            self.result = Some((!player_a_moved, GameEndReason::Timeout));
+            return;
+        }
+        self.update_result();
+        if self.result.is_some() {
+            return;
+        }
+
+        let bot = if player_a_moved { self.player_b_bot.as_mut() } else { self.player_a_bot.as_mut() };
+        if let Some(bot) = bot {
+            if let Err(error) = bot.strategy.opponent_moved(&self.context) {
+                self.result = Some((player_a_moved, GameEndReason::StrategyError(error.to_string())));
+            }
+        }
+    }
+
+    fn play_human_move(&mut self, notation: &str) -> Result<()> {
+        ensure!(self.result.is_none(), "The game is over");
+        ensure!(!self.is_bot_to_move(), "The bot is to move");
+        let (_, next_state) = find_move(self.context.get_current_state(), notation)?;
+        // Humans are not bound to the time control
+        self.apply_move(next_state, Duration::ZERO);
+        return Ok(());
+    }
+
+    fn play_bot_move(&mut self) -> Result<()> {
+        ensure!(self.result.is_none(), "The game is over");
+        ensure!(self.is_bot_to_move(), "A human is to move");
+        let player_a_turn = self.context.get_current_state().is_player_a_turn();
+        let possible_next_states = self.context.get_current_state().get_children_states();
+
+        let bot = if player_a_turn { self.player_a_bot.as_mut() } else { self.player_b_bot.as_mut() };
+        let bot = bot.expect("The bot is to move");
+        let start = Instant::now();
+        let chosen_move_index = bot.strategy.choose_move_in_context(player_a_turn, &self.context, &possible_next_states);
+        let time_used = start.elapsed();
+
+        match chosen_move_index {
+            Ok(index) if index < possible_next_states.len() => self.apply_move(possible_next_states[index], time_used),
+            Ok(index) => {
+                let error = format!("Invalid move index {} for {} possible moves", index, possible_next_states.len());
+                self.result = Some((!player_a_turn, GameEndReason::StrategyError(error)));
+            }
+            Err(error) => self.result = Some((!player_a_turn, GameEndReason::StrategyError(error.to_string()))),
+        }
+        return Ok(());
+    }
+
+    fn get_view(&self) -> Result<GameView> {
+        let current_state = self.context.get_current_state();
+        let mut moves = Vec::with_capacity(self.context.history.len() - 1);
+        for states in self.context.history.windows(2) {
+            moves.push(get_move_notation(&states[0], &states[1])?);
+        }
+        let mut legal_moves = Vec::new();
+        if self.result.is_none() {
+            for child_state in current_state.get_children_states() {
+                legal_moves.push(get_move_notation(current_state, &child_state)?);
+            }
+        }
+        let player_name = |player_a: bool| if player_a { "A".to_string() } else { "B".to_string() };
+
+        return Ok(GameView {
+            id: self.id,
+            position: current_state.to_generic_game_state().to_position_string(),
+            player_to_move: player_name(current_state.is_player_a_turn()),
+            player_a: self.player_a_bot.as_ref().map(|bot| bot.name.clone()),
+            player_b: self.player_b_bot.as_ref().map(|bot| bot.name.clone()),
+            moves,
+            legal_moves,
+            winner: self.result.as_ref().map(|(player_a_wins, _)| player_name(*player_a_wins)),
+            end_reason: self.result.as_ref().map(|(_, end_reason)| format!("{:?}", end_reason)),
+        });
+    }
+}
+
+type SharedGame<GS> = Arc<Mutex<ServerGame<GS>>>;
+
+/**
+Local server for playing against the strategies in a browser, see the comment at the top of the file for the endpoints.
+Bot moves and analyses are computed on the blocking thread pool of tokio, so they don't stall other requests.
+ */
+pub struct PlayServer<GS: GameState + SantoriniEval + 'static> {
+    bots: BTreeMap<String, BotFactory<GS>>,
+    heuristic_function: HeuristicFunction<GS>,
+    // Ordered by id, so the first game is the oldest one
+    games: Mutex<BTreeMap<u64, SharedGame<GS>>>,
+    max_games: usize,
+    next_game_id: AtomicU64,
+}
+
+impl<GS: GameState + SantoriniEval + 'static> PlayServer<GS> {
+    // The heuristic function is used for analyses
+    pub fn new<F: Fn(&GS) -> f32 + Send + Sync + 'static>(heuristic_function: F) -> PlayServer<GS> {
+        return PlayServer {
+            bots: BTreeMap::new(),
+            heuristic_function: Arc::new(heuristic_function),
+            games: Mutex::new(BTreeMap::new()),
+            max_games: DEFAULT_MAX_GAMES,
+            next_game_id: AtomicU64::new(1),
+        };
+    }
+
+    pub fn with_max_games(mut self, max_games: usize) -> Self {
+        assert!(max_games > 0);
+        self.max_games = max_games;
+        return self;
+    }
+
+    // Every game gets its own instance of the strategy
+    pub fn with_bot<S: StatefulStrategy<GameState=GS> + 'static, C: Fn() -> S + Send + Sync + 'static>(mut self, name: &str, create_strategy: C) -> Self {
+        let factory: BotFactory<GS> = Arc::new(move || Box::new(create_strategy()));
+        self.bots.insert(name.to_string(), factory);
+        return self;
+    }
+
+    pub fn into_router(self) -> Router {
+        return Router::new()
+            .route("/bots", get(list_bots::<GS>))
+            .route("/games", post(create_game::<GS>))
+            .route("/games/:id", get(get_game::<GS>).delete(delete_game::<GS>))
+            .route("/games/:id/legal-moves", get(get_legal_moves::<GS>))
+            .route("/games/:id/moves", post(post_move::<GS>))
+            .route("/games/:id/bot-move", post(post_bot_move::<GS>))
+            .route("/games/:id/analysis", get(get_analysis::<GS>))
+            .route("/games/:id/ws", get(connect_websocket::<GS>))
+            .with_state(Arc::new(self));
+    }
+
+    pub async fn serve(self, address: SocketAddr) -> Result<()> {
+        return self.serve_with_listener(tokio::net::TcpListener::bind(address).await?).await;
+    }
+
+    // Binding the listener to port 0 lets the system choose a free port, which can be read from the listener before serving
+    pub async fn serve_with_listener(self, listener: tokio::net::TcpListener) -> Result<()> {
+        axum::serve(listener, self.into_router()).await?;
+        return Ok(());
+    }
+
+    fn create_bot(&self, name: &Option<String>) -> Result<Option<Bot<GS>>> {
+        let Some(name) = name else {
+            return Ok(None);
+        };
+        let factory = self.bots.get(name).ok_or_else(|| anyhow!("Unknown bot '{}'", name))?;
+        return Ok(Some(Bot { name: name.clone(), strategy: factory() }));
+    }
+
+    async fn get_game(&self, id: u64) -> Result<SharedGame<GS>, ApiError> {
+        return self.games.lock().await.get(&id).cloned()
+            .ok_or_else(|| ApiError::not_found(format!("Game {} doesn't exist", id)));
+    }
+}
+
+async fn run_blocking<T: Send + 'static, F: FnOnce() -> Result<T> + Send + 'static>(function: F) -> Result<T> {
+    return tokio::task::spawn_blocking(function).await.map_err(|error| anyhow!("The computation failed: {}", error))?;
+}
+
+async fn list_bots<GS: GameState + SantoriniEval + 'static>(State(server): State<Arc<PlayServer<GS>>>) -> Json<Vec<String>> {
+    return Json(server.bots.keys().cloned().collect());
+}
+
+async fn create_game<GS: GameState + SantoriniEval + 'static>(
+    State(server): State<Arc<PlayServer<GS>>>,
+    Json(request): Json<CreateGameRequest>,
+) -> Result<Json<GameView>, ApiError> {
+    let initial_state = match &request.position {
+        Some(position) => GS::from_generic_game_state(&GS::GenericGameState::from_position_string(position)?),
+        None => get_initial_setup_state::<GS>(),
+    };
+    let time_control = match request.move_time_ms {
+        Some(move_time_ms) => TimeControl::FixedPerMove(Duration::from_millis(move_time_ms)),
+        None => TimeControl::Unlimited,
+    };
+    let context = match request.seed {
+        Some(seed) => GameContext::with_seed(initial_state, time_control, seed),
+        None => GameContext::with_time_control(initial_state, time_control),
+    };
+
+    let id = server.next_game_id.fetch_add(1, Ordering::SeqCst);
+    let game = ServerGame::new(id, context, server.create_bot(&request.player_a)?, server.create_bot(&request.player_b)?)?;
+    let view = game.get_view()?;
+    let mut games = server.games.lock().await;
+    games.insert(id, Arc::new(Mutex::new(game)));
+    while games.len() > server.max_games {
+        games.pop_first();
+    }
+    return Ok(Json(view));
+}
+
+async fn delete_game<GS: GameState + SantoriniEval + 'static>(
+    State(server): State<Arc<PlayServer<GS>>>,
+    Path(id): Path<u64>,
+) -> Result<StatusCode, ApiError> {
+    // Requests that already hold the game can still finish, the game is dropped afterwards
+    server.games.lock().await.remove(&id)
+        .ok_or_else(|| ApiError::not_found(format!("Game {} doesn't exist", id)))?;
+    return Ok(StatusCode::NO_CONTENT);
+}
+
+async fn get_game<GS: GameState + SantoriniEval + 'static>(
+    State(server): State<Arc<PlayServer<GS>>>,
+    Path(id): Path<u64>,
+) -> Result<Json<GameView>, ApiError> {
+    let game = server.get_game(id).await?;
+    let view = game.lock().await.get_view()?;
+    return Ok(Json(view));
+}
+
+async fn get_legal_moves<GS: GameState + SantoriniEval + 'static>(
+    State(server): State<Arc<PlayServer<GS>>>,
+    Path(id): Path<u64>,
+) -> Result<Json<Vec<LegalMoveView>>, ApiError> {
+    let game = server.get_game(id).await?;
+    let game = game.lock().await;
+    let current_state = game.context.get_current_state();
+
+    let mut legal_moves = Vec::new();
+    if game.result.is_none() {
+        for child_state in current_state.get_children_states() {
+            legal_moves.push(LegalMoveView {
+                notation: get_move_notation(current_state, &child_state)?,
+                position: child_state.to_generic_game_state().to_position_string(),
+            });
+        }
+    }
+    return Ok(Json(legal_moves));
+}
+
+async fn post_move<GS: GameState + SantoriniEval + 'static>(
+    State(server): State<Arc<PlayServer<GS>>>,
+    Path(id): Path<u64>,
+    Json(request): Json<MoveRequest>,
+) -> Result<Json<GameView>, ApiError> {
+    let game = server.get_game(id).await?;
+    let mut game = game.lock().await;
+    game.play_human_move(&request.notation)?;
+    return Ok(Json(game.get_view()?));
+}
+
+async fn post_bot_move<GS: GameState + SantoriniEval + 'static>(
+    State(server): State<Arc<PlayServer<GS>>>,
+    Path(id): Path<u64>,
+) -> Result<Json<GameView>, ApiError> {
+    let game = server.get_game(id).await?;
+    let view = run_blocking(move || {
+        let mut game = game.blocking_lock();
+        game.play_bot_move()?;
+        return game.get_view();
+    }).await?;
+    return Ok(Json(view));
+}
+
+async fn get_analysis<GS: GameState + SantoriniEval + 'static>(
+    State(server): State<Arc<PlayServer<GS>>>,
+    Path(id): Path<u64>,
+    Query(query): Query<AnalysisQuery>,
+) -> Result<Json<AnalysisView>, ApiError> {
+    let depth = query.depth.unwrap_or(DEFAULT_ANALYSIS_DEPTH);
+    if !(1..=MAX_ANALYSIS_DEPTH).contains(&depth) {
+        return Err(anyhow!("The depth must be between 1 and {}", MAX_ANALYSIS_DEPTH).into());
+    }
+    let number_of_moves = query.moves.unwrap_or(usize::MAX);
+    let state = *server.get_game(id).await?.lock().await.context.get_current_state();
+    if state.has_player_a_won() || state.has_player_b_won() {
+        return Err(anyhow!("The game is over").into());
+    }
+
+    let heuristic_function = server.heuristic_function.clone();
+    let analysis = run_blocking(move || {
+        let analyzed_moves = analyze_root_moves(&state, depth, number_of_moves, &|state: &GS| heuristic_function(state));
+
+        let mut moves = Vec::with_capacity(analyzed_moves.len());
+        for analyzed_move in analyzed_moves {
+            let mut principal_variation = Vec::with_capacity(analyzed_move.principal_variation.len());
+            let mut previous_state = state;
+            for pv_state in analyzed_move.principal_variation {
+                principal_variation.push(get_move_notation(&previous_state, &pv_state)?);
+                previous_state = pv_state;
+            }
+            moves.push(AnalyzedMoveView {
+                notation: get_move_notation(&state, &analyzed_move.child_state)?,
+                score: format_score(analyzed_move.score, state.is_player_a_turn()),
+                principal_variation,
+            });
+        }
+        return Ok(AnalysisView { depth, moves });
+    }).await?;
+    return Ok(Json(analysis));
+}
+
+async fn connect_websocket<GS: GameState + SantoriniEval + 'static>(
+    State(server): State<Arc<PlayServer<GS>>>,
+    Path(id): Path<u64>,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response, ApiError> {
+    let game = server.get_game(id).await?;
+    return Ok(upgrade.on_upgrade(move |socket| handle_websocket(socket, game)));
+}
+
+async fn send_message(socket: &mut WebSocket, message: &ServerMessage) -> Result<()> {
+    socket.send(Message::Text(serde_json::to_string(message)?)).await?;
+    return Ok(());
+}
+
+// Returns the number of moves the bots played
+async fn play_bot_moves<GS: GameState + 'static>(socket: &mut WebSocket, game: &SharedGame<GS>) -> Result<usize> {
+    let mut bot_moves = 0;
+    loop {
+        let game = game.clone();
+        let view = run_blocking(move || {
+            let mut game = game.blocking_lock();
+            if !game.is_bot_to_move() {
+                return Ok(None);
+            }
+            game.play_bot_move()?;
+            return Ok(Some(game.get_view()?));
+        }).await?;
+
+        let Some(view) = view else {
+            return Ok(bot_moves);
+        };
+        bot_moves += 1;
+        let notation = view.moves.last().cloned().unwrap_or_default();
+        send_message(socket, &ServerMessage::BotMove { notation, game: view }).await?;
+    }
+}
+
+async fn handle_client_message<GS: GameState + 'static>(socket: &mut WebSocket, game: &SharedGame<GS>, text: &str) -> Result<()> {
+    match serde_json::from_str::<ClientMessage>(text)? {
+        ClientMessage::Move { notation } => {
+            let view = {
+                let mut game = game.lock().await;
+                game.play_human_move(&notation)?;
+                game.get_view()?
+            };
+            send_message(socket, &ServerMessage::Game { game: view }).await?;
+            play_bot_moves(socket, game).await?;
+        }
+        ClientMessage::BotMoves => {
+            if play_bot_moves(socket, game).await? == 0 {
+                ensure!(game.lock().await.result.is_none(), "The game is over");
+                bail!("A human is to move");
+            }
+        }
+    }
+    return Ok(());
+}
+
+async fn handle_websocket<GS: GameState + 'static>(mut socket: WebSocket, game: SharedGame<GS>) {
+    let view = game.lock().await.get_view();
+    let message = match view {
+        Ok(view) => ServerMessage::Game { game: view },
+        Err(error) => ServerMessage::Error { message: error.to_string() },
+    };
+    if send_message(&mut socket, &message).await.is_err() {
+        return;
+    }
+
+    while let Some(Ok(message)) = socket.recv().await {
+        let Message::Text(text) = message else {
+            continue;
+        };
+        if let Err(error) = handle_client_message(&mut socket, &game, &text).await {
+            // The connection is closed if even the error can't be sent
+            if send_message(&mut socket, &ServerMessage::Error { message: error.to_string() }).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Method, Request, StatusCode};
+    use axum::Router;
+    use futures::{SinkExt, StreamExt};
+    use serde_json::{json, Value};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio_tungstenite::tungstenite::Message;
+    use tower::ServiceExt;
+    use crate::game_state::game_state_4x4_binary_3bit::GameState4x4Binary3Bit;
+    use crate::game_state::SantoriniEval;
+    use crate::play_server::PlayServer;
+    use crate::strategy::random_strategy::RandomStrategy;
+
+    fn new_server(max_games: usize) -> PlayServer<GameState4x4Binary3Bit> {
+        return PlayServer::<GameState4x4Binary3Bit>::new(|state| state.get_child_evaluation())
+            .with_bot("random", RandomStrategy::<GameState4x4Binary3Bit>::new)
+            .with_max_games(max_games);
+    }
+
+    fn new_router(max_games: usize) -> Router {
+        return new_server(max_games).into_router();
+    }
+
+    async fn send(router: &Router, method: Method, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+        let request = Request::builder().method(method).uri(uri).header("content-type", "application/json");
+        let request = match body {
+            Some(body) => request.body(Body::from(body.to_string())).unwrap(),
+            None => request.body(Body::empty()).unwrap(),
+        };
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = if bytes.is_empty() { Value::Null } else { serde_json::from_slice(&bytes).unwrap() };
+        return (status, body);
+    }
+
+    #[tokio::test]
+    async fn test_human_and_bot_moves_are_played_through_the_router() {
+        let router = new_router(10);
+        let (status, game) = send(&router, Method::POST, "/games", Some(json!({"player_b": "random"}))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(game["player_to_move"], "A");
+        assert_eq!(game["player_b"], "random");
+        let id = game["id"].as_u64().unwrap();
+
+        let (status, legal_moves) = send(&router, Method::GET, &format!("/games/{}/legal-moves", id), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let notations: Vec<Value> = legal_moves.as_array().unwrap().iter().map(|legal_move| legal_move["move"].clone()).collect();
+        assert_eq!(&notations, game["legal_moves"].as_array().unwrap());
+
+        let human_move = notations[0].clone();
+        let (status, game) = send(&router, Method::POST, &format!("/games/{}/moves", id), Some(json!({"move": human_move}))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(game["moves"], json!([human_move]));
+        assert_eq!(game["player_to_move"], "B");
+
+        // Only the bot can move now
+        let (status, _) = send(&router, Method::POST, &format!("/games/{}/moves", id), Some(json!({"move": human_move}))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, game) = send(&router, Method::POST, &format!("/games/{}/bot-move", id), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(game["moves"].as_array().unwrap().len(), 2);
+        assert_eq!(game["player_to_move"], "A");
+        let (status, _) = send(&router, Method::POST, &format!("/games/{}/bot-move", id), None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, analysis) = send(&router, Method::GET, &format!("/games/{}/analysis?depth=2&moves=3", id), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(analysis["moves"].as_array().unwrap().len(), 3.min(game["legal_moves"].as_array().unwrap().len()));
+    }
+
+    #[tokio::test]
+    async fn test_deleted_and_evicted_games_are_not_found() {
+        let router = new_router(2);
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            let (status, game) = send(&router, Method::POST, "/games", Some(json!({}))).await;
+            assert_eq!(status, StatusCode::OK);
+            ids.push(game["id"].as_u64().unwrap());
+        }
+
+        // The oldest game is removed once there are more games than allowed
+        assert_eq!(send(&router, Method::GET, &format!("/games/{}", ids[0]), None).await.0, StatusCode::NOT_FOUND);
+        assert_eq!(send(&router, Method::GET, &format!("/games/{}", ids[1]), None).await.0, StatusCode::OK);
+
+        assert_eq!(send(&router, Method::DELETE, &format!("/games/{}", ids[2]), None).await.0, StatusCode::NO_CONTENT);
+        assert_eq!(send(&router, Method::GET, &format!("/games/{}", ids[2]), None).await.0, StatusCode::NOT_FOUND);
+        assert_eq!(send(&router, Method::DELETE, &format!("/games/{}", ids[2]), None).await.0, StatusCode::NOT_FOUND);
+    }
+
+    // Minimal HTTP/1.1 request, the server closes the connection after the response
+    async fn post_over_tcp(address: &str, uri: &str, body: Value) -> Value {
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        let body = body.to_string();
+        let request = format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            uri, address, body.len(), body
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        let (_, response_body) = response.split_once("\r\n\r\n").unwrap();
+        return serde_json::from_str(response_body).unwrap();
+    }
+
+    async fn receive<S: StreamExt<Item=Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin>(socket: &mut S) -> Value {
+        let Message::Text(text) = socket.next().await.unwrap().unwrap() else {
+            panic!("Expected a text message");
+        };
+        return serde_json::from_str(&text).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_websocket_sends_the_game_bot_replies_and_errors() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let server = tokio::spawn(new_server(10).serve_with_listener(listener));
+
+        let game = post_over_tcp(&address, "/games", json!({"player_b": "random"})).await;
+        let id = game["id"].as_u64().unwrap();
+        let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}/games/{}/ws", address, id)).await.unwrap();
+
+        let message = receive(&mut socket).await;
+        assert_eq!(message["type"], "game");
+        assert_eq!(message["game"]["player_to_move"], "A");
+        let human_move = message["game"]["legal_moves"][0].clone();
+
+        // The human move is confirmed, and the bot replies immediately
+        socket.send(Message::Text(json!({"type": "move", "move": human_move}).to_string())).await.unwrap();
+        let message = receive(&mut socket).await;
+        assert_eq!(message["type"], "game");
+        assert_eq!(message["game"]["moves"], json!([human_move]));
+        let message = receive(&mut socket).await;
+        assert_eq!(message["type"], "bot_move");
+        assert_eq!(message["game"]["moves"].as_array().unwrap().len(), 2);
+        assert_eq!(message["game"]["moves"][1], message["move"]);
+        assert_eq!(message["game"]["player_to_move"], "A");
+
+        // The human is to move, so the bot can't move, and invalid moves are rejected without closing the connection
+        socket.send(Message::Text(json!({"type": "bot_moves"}).to_string())).await.unwrap();
+        let message = receive(&mut socket).await;
+        assert_eq!(message["type"], "error");
+        assert_eq!(message["message"], "A human is to move");
+        socket.send(Message::Text(json!({"type": "move", "move": "z9"}).to_string())).await.unwrap();
+        assert_eq!(receive(&mut socket).await["type"], "error");
+        socket.send(Message::Text("not json".to_string())).await.unwrap();
+        assert_eq!(receive(&mut socket).await["type"], "error");
+
+        server.abort();
+    }
+}
@@ -1,16 +1,20 @@
 use std::env;
+use std::ops::RangeInclusive;
 use std::sync::{Arc, Mutex};
-use crate::game_state::{ContinuousBlockId, GameState, SimplifiedState};
-use anyhow::Result;
+use crate::game_state::{get_initial_setup_state, ContinuousBlockId, GameState, SimplifiedState};
+use anyhow::{Context, Result};
 use chrono::Local;
 use num_format::ToFormattedString;
 use tokio::fs::File;
 use tokio::io::{AsyncReadExt, AsyncWriteExt, BufWriter};
 use crate::game_state::game_state_4x4_binary_3bit::GameState4x4Binary3Bit;
+use crate::generic_game_state::generic_santorini_game_state::GenericSantoriniGameState;
+use crate::generic_game_state::GenericGameState;
+use crate::minimax::forced_win::shortest_forced_win_moves;
 use crate::precompute_state_winner::bit_vector::BitVector;
 use crate::precompute_state_winner::bit_writer::BitWriter;
 
-mod bit_vector;
+pub mod bit_vector;
 mod bit_writer;
 mod asset_valid_bit_count;
 
@@ -231,90 +235,177 @@ pub async fn presolve_state_winner<
 }
 
 
-// Checks if a forced win for player B can be found in the current state with the given remaining moves
-fn internal_find_forced_win(state: &GameState4x4Binary3Bit, max_moves: usize, player_a_turn: bool, bit_vectors: &Vec<BitVector<1>>, child_bit_vector_index: usize, reusable_child_states: &mut Vec<GameState4x4Binary3Bit>) -> bool {
-    state.get_children_states_reuse_vec(reusable_child_states);
+pub struct WinnerTablebase<GS: GameState + SimplifiedState + ContinuousBlockId> {
+    block_counts: RangeInclusive<isize>,
+    bit_vectors: Vec<BitVector<1>>,
+    _marker: std::marker::PhantomData<GS>,
+}
 
-    if reusable_child_states.is_empty() {
-        return player_a_turn;
+impl<GS: GameState + SimplifiedState + ContinuousBlockId> WinnerTablebase<GS> {
+    // Loads the presolved winners (without draws) for the given block counts from the WINNER_DATA_FOLDER
+    pub async fn load(block_counts: RangeInclusive<isize>) -> Result<Self> {
+        let mut tablebase = WinnerTablebase {
+            block_counts: *block_counts.start()..=(*block_counts.start() - 1),
+            bit_vectors: Vec::with_capacity(block_counts.clone().count()),
+            _marker: std::marker::PhantomData,
+        };
+        tablebase.extend_to(*block_counts.end()).await?;
+        return Ok(tablebase);
     }
 
-    if max_moves == 0 {
-        return false;
+    // Creates a tablebase from bit vectors that are already in memory, one for every block count in block_counts
+    pub fn from_bit_vectors(block_counts: RangeInclusive<isize>, bit_vectors: Vec<BitVector<1>>) -> Self {
+        assert_eq!(block_counts.clone().count(), bit_vectors.len(), "There has to be one bit vector for every block count");
+        return WinnerTablebase {
+            block_counts,
+            bit_vectors,
+            _marker: std::marker::PhantomData,
+        };
     }
 
-    let mut reusable_vec_for_child_states = Vec::with_capacity(32);
+    // Loads the block counts after the already loaded ones up to max_block_count, so searches can load block counts as they need them
+    pub async fn extend_to(&mut self, max_block_count: isize) -> Result<()> {
+        let data_folder_path = env::var("WINNER_DATA_FOLDER").context("WINNER_DATA_FOLDER must be set")?;
 
-    let child_bit_vector = bit_vectors.get(child_bit_vector_index).unwrap();
+        for block_count in (*self.block_counts.end() + 1)..=max_block_count {
+            let block_id_count = GS::get_continuous_block_id_count(block_count);
+            let expected_length = (block_id_count as usize + 7) / 8;
+            let bit_vector = BitVector::from_file_with_expected_length(&format!("{}/block{}_{}-{}.bin", data_folder_path, block_count, 0, block_id_count - 1), expected_length).await?;
+            self.bit_vectors.push(bit_vector);
+            self.block_counts = *self.block_counts.start()..=block_count;
+        }
+        return Ok(());
+    }
 
-    if player_a_turn {
-        for child_state in reusable_child_states {
-            if child_state.has_player_a_won() {
-                return false;
-            }
-            let simplified_child_state = child_state.get_simplified_state();
-            let child_block_id = simplified_child_state.get_continuous_block_id();
-            if child_bit_vector.get(child_block_id as usize) == PresolveResult::PlayerAWinning as u8 {
-                return false;
-            }
-            if !internal_find_forced_win(&simplified_child_state, max_moves - 1, false, bit_vectors, child_bit_vector_index + 1, &mut reusable_vec_for_child_states) {
-                return false;
-            }
+    // Returns Some(true) if player A wins, Some(false) if player B wins, or None if the block count of the state is not loaded
+    pub fn get_winner(&self, state: &GS) -> Option<bool> {
+        if state.has_player_a_won() {
+            return Some(true);
+        } else if state.has_player_b_won() {
+            return Some(false);
         }
-        return true;
-    } else {
-        for child_state in reusable_child_states {
-            if child_state.has_player_b_won() {
-                return true;
-            }
-            let simplified_child_state = child_state.get_simplified_state();
-            let child_block_id = simplified_child_state.get_continuous_block_id();
-            if child_bit_vector.get(child_block_id as usize) == PresolveResult::PlayerAWinning as u8 {
-                continue;
-            }
-            if internal_find_forced_win(&simplified_child_state, max_moves - 1, true, bit_vectors, child_bit_vector_index + 1, &mut reusable_vec_for_child_states) {
-                return true;
-            }
+
+        let block_count = state.get_block_count() as isize;
+        if !self.block_counts.contains(&block_count) {
+            return None;
         }
-        return false;
+
+        let bit_vector = &self.bit_vectors[(block_count - self.block_counts.start()) as usize];
+        let continuous_block_id = state.get_simplified_state().get_continuous_block_id();
+        return Some(bit_vector.get(continuous_block_id as usize) == PresolveResult::PlayerAWinning as u8);
     }
 }
 
 
 // This function is specific to the 4x4 binary 3-bit game state
-// It finds the shorted forced win for player B
+// It finds the shortest forced win for player B from the initial state
 pub async fn find_shortest_forced_win() -> Result<usize> {
     type GS = GameState4x4Binary3Bit;
 
-    let data_folder_path = env::var("WINNER_DATA_FOLDER").expect("WINNER_DATA_FOLDER must be set");
-
-    let mut bit_vectors = Vec::new();
+    let initial_block_count = -2;
+    let initial_state = GS::from_continuous_block_id(initial_block_count, 0);
 
-    let initial_block_id = -2;
+    // Only the block counts that can be reached within the move limit are loaded, so the tablebase grows with the limit
+    let mut tablebase = WinnerTablebase::<GS>::load(initial_block_count..=initial_block_count).await?;
+    for move_limit in (1..=30).map(|x| x * 2) {
+        println!("Checking move limit {}", move_limit);
+        tablebase.extend_to(initial_block_count + move_limit as isize).await?;
 
-    let initial_block_id_count = GS::get_continuous_block_id_count(initial_block_id);
-    let expected_length = (initial_block_id_count as usize + 7) / 8;
-    let bit_vector = BitVector::from_file_with_expected_length(&format!("{}/block{}_{}-{}.bin", data_folder_path, initial_block_id, 0, initial_block_id_count - 1), expected_length).await?;
-    bit_vectors.push(bit_vector);
+        if let Some(moves_to_win) = shortest_forced_win_moves(&initial_state, false, move_limit, &|state: &GS| tablebase.get_winner(state)) {
+            println!("Found forced win for move limit {}", moves_to_win);
+            return Ok(moves_to_win);
+        }
+    }
+    return Err(anyhow::anyhow!("No forced win found"));
+}
 
 
-    let initial_state = GS::from_continuous_block_id(initial_block_id, 0);
+pub struct WorkerPlacementValue {
+    // Tile of player A's worker for every placement in the symmetry class
+    pub worker_tiles: Vec<u8>,
+    pub player_a_wins: bool,
+    // Number of worker placements of player B, and how many of them still lose against player A
+    pub replies: usize,
+    pub losing_replies: usize,
+    // Shortest forced win of the winning player, None if it is longer than the searched amount of moves
+    pub moves_to_win: Option<usize>,
+}
 
-    for move_limit in (1..=30).map(|x| x * 2) {
-        println!("Checking move limit {}", move_limit);
-        for block_id in (initial_block_id + move_limit as isize - 1)..=(initial_block_id + move_limit as isize) {
-            let block_id_count = GS::get_continuous_block_id_count(block_id);
-            let expected_length = (block_id_count as usize + 7) / 8;
-            let bit_vector = BitVector::from_file_with_expected_length(&format!("{}/block{}_{}-{}.bin", data_folder_path, block_id, 0, block_id_count - 1), expected_length).await?;
-            bit_vectors.push(bit_vector);
+/*
+Evaluates every worker placement of player A in the 4x4 game, grouped by symmetry classes (corner, edge and center tiles).
+The tablebase has to contain the block counts from -1 up to (max_moves - 1), otherwise the winners can't be determined.
+ */
+pub fn analyze_worker_placements<
+    GS: GameState<GenericGameState=GenericSantoriniGameState<4, 4, 1>> + SimplifiedState + ContinuousBlockId
+>(tablebase: &WinnerTablebase<GS>, max_moves: usize) -> Result<Vec<WorkerPlacementValue>> {
+    let initial_state = get_initial_setup_state::<GS>();
+    let known_winner = |state: &GS| tablebase.get_winner(state);
+
+    let mut placements_by_class: Vec<(String, Vec<GS>)> = Vec::new();
+    for placement in initial_state.get_children_states() {
+        let canonical_position_string = placement.to_generic_game_state().to_canonical_position_string();
+        match placements_by_class.iter_mut().find(|(class, _)| *class == canonical_position_string) {
+            Some((_, placements)) => placements.push(placement),
+            None => placements_by_class.push((canonical_position_string, vec![placement])),
         }
+    }
 
-        if internal_find_forced_win(&initial_state, move_limit, true, &bit_vectors, 1, &mut Vec::with_capacity(32)) {
-            println!("Found forced win for move limit {}", move_limit);
-            return Ok(move_limit);
+    let mut placement_values = Vec::with_capacity(placements_by_class.len());
+    for (_, placements) in placements_by_class {
+        let representative = placements[0];
+        let player_a_wins = tablebase.get_winner(&representative)
+            .ok_or_else(|| anyhow::anyhow!("Block count -1 is not contained in the tablebase"))?;
+
+        let replies = representative.get_children_states();
+        let mut losing_replies = 0;
+        for reply in &replies {
+            let reply_winner = tablebase.get_winner(reply)
+                .ok_or_else(|| anyhow::anyhow!("Block count 0 is not contained in the tablebase"))?;
+            if reply_winner {
+                losing_replies += 1;
+            }
         }
-        println!("No forced win found for move limit {}", move_limit);
+
+        let mut worker_tiles: Vec<u8> = placements.iter()
+            .map(|placement| placement.to_generic_game_state().player_a_workers.expect("Player A placed their worker")[0])
+            .collect();
+        worker_tiles.sort_unstable();
+
+        placement_values.push(WorkerPlacementValue {
+            worker_tiles,
+            player_a_wins,
+            replies: replies.len(),
+            losing_replies,
+            moves_to_win: shortest_forced_win_moves(&representative, player_a_wins, max_moves, &known_winner),
+        });
     }
 
-    return Err(anyhow::anyhow!("No forced win found"));
-}
\ No newline at end of file
+    return Ok(placement_values);
+}
+
+pub fn format_worker_placement_report(placement_values: &[WorkerPlacementValue]) -> String {
+    let mut report = "Worker tiles | Winner | Losing replies of B | Moves to win\n".to_string();
+    for placement_value in placement_values {
+        let worker_tiles = placement_value.worker_tiles.iter().map(|tile| tile.to_string()).collect::<Vec<String>>().join(",");
+        let moves_to_win = placement_value.moves_to_win.map(|moves| moves.to_string()).unwrap_or("-".to_string());
+        report.push_str(&format!(
+            "{} | {} | {}/{} | {}\n",
+            worker_tiles,
+            if placement_value.player_a_wins { "A" } else { "B" },
+            placement_value.losing_replies,
+            placement_value.replies,
+            moves_to_win
+        ));
+    }
+    return report;
+}
+
+// This function is specific to the 4x4 binary 3-bit game state
+pub async fn print_worker_placement_report(max_moves: usize) -> Result<()> {
+    type GS = GameState4x4Binary3Bit;
+
+    let tablebase = WinnerTablebase::<GS>::load(-1..=(max_moves as isize - 1)).await?;
+    let placement_values = analyze_worker_placements(&tablebase, max_moves)?;
+    print!("{}", format_worker_placement_report(&placement_values));
+    return Ok(());
+}
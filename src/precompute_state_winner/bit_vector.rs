@@ -26,6 +26,13 @@ impl<const BITS_PER_ENTRY: usize> BitVector<BITS_PER_ENTRY> {
         });
     }
 
+    // Entries are stored like in the presolved files, starting at the lowest bits of the first byte
+    pub fn from_data(data: Vec<u8>) -> Self {
+        return BitVector {
+            data
+        };
+    }
+
     pub fn new_empty() -> Self {
         return BitVector {
             data: Vec::new()
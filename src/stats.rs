@@ -81,6 +81,10 @@ pub mod benchmark_minimax_simple;
 pub mod benchmark_minimax_alpha_beta;
 pub mod benchmark_minimax_sorted;
 pub mod benchmark_minimax_cached;
+pub mod benchmark_minimax_null_window;
+pub mod move_ordering_stats;
 pub mod minimax_solve_stats;
 pub mod presolve_analysis;
-pub mod benchmark_minimax_infinite;
\ No newline at end of file
+pub mod benchmark_minimax_infinite;
+pub mod benchmark_proof_number_search;
+pub mod search_test_suite;
\ No newline at end of file
@@ -1,21 +1,23 @@
 use std::env;
 use serde::{Deserialize, Serialize};
 use plotters::prelude::*;
-use crate::game_state::GameState;
+use crate::game_state::{GameState, SantoriniEval};
 use crate::minimax::alpha_beta_minimax;
 use crate::stats::benchmark_minimax_simple::BenchmarkMinimaxSimple;
 use crate::stats::StatGenerator;
 use crate::stats::utils::draw_minimax_benchmark::{AverageMinimaxMeasurement, draw_minimax_benchmark, MinimaxBenchmarkData, MinimaxMeasurement};
 use crate::stats::utils::gather_minimax_benchmark::gather_minimax_benchmark;
+use crate::stats::utils::position_sampling::PositionSampling;
 
 #[derive(Clone)]
-pub struct BenchmarkMinimaxAlphaBeta<GS: GameState> {
+pub struct BenchmarkMinimaxAlphaBeta<GS: GameState + SantoriniEval> {
     game_name: String,
     game_state_name: String,
     game_state_short_name: String,
     pub(crate) max_depth_alpha_beta: usize,
     pub(crate) number_alpha_beta_game_states: usize,
     pub(crate) block_count: usize,
+    pub(crate) position_sampling: PositionSampling<GS>,
     pub(crate) simple_benchmark: BenchmarkMinimaxSimple<GS>,
 }
 
@@ -27,7 +29,7 @@ pub struct BenchmarkMinimaxAlphaBetaData {
 }
 
 
-impl<GS: GameState> BenchmarkMinimaxAlphaBeta<GS> {
+impl<GS: GameState + SantoriniEval> BenchmarkMinimaxAlphaBeta<GS> {
     pub fn new(game_name: String, game_state_name: String, game_state_short_name: String, max_depth_alpha_beta: usize, number_alpha_beta_game_states: usize, block_count: usize, simple_benchmark: BenchmarkMinimaxSimple<GS>) -> Self {
         assert_eq!(simple_benchmark.number_simple_game_states, number_alpha_beta_game_states);
         assert_eq!(simple_benchmark.block_count, block_count);
@@ -38,23 +40,32 @@ impl<GS: GameState> BenchmarkMinimaxAlphaBeta<GS> {
             max_depth_alpha_beta,
             number_alpha_beta_game_states,
             block_count,
+            position_sampling: simple_benchmark.position_sampling.clone(),
             simple_benchmark,
         };
     }
+
+    // Also used for the benchmark this one is compared to
+    pub fn with_position_sampling(mut self, position_sampling: PositionSampling<GS>) -> Self {
+        self.position_sampling = position_sampling.clone();
+        self.simple_benchmark = self.simple_benchmark.with_position_sampling(position_sampling);
+        return self;
+    }
 }
 
 
-impl<GS: GameState + 'static> StatGenerator for BenchmarkMinimaxAlphaBeta<GS> {
+impl<GS: GameState + SantoriniEval + 'static> StatGenerator for BenchmarkMinimaxAlphaBeta<GS> {
     type DataType = BenchmarkMinimaxAlphaBetaData;
 
     fn get_stat_name(&self) -> String {
-        return format!("minimax_alpha_beta_{}", self.game_state_short_name);
+        return format!("minimax_alpha_beta_{}{}", self.game_state_short_name, self.position_sampling.get_name_suffix());
     }
 
     async fn gather_data(&self) -> anyhow::Result<Self::DataType> {
         let measurements = gather_minimax_benchmark(
             self.number_alpha_beta_game_states,
             self.block_count,
+            &self.position_sampling,
             self.max_depth_alpha_beta,
             alpha_beta_minimax::<GS>
         ).await?;
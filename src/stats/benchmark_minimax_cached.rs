@@ -7,15 +7,17 @@ use crate::stats::benchmark_minimax_sorted::BenchmarkMinimaxSorted;
 use crate::stats::StatGenerator;
 use crate::stats::utils::draw_minimax_benchmark::{AverageMinimaxMeasurement, draw_minimax_benchmark, MinimaxBenchmarkData, MinimaxMeasurement};
 use crate::stats::utils::gather_minimax_benchmark::gather_minimax_benchmark;
+use crate::stats::utils::position_sampling::PositionSampling;
 
 #[derive(Clone)]
 pub struct BenchmarkMinimaxCached<GS: GameState + SantoriniEval + 'static, const SORTED_MIN_DEPTH_TO_SORT: usize, const MIN_DEPTH_TO_SORT: usize, const MIN_DEPTH_TO_CACHE: usize> {
     game_name: String,
     game_state_name: String,
     game_state_short_name: String,
-    max_depth_cached: usize,
-    number_cached_states: usize,
-    block_count: usize,
+    pub(crate) max_depth_cached: usize,
+    pub(crate) number_cached_states: usize,
+    pub(crate) block_count: usize,
+    pub(crate) position_sampling: PositionSampling<GS>,
     sorted_benchmark: BenchmarkMinimaxSorted<GS, SORTED_MIN_DEPTH_TO_SORT>,
 }
 
@@ -52,9 +54,17 @@ impl<
             max_depth_cached,
             number_cached_states,
             block_count,
+            position_sampling: sorted_benchmark.position_sampling.clone(),
             sorted_benchmark,
         };
     }
+
+    // Also used for the benchmarks this one is compared to
+    pub fn with_position_sampling(mut self, position_sampling: PositionSampling<GS>) -> Self {
+        self.position_sampling = position_sampling.clone();
+        self.sorted_benchmark = self.sorted_benchmark.with_position_sampling(position_sampling);
+        return self;
+    }
 }
 
 
@@ -67,13 +77,14 @@ impl<
     type DataType = BenchmarkMinimaxCachedData;
 
     fn get_stat_name(&self) -> String {
-        return format!("minimax_cached_min{}x{}_{}", MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE, self.game_state_short_name);
+        return format!("minimax_cached_min{}x{}_{}{}", MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE, self.game_state_short_name, self.position_sampling.get_name_suffix());
     }
 
     async fn gather_data(&self) -> anyhow::Result<Self::DataType> {
         let measurements = gather_minimax_benchmark(
             self.number_cached_states,
             self.block_count,
+            &self.position_sampling,
             self.max_depth_cached,
             cached_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>
         ).await?;
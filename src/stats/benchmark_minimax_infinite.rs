@@ -9,10 +9,10 @@ use rand::SeedableRng;
 use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 use crate::game_state::{GameState, SantoriniEval};
-use crate::generic_game_state::GenericGameState;
 use crate::minimax::infinite_depth_minimax;
 use crate::stats::StatGenerator;
 use crate::stats::utils::formatters::ns_formatter;
+use crate::stats::utils::position_sampling::PositionSampling;
 
 #[derive(Clone)]
 pub struct BenchmarkMinimaxInfinite<GS: GameState + SantoriniEval> {
@@ -20,6 +20,7 @@ pub struct BenchmarkMinimaxInfinite<GS: GameState + SantoriniEval> {
     game_state_short_name: String,
     number_of_game_states: usize,
     block_counts: RangeInclusive<usize>,
+    position_sampling: PositionSampling<GS>,
     _phantom: std::marker::PhantomData<GS>,
 }
 
@@ -53,9 +54,15 @@ impl<GS: GameState + SantoriniEval> BenchmarkMinimaxInfinite<GS> {
             game_state_short_name,
             number_of_game_states: number_game_states,
             block_counts,
+            position_sampling: PositionSampling::Uniform,
             _phantom: std::marker::PhantomData,
         };
     }
+
+    pub fn with_position_sampling(mut self, position_sampling: PositionSampling<GS>) -> Self {
+        self.position_sampling = position_sampling;
+        return self;
+    }
 }
 
 
@@ -63,7 +70,7 @@ impl <GS: GameState + SantoriniEval + 'static> StatGenerator for BenchmarkMinima
     type DataType = BenchmarkMinimaxInfiniteData;
 
     fn get_stat_name(&self) -> String {
-        return format!("minimax_infinite_{}_b{}-{}", self.game_state_short_name, self.block_counts.start(), self.block_counts.end());
+        return format!("minimax_infinite_{}_b{}-{}{}", self.game_state_short_name, self.block_counts.start(), self.block_counts.end(), self.position_sampling.get_name_suffix());
     }
 
     async fn gather_data(&self) -> anyhow::Result<Self::DataType> {
@@ -77,8 +84,7 @@ impl <GS: GameState + SantoriniEval + 'static> StatGenerator for BenchmarkMinima
         for block_count in self.block_counts.clone().rev() {
             let mut rng = rand::rngs::StdRng::seed_from_u64(0);
 
-            let random_states: Vec<GS> = (0..self.number_of_game_states)
-                .map(|_| GS::from_generic_game_state(&GenericGameState::generate_random_state_with_blocks_rng(&mut rng, block_count))).collect();
+            let random_states: Vec<GS> = self.position_sampling.sample_positions(&mut rng, block_count, self.number_of_game_states)?;
 
             let states_progress = Arc::new(Mutex::new(0));
 
@@ -0,0 +1,175 @@
+use std::env;
+use serde::{Deserialize, Serialize};
+use plotters::prelude::full_palette::{DEEPPURPLE, ORANGE, RED_600, TEAL};
+use crate::game_state::{GameState, SantoriniEval};
+use crate::minimax::{aspiration_window_minimax, cached_minimax, mtdf_minimax, principal_variation_minimax};
+use crate::stats::benchmark_minimax_cached::BenchmarkMinimaxCached;
+use crate::stats::StatGenerator;
+use crate::stats::utils::draw_minimax_benchmark::{AverageMinimaxMeasurement, draw_minimax_benchmark, MinimaxBenchmarkData, MinimaxMeasurement};
+use crate::stats::utils::gather_minimax_benchmark::gather_minimax_benchmark;
+
+// The cached minimax only returns 0.0 or decisive scores, so a small window is enough
+const ASPIRATION_WINDOW_SIZE: f32 = 1.0;
+
+#[derive(Clone)]
+pub struct BenchmarkMinimaxNullWindow<GS: GameState + SantoriniEval + 'static, const SORTED_MIN_DEPTH_TO_SORT: usize, const MIN_DEPTH_TO_SORT: usize, const MIN_DEPTH_TO_CACHE: usize> {
+    game_name: String,
+    game_state_name: String,
+    game_state_short_name: String,
+    cached_benchmark: BenchmarkMinimaxCached<GS, SORTED_MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BenchmarkMinimaxNullWindowData {
+    pub cpu_name: String,
+    // The cached minimax is measured again instead of using the data of the cached benchmark, which may be recorded with an older cache
+    pub raw_measurements_cached: Vec<Vec<MinimaxMeasurement>>,
+    pub average_measurements_cached: Vec<AverageMinimaxMeasurement>,
+    pub raw_measurements_principal_variation: Vec<Vec<MinimaxMeasurement>>,
+    pub average_measurements_principal_variation: Vec<AverageMinimaxMeasurement>,
+    pub raw_measurements_mtdf: Vec<Vec<MinimaxMeasurement>>,
+    pub average_measurements_mtdf: Vec<AverageMinimaxMeasurement>,
+    pub raw_measurements_aspiration_window: Vec<Vec<MinimaxMeasurement>>,
+    pub average_measurements_aspiration_window: Vec<AverageMinimaxMeasurement>,
+}
+
+
+impl<
+    GS: GameState + SantoriniEval + 'static,
+    const SORTED_MIN_DEPTH_TO_SORT: usize,
+    const MIN_DEPTH_TO_SORT: usize,
+    const MIN_DEPTH_TO_CACHE: usize
+> BenchmarkMinimaxNullWindow<GS, SORTED_MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE> {
+    // Uses the same depth, number of states, block count and position sampling as the cached benchmark, so the results can be compared
+    pub fn new(
+        game_name: String,
+        game_state_name: String,
+        game_state_short_name: String,
+        cached_benchmark: BenchmarkMinimaxCached<GS, SORTED_MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>,
+    ) -> Self {
+        return BenchmarkMinimaxNullWindow {
+            game_name,
+            game_state_name,
+            game_state_short_name,
+            cached_benchmark,
+        };
+    }
+}
+
+
+impl<
+    GS: GameState + SantoriniEval + 'static,
+    const SORTED_MIN_DEPTH_TO_SORT: usize,
+    const MIN_DEPTH_TO_SORT: usize,
+    const MIN_DEPTH_TO_CACHE: usize
+> StatGenerator for BenchmarkMinimaxNullWindow<GS, SORTED_MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE> {
+    type DataType = BenchmarkMinimaxNullWindowData;
+
+    fn get_stat_name(&self) -> String {
+        return format!("minimax_null_window_min{}x{}_{}{}", MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE, self.game_state_short_name, self.cached_benchmark.position_sampling.get_name_suffix());
+    }
+
+    async fn gather_data(&self) -> anyhow::Result<Self::DataType> {
+        let number_of_game_states = self.cached_benchmark.number_cached_states;
+        let block_count = self.cached_benchmark.block_count;
+        let max_depth = self.cached_benchmark.max_depth_cached;
+        let position_sampling = &self.cached_benchmark.position_sampling;
+
+        let cached_measurements = gather_minimax_benchmark(
+            number_of_game_states,
+            block_count,
+            position_sampling,
+            max_depth,
+            cached_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>,
+        ).await?;
+
+        let principal_variation_measurements = gather_minimax_benchmark(
+            number_of_game_states,
+            block_count,
+            position_sampling,
+            max_depth,
+            principal_variation_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>,
+        ).await?;
+
+        let mtdf_measurements = gather_minimax_benchmark(
+            number_of_game_states,
+            block_count,
+            position_sampling,
+            max_depth,
+            mtdf_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>,
+        ).await?;
+
+        let aspiration_window_measurements = gather_minimax_benchmark(
+            number_of_game_states,
+            block_count,
+            position_sampling,
+            max_depth,
+            |state: &GS, depth| aspiration_window_minimax::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>(state, depth, ASPIRATION_WINDOW_SIZE),
+        ).await?;
+
+        let cpu_name = env::var("CPU_NAME").unwrap_or("Unknown".to_string());
+        return Ok(BenchmarkMinimaxNullWindowData {
+            cpu_name,
+            raw_measurements_cached: cached_measurements.0,
+            average_measurements_cached: cached_measurements.1,
+            raw_measurements_principal_variation: principal_variation_measurements.0,
+            average_measurements_principal_variation: principal_variation_measurements.1,
+            raw_measurements_mtdf: mtdf_measurements.0,
+            average_measurements_mtdf: mtdf_measurements.1,
+            raw_measurements_aspiration_window: aspiration_window_measurements.0,
+            average_measurements_aspiration_window: aspiration_window_measurements.1,
+        });
+    }
+
+    fn generate_graph(&self, data: Self::DataType, data_time: String, output_folder_path: &str) -> anyhow::Result<()> {
+        let graph_path = format!("{}/{}.svg", output_folder_path, data_time);
+
+        let cached_data = MinimaxBenchmarkData {
+            label: "Cached Alpha-Beta Minimax".to_string(),
+            cpu_name: data.cpu_name.clone(),
+            color: DEEPPURPLE,
+            draw_execution_time_text: false,
+            draw_game_states_text: false,
+            average_measurements: data.average_measurements_cached,
+            raw_measurements: data.raw_measurements_cached,
+        };
+
+        let principal_variation_data = MinimaxBenchmarkData {
+            label: "Principal Variation Search".to_string(),
+            cpu_name: data.cpu_name.clone(),
+            color: TEAL,
+            draw_execution_time_text: true,
+            draw_game_states_text: true,
+            average_measurements: data.average_measurements_principal_variation,
+            raw_measurements: data.raw_measurements_principal_variation,
+        };
+
+        let mtdf_data = MinimaxBenchmarkData {
+            label: "MTD(f)".to_string(),
+            cpu_name: data.cpu_name.clone(),
+            color: ORANGE,
+            draw_execution_time_text: false,
+            draw_game_states_text: false,
+            average_measurements: data.average_measurements_mtdf,
+            raw_measurements: data.raw_measurements_mtdf,
+        };
+
+        let aspiration_window_data = MinimaxBenchmarkData {
+            label: "Aspiration Windows".to_string(),
+            cpu_name: data.cpu_name,
+            color: RED_600,
+            draw_execution_time_text: false,
+            draw_game_states_text: false,
+            average_measurements: data.average_measurements_aspiration_window,
+            raw_measurements: data.raw_measurements_aspiration_window,
+        };
+
+        return draw_minimax_benchmark(
+            graph_path,
+            format!("Null Window Minimax - {} Benchmark", self.game_name),
+            self.game_state_name.clone(),
+            self.cached_benchmark.block_count,
+            vec![cached_data, principal_variation_data, mtdf_data, aspiration_window_data]
+        );
+    }
+}
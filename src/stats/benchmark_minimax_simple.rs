@@ -1,20 +1,22 @@
 use std::env;
 use serde::{Deserialize, Serialize};
 use plotters::prelude::*;
-use crate::game_state::GameState;
+use crate::game_state::{GameState, SantoriniEval};
 use crate::minimax::simple_minimax;
 use crate::stats::StatGenerator;
 use crate::stats::utils::draw_minimax_benchmark::{AverageMinimaxMeasurement, draw_minimax_benchmark, MinimaxBenchmarkData, MinimaxMeasurement};
 use crate::stats::utils::gather_minimax_benchmark::gather_minimax_benchmark;
+use crate::stats::utils::position_sampling::PositionSampling;
 
 #[derive(Clone)]
-pub struct BenchmarkMinimaxSimple<GS: GameState> {
+pub struct BenchmarkMinimaxSimple<GS: GameState + SantoriniEval> {
     pub(crate) game_name: String,
     pub(crate) game_state_name: String,
     pub(crate) game_state_short_name: String,
     pub(crate) max_depth_simple: usize,
     pub(crate) number_simple_game_states: usize,
     pub(crate) block_count: usize,
+    pub(crate) position_sampling: PositionSampling<GS>,
     _phantom: std::marker::PhantomData<GS>,
 }
 
@@ -26,7 +28,7 @@ pub struct BenchmarkMinimaxSimpleData {
 }
 
 
-impl<GS: GameState> BenchmarkMinimaxSimple<GS> {
+impl<GS: GameState + SantoriniEval> BenchmarkMinimaxSimple<GS> {
     pub fn new(game_name: String, game_state_name: String, game_state_short_name: String, max_depth_simple: usize, number_simple_game_states: usize, block_count: usize) -> Self {
         return BenchmarkMinimaxSimple {
             game_name,
@@ -35,23 +37,30 @@ impl<GS: GameState> BenchmarkMinimaxSimple<GS> {
             max_depth_simple,
             number_simple_game_states,
             block_count,
+            position_sampling: PositionSampling::Uniform,
             _phantom: std::marker::PhantomData,
         };
     }
+
+    pub fn with_position_sampling(mut self, position_sampling: PositionSampling<GS>) -> Self {
+        self.position_sampling = position_sampling;
+        return self;
+    }
 }
 
 
-impl<GS: GameState + 'static> StatGenerator for BenchmarkMinimaxSimple<GS> {
+impl<GS: GameState + SantoriniEval + 'static> StatGenerator for BenchmarkMinimaxSimple<GS> {
     type DataType = BenchmarkMinimaxSimpleData;
 
     fn get_stat_name(&self) -> String {
-        return format!("minimax_simple_{}", self.game_state_short_name);
+        return format!("minimax_simple_{}{}", self.game_state_short_name, self.position_sampling.get_name_suffix());
     }
 
     async fn gather_data(&self) -> anyhow::Result<Self::DataType> {
         let measurements = gather_minimax_benchmark(
             self.number_simple_game_states,
             self.block_count,
+            &self.position_sampling,
             self.max_depth_simple,
             simple_minimax::<GS>
         ).await?;
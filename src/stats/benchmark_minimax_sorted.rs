@@ -8,6 +8,7 @@ use crate::stats::benchmark_minimax_alpha_beta::BenchmarkMinimaxAlphaBeta;
 use crate::stats::StatGenerator;
 use crate::stats::utils::draw_minimax_benchmark::{AverageMinimaxMeasurement, draw_minimax_benchmark, MinimaxBenchmarkData, MinimaxMeasurement};
 use crate::stats::utils::gather_minimax_benchmark::gather_minimax_benchmark;
+use crate::stats::utils::position_sampling::PositionSampling;
 
 #[derive(Clone)]
 pub struct BenchmarkMinimaxSorted<GS: GameState + SantoriniEval + 'static, const MIN_DEPTH_TO_SORT: usize> {
@@ -17,6 +18,7 @@ pub struct BenchmarkMinimaxSorted<GS: GameState + SantoriniEval + 'static, const
     pub(crate) max_depth_sorted: usize,
     pub(crate) number_sorted_states: usize,
     pub(crate) block_count: usize,
+    pub(crate) position_sampling: PositionSampling<GS>,
     alpha_beta_benchmark: BenchmarkMinimaxAlphaBeta<GS>,
 }
 
@@ -48,9 +50,17 @@ impl<GS: GameState + SantoriniEval + 'static, const MIN_DEPTH_TO_SORT: usize> Be
             max_depth_sorted,
             number_sorted_states,
             block_count,
+            position_sampling: alpha_beta_benchmark.position_sampling.clone(),
             alpha_beta_benchmark,
         };
     }
+
+    // Also used for the benchmarks this one is compared to
+    pub fn with_position_sampling(mut self, position_sampling: PositionSampling<GS>) -> Self {
+        self.position_sampling = position_sampling.clone();
+        self.alpha_beta_benchmark = self.alpha_beta_benchmark.with_position_sampling(position_sampling);
+        return self;
+    }
 }
 
 
@@ -58,13 +68,14 @@ impl<GS: GameState + SantoriniEval + 'static, const MIN_DEPTH_TO_SORT: usize> St
     type DataType = BenchmarkMinimaxSortedData;
 
     fn get_stat_name(&self) -> String {
-        return format!("minimax_sorted_min{}_{}", MIN_DEPTH_TO_SORT, self.game_state_short_name);
+        return format!("minimax_sorted_min{}_{}{}", MIN_DEPTH_TO_SORT, self.game_state_short_name, self.position_sampling.get_name_suffix());
     }
 
     async fn gather_data(&self) -> anyhow::Result<Self::DataType> {
         let measurements = gather_minimax_benchmark(
             self.number_sorted_states,
             self.block_count,
+            &self.position_sampling,
             self.max_depth_sorted,
             alpha_beta_sorted_minimax::<GS, MIN_DEPTH_TO_SORT>
         ).await?;
@@ -0,0 +1,251 @@
+use std::env;
+use std::ops::RangeInclusive;
+use std::time::{Duration, Instant};
+use fnv::FnvHashMap;
+use plotters::prelude::*;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use crate::game_state::{GameState, SantoriniEval};
+use crate::minimax::infinite_depth_minimax;
+use crate::minimax::proof_number_search::{proof_number_search, ProofNumberTable};
+use crate::stats::StatGenerator;
+use crate::stats::utils::formatters::ns_formatter;
+use crate::stats::utils::position_sampling::PositionSampling;
+
+#[derive(Clone)]
+pub struct BenchmarkProofNumberSearch<GS: GameState + SantoriniEval> {
+    game_state_name: String,
+    game_state_short_name: String,
+    number_of_game_states: usize,
+    block_counts: RangeInclusive<usize>,
+    // infinite_depth_minimax is only run for these block counts, as it doesn't finish in reasonable time for lower block counts
+    minimax_block_counts: RangeInclusive<usize>,
+    table_bucket_count: usize,
+    position_sampling: PositionSampling<GS>,
+    _phantom: std::marker::PhantomData<GS>,
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone)]
+pub struct ProofNumberSearchMeasurement {
+    pub block_count: usize,
+    pub proof_number_execution_time: Duration,
+    pub proof_number_expanded_nodes: usize,
+    pub minimax_execution_time: Option<Duration>,
+    pub player_a_wins: bool,
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone)]
+pub struct AverageProofNumberSearchMeasurement {
+    pub block_count: usize,
+    pub average_proof_number_execution_time: Duration,
+    pub average_proof_number_expanded_nodes: usize,
+    pub average_minimax_execution_time: Option<Duration>,
+    pub player_a_win_rate: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BenchmarkProofNumberSearchData {
+    pub cpu_name: String,
+    pub table_memory_size: usize,
+    pub raw_measurements: Vec<ProofNumberSearchMeasurement>,
+    pub average_measurements: Vec<AverageProofNumberSearchMeasurement>,
+}
+
+impl<GS: GameState + SantoriniEval> BenchmarkProofNumberSearch<GS> {
+    pub fn new(game_state_name: String, game_state_short_name: String, number_game_states: usize, block_counts: RangeInclusive<usize>, minimax_block_counts: RangeInclusive<usize>, table_bucket_count: usize) -> Self {
+        return BenchmarkProofNumberSearch {
+            game_state_name,
+            game_state_short_name,
+            number_of_game_states: number_game_states,
+            block_counts,
+            minimax_block_counts,
+            table_bucket_count,
+            position_sampling: PositionSampling::Uniform,
+            _phantom: std::marker::PhantomData,
+        };
+    }
+
+    pub fn with_position_sampling(mut self, position_sampling: PositionSampling<GS>) -> Self {
+        self.position_sampling = position_sampling;
+        return self;
+    }
+}
+
+
+impl<GS: GameState + SantoriniEval + 'static> StatGenerator for BenchmarkProofNumberSearch<GS> {
+    type DataType = BenchmarkProofNumberSearchData;
+
+    fn get_stat_name(&self) -> String {
+        return format!("proof_number_search_{}_b{}-{}{}", self.game_state_short_name, self.block_counts.start(), self.block_counts.end(), self.position_sampling.get_name_suffix());
+    }
+
+    async fn gather_data(&self) -> anyhow::Result<Self::DataType> {
+        if cfg!(debug_assertions) {
+            return Err(anyhow::anyhow!("Benchmarking should be done in release mode"));
+        }
+
+        let mut table = ProofNumberTable::new(self.table_bucket_count);
+        let mut raw_measurements = Vec::with_capacity(self.number_of_game_states * self.block_counts.clone().count());
+
+        for block_count in self.block_counts.clone().rev() {
+            let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+
+            let random_states: Vec<GS> = self.position_sampling.sample_positions(&mut rng, block_count, self.number_of_game_states)?;
+
+            for (i, state) in random_states.into_iter().enumerate() {
+                table.clear();
+                let start = Instant::now();
+                let (player_a_wins, expanded_nodes) = proof_number_search(state, &mut table)
+                    .ok_or_else(|| anyhow::anyhow!("Proof-number search didn't solve state {}, the table is too small", state))?;
+                let proof_number_execution_time = start.elapsed();
+
+                let minimax_execution_time = if self.minimax_block_counts.contains(&block_count) {
+                    let mut cache = FnvHashMap::default();
+                    let start = Instant::now();
+                    let minimax_player_a_wins = infinite_depth_minimax(state, &mut cache);
+                    let execution_time = start.elapsed();
+
+                    if minimax_player_a_wins != player_a_wins {
+                        return Err(anyhow::anyhow!("Proof-number search and minimax disagree on the winner of state {}", state));
+                    }
+                    Some(execution_time)
+                } else {
+                    None
+                };
+
+                println!("Block Count {} - Progress: {}/{}", block_count, i + 1, self.number_of_game_states);
+
+                raw_measurements.push(ProofNumberSearchMeasurement {
+                    block_count,
+                    proof_number_execution_time,
+                    proof_number_expanded_nodes: expanded_nodes,
+                    minimax_execution_time,
+                    player_a_wins,
+                });
+            }
+        }
+
+        let mut average_measurements = Vec::with_capacity(self.block_counts.clone().count());
+        for block_count in self.block_counts.clone() {
+            let block_measurements: Vec<&ProofNumberSearchMeasurement> = raw_measurements.iter().filter(|m| m.block_count == block_count).collect();
+
+            let total_proof_number_execution_time = block_measurements.iter().map(|m| m.proof_number_execution_time).sum::<Duration>();
+            let average_proof_number_execution_time = total_proof_number_execution_time / self.number_of_game_states as u32;
+
+            let total_proof_number_expanded_nodes = block_measurements.iter().map(|m| m.proof_number_expanded_nodes).sum::<usize>();
+            let average_proof_number_expanded_nodes = total_proof_number_expanded_nodes / self.number_of_game_states;
+
+            let average_minimax_execution_time = if self.minimax_block_counts.contains(&block_count) {
+                let total_minimax_execution_time = block_measurements.iter().filter_map(|m| m.minimax_execution_time).sum::<Duration>();
+                Some(total_minimax_execution_time / self.number_of_game_states as u32)
+            } else {
+                None
+            };
+
+            let player_a_wins = block_measurements.iter().filter(|m| m.player_a_wins).count();
+            let player_a_win_rate = player_a_wins as f64 / self.number_of_game_states as f64;
+
+            average_measurements.push(AverageProofNumberSearchMeasurement {
+                block_count,
+                average_proof_number_execution_time,
+                average_proof_number_expanded_nodes,
+                average_minimax_execution_time,
+                player_a_win_rate,
+            });
+        }
+
+        let cpu_name = env::var("CPU_NAME").unwrap_or("Unknown".to_string());
+        return Ok(BenchmarkProofNumberSearchData {
+            cpu_name,
+            table_memory_size: table.get_memory_size(),
+            raw_measurements,
+            average_measurements,
+        });
+    }
+
+    fn generate_graph(&self, data: Self::DataType, data_time: String, output_folder_path: &str) -> anyhow::Result<()> {
+        let width = 1000;
+        let height = 500;
+
+        let graph_path = format!("{}/{}.svg", output_folder_path, data_time);
+
+        let root = SVGBackend::new(&graph_path, (width, height)).into_drawing_area();
+        root.fill(&WHITE)?;
+
+        let graph_upper_bound = 1e12 as usize;
+        let log_duration_range = (0..graph_upper_bound).log_scale();
+
+        let mut chart = ChartBuilder::on(&root)
+            .margin(10)
+            .set_label_area_size(LabelAreaPosition::Left, 70)
+            .set_label_area_size(LabelAreaPosition::Bottom, 50)
+            .build_cartesian_2d(*self.block_counts.start()..*self.block_counts.end(), log_duration_range)?;
+
+        chart
+            .configure_mesh()
+            .y_labels(13)
+            .y_desc("Execution Time")
+            .x_desc("Block Count")
+            .y_label_formatter(&|y| ns_formatter(y))
+            .axis_desc_style(("sans-serif", 20).into_font())
+            .label_style(("sans-serif", 15).into_font())
+            .draw()?;
+
+
+        chart.draw_series(LineSeries::new(
+            data.average_measurements.iter().map(|m| (m.block_count, m.average_proof_number_execution_time.as_nanos() as usize)),
+            &BLUE,
+        ))?
+            .label("Proof-Number Search")
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLUE));
+
+        chart.draw_series(LineSeries::new(
+            data.average_measurements.iter().filter_map(|m| m.average_minimax_execution_time.map(|time| (m.block_count, time.as_nanos() as usize))),
+            &RED,
+        ))?
+            .label("Infinite Depth Minimax")
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
+
+        chart.configure_series_labels()
+            .position(SeriesLabelPosition::UpperRight)
+            .border_style(&BLACK)
+            .background_style(WHITE.filled())
+            .label_font(("sans-serif", 16).into_font())
+            .draw()?;
+
+
+        // add footnote in bottom right corner
+        let game_state_footnote_text = Text::new(
+            format!("N = {} game states per block count", self.number_of_game_states),
+            (width as i32 - 10, height as i32 - 10),
+            ("Arial", 12).into_font().color(&BLACK).pos(Pos::new(HPos::Right, VPos::Bottom)),
+        );
+        root.draw(&game_state_footnote_text)?;
+        let encoding_footnote_text = Text::new(
+            format!("Encoding: {}", self.game_state_name),
+            (width as i32 - 10, height as i32 - 23),
+            ("Arial", 12).into_font().color(&BLACK).pos(Pos::new(HPos::Right, VPos::Bottom)),
+        );
+        root.draw(&encoding_footnote_text)?;
+
+        // add footnote in bottom left corner
+        let table_footnote_text = Text::new(
+            format!("Transposition table: {} MB", data.table_memory_size / 1_000_000),
+            (10, height as i32 - 10),
+            ("Arial", 12).into_font().color(&BLACK).pos(Pos::new(HPos::Left, VPos::Bottom)),
+        );
+        root.draw(&table_footnote_text)?;
+        let cpu_footnote_text = Text::new(
+            format!("CPU: {}", data.cpu_name),
+            (10, height as i32 - 23),
+            ("Arial", 12).into_font().color(&BLACK).pos(Pos::new(HPos::Left, VPos::Bottom)),
+        );
+        root.draw(&cpu_footnote_text)?;
+
+
+        root.present()?;
+
+        return Ok(());
+    }
+}
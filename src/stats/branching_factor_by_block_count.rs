@@ -1,15 +1,16 @@
 use plotters::prelude::*;
 use serde::{Deserialize, Serialize};
-use crate::game_state::GameState;
-use crate::generic_game_state::GenericGameState;
+use crate::game_state::{GameState, SantoriniEval};
 use crate::stats::StatGenerator;
+use crate::stats::utils::position_sampling::PositionSampling;
 
-pub struct BranchingFactorByBlockCount<GS: GameState> {
+pub struct BranchingFactorByBlockCount<GS: GameState + SantoriniEval> {
     _marker: std::marker::PhantomData<GS>,
     game_name: String,
     max_block_count: usize,
     max_graph_block_count: usize,
     sample_size_per_block_count: usize,
+    position_sampling: PositionSampling<GS>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -18,7 +19,7 @@ pub struct BranchingFactorByBlockCountData {
     average_branching_factor_by_block_count: Vec<f32>,
 }
 
-impl<GS: GameState> BranchingFactorByBlockCount<GS> {
+impl<GS: GameState + SantoriniEval> BranchingFactorByBlockCount<GS> {
 
     pub fn new(game_name: String, max_block_count: usize, max_graph_block_count: usize, sample_size_per_block_count: usize) -> Self {
         return BranchingFactorByBlockCount {
@@ -27,16 +28,22 @@ impl<GS: GameState> BranchingFactorByBlockCount<GS> {
             max_block_count,
             max_graph_block_count,
             sample_size_per_block_count,
+            position_sampling: PositionSampling::Uniform,
         };
     }
 
+    pub fn with_position_sampling(mut self, position_sampling: PositionSampling<GS>) -> Self {
+        self.position_sampling = position_sampling;
+        return self;
+    }
+
 }
 
-impl<GS: GameState> StatGenerator for BranchingFactorByBlockCount<GS> {
+impl<GS: GameState + SantoriniEval> StatGenerator for BranchingFactorByBlockCount<GS> {
     type DataType = BranchingFactorByBlockCountData;
 
     fn get_stat_name(&self) -> String {
-        return format!("branching_factor_by_block_count_{}", self.game_name);
+        return format!("branching_factor_by_block_count_{}{}", self.game_name, self.position_sampling.get_name_suffix());
     }
 
     async fn gather_data(&self) -> anyhow::Result<Self::DataType> {
@@ -53,8 +60,7 @@ impl<GS: GameState> StatGenerator for BranchingFactorByBlockCount<GS> {
 
             let mut summed_branching_factors: usize = 0;
             for _ in 0..self.sample_size_per_block_count {
-                let random_generic_state = GS::GenericGameState::generate_random_state_with_blocks_rng(&mut rng, block_count);
-                let random_state = GS::from_generic_game_state(&random_generic_state);
+                let random_state: GS = self.position_sampling.sample_position(&mut rng, block_count)?;
                 random_state.get_children_states_reuse_vec(&mut children_states);
                 summed_branching_factors += children_states.len();
             }
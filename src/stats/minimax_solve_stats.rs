@@ -8,11 +8,12 @@ use serde::{Deserialize, Serialize};
 use rand::SeedableRng;
 use tokio::sync::Mutex;
 use crate::game_state::{GameState, SantoriniEval};
-use crate::generic_game_state::GenericGameState;
 use crate::minimax::minimax;
 use crate::minimax::minimax_cache::MinimaxCache;
+use crate::minimax::score::{is_decisive, is_player_a_win, is_player_b_win};
 use crate::stats::StatGenerator;
 use crate::stats::utils::formatters::ns_formatter;
+use crate::stats::utils::position_sampling::PositionSampling;
 
 use plotters::prelude::full_palette::{BROWN, GREEN_900, GREY, LIGHTBLUE, ORANGE, PURPLE, YELLOW_600};
 use plotters::style::text_anchor::{HPos, Pos, VPos};
@@ -44,6 +45,7 @@ pub struct MinimaxSolveStats<GS: GameState + SantoriniEval> {
     depths: RangeInclusive<usize>,
     block_counts: RangeInclusive<usize>,
     number_game_states: usize,
+    position_sampling: PositionSampling<GS>,
     _phantom: std::marker::PhantomData<GS>,
 }
 
@@ -72,9 +74,15 @@ impl<GS: GameState + SantoriniEval> MinimaxSolveStats<GS> {
             depths,
             block_counts,
             number_game_states,
+            position_sampling: PositionSampling::Uniform,
             _phantom: std::marker::PhantomData,
         };
     }
+
+    pub fn with_position_sampling(mut self, position_sampling: PositionSampling<GS>) -> Self {
+        self.position_sampling = position_sampling;
+        return self;
+    }
 }
 
 
@@ -82,7 +90,7 @@ impl<GS: GameState + SantoriniEval + 'static> StatGenerator for MinimaxSolveStat
     type DataType = MinimaxSolveData;
 
     fn get_stat_name(&self) -> String {
-        return format!("solved_minimax_{}_d{}-{}_b{}-{}", self.game_state_short_name, self.depths.start(), self.depths.end(), self.block_counts.start(), self.block_counts.end());
+        return format!("solved_minimax_{}_d{}-{}_b{}-{}{}", self.game_state_short_name, self.depths.start(), self.depths.end(), self.block_counts.start(), self.block_counts.end(), self.position_sampling.get_name_suffix());
     }
 
     async fn gather_data(&self) -> anyhow::Result<MinimaxSolveData> {
@@ -99,8 +107,7 @@ impl<GS: GameState + SantoriniEval + 'static> StatGenerator for MinimaxSolveStat
         for block_count in self.block_counts.clone().into_iter() {
             let block_state_progress = Arc::new(Mutex::new(0));
 
-            let random_states: Vec<GS> = (0..self.number_game_states)
-                .map(|_| GS::from_generic_game_state(&GenericGameState::generate_random_state_with_blocks_rng(&mut rng, block_count))).collect();
+            let random_states: Vec<GS> = self.position_sampling.sample_positions(&mut rng, block_count, self.number_game_states)?;
 
             for state in random_states.into_iter() {
                 let block_state_progress = block_state_progress.clone();
@@ -117,7 +124,7 @@ impl<GS: GameState + SantoriniEval + 'static> StatGenerator for MinimaxSolveStat
                             depth,
                             block_count,
                             result,
-                            solved: result.is_infinite(),
+                            solved: is_decisive(result),
                             execution_time: computation_time,
                         });
                     }
@@ -165,8 +172,8 @@ impl<GS: GameState + SantoriniEval + 'static> StatGenerator for MinimaxSolveStat
                 let measurements = measurements_map.get(&(block_count, depth)).unwrap();
                 let total_time_ns = measurements.iter().map(|m| m.execution_time.as_nanos() as usize).sum::<usize>();
                 let total_solved = measurements.iter().filter(|m| m.solved).count();
-                let total_player_a_wins = measurements.iter().filter(|m| m.result == f32::INFINITY).count();
-                let total_player_b_wins = measurements.iter().filter(|m| m.result == f32::NEG_INFINITY).count();
+                let total_player_a_wins = measurements.iter().filter(|m| is_player_a_win(m.result)).count();
+                let total_player_b_wins = measurements.iter().filter(|m| is_player_b_win(m.result)).count();
 
                 let average_time_ns = total_time_ns / self.number_game_states;
                 let average_solved = total_solved as f64 / self.number_game_states as f64;
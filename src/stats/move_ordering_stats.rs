@@ -0,0 +1,181 @@
+use std::env;
+use plotters::prelude::*;
+use plotters::prelude::full_palette::{DEEPPURPLE, GREEN_900, ORANGE};
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use crate::game_state::{GameState, MoveIdentity, SantoriniEval};
+use crate::minimax::move_ordered_minimax_with_ordering;
+use crate::minimax::move_ordering::{MoveOrdering, MoveOrderingConfig};
+use crate::stats::StatGenerator;
+use crate::stats::utils::position_sampling::PositionSampling;
+
+const ORDERING_CONFIGS: [(&str, MoveOrderingConfig); 5] = [
+    ("Static Evaluation", MoveOrderingConfig::STATIC_ONLY),
+    ("Best Move", MoveOrderingConfig { use_best_move: true, use_killer_moves: false, use_history: false }),
+    ("Killer Moves", MoveOrderingConfig { use_best_move: false, use_killer_moves: true, use_history: false }),
+    ("History", MoveOrderingConfig { use_best_move: false, use_killer_moves: false, use_history: true }),
+    ("Combined", MoveOrderingConfig::ALL),
+];
+
+pub struct MoveOrderingStats<GS: GameState + SantoriniEval + MoveIdentity, const MIN_DEPTH_TO_SORT: usize, const MIN_DEPTH_TO_CACHE: usize> {
+    game_state_name: String,
+    game_state_short_name: String,
+    max_depth: usize,
+    block_count: usize,
+    number_game_states: usize,
+    position_sampling: PositionSampling<GS>,
+    _marker: std::marker::PhantomData<GS>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MoveOrderingMeasurement {
+    pub ordering_name: String,
+    pub depth: usize,
+    pub cutoff_states: usize,
+    pub first_move_cutoff_states: usize,
+    pub evaluated_states: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MoveOrderingStatsData {
+    pub cpu_name: String,
+    // Summed over all game states
+    pub measurements: Vec<MoveOrderingMeasurement>,
+}
+
+impl<GS: GameState + SantoriniEval + MoveIdentity, const MIN_DEPTH_TO_SORT: usize, const MIN_DEPTH_TO_CACHE: usize> MoveOrderingStats<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE> {
+    pub fn new(game_state_name: String, game_state_short_name: String, max_depth: usize, block_count: usize, number_game_states: usize) -> Self {
+        return MoveOrderingStats {
+            game_state_name,
+            game_state_short_name,
+            max_depth,
+            block_count,
+            number_game_states,
+            position_sampling: PositionSampling::Uniform,
+            _marker: std::marker::PhantomData,
+        };
+    }
+
+    pub fn with_position_sampling(mut self, position_sampling: PositionSampling<GS>) -> Self {
+        self.position_sampling = position_sampling;
+        return self;
+    }
+}
+
+impl<GS: GameState + SantoriniEval + MoveIdentity + 'static, const MIN_DEPTH_TO_SORT: usize, const MIN_DEPTH_TO_CACHE: usize> StatGenerator for MoveOrderingStats<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE> {
+    type DataType = MoveOrderingStatsData;
+
+    fn get_stat_name(&self) -> String {
+        return format!("move_ordering_min{}x{}_{}_b{}{}", MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE, self.game_state_short_name, self.block_count, self.position_sampling.get_name_suffix());
+    }
+
+    async fn gather_data(&self) -> anyhow::Result<Self::DataType> {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+        let random_states: Vec<GS> = self.position_sampling.sample_positions(&mut rng, self.block_count, self.number_game_states)?;
+
+        let mut tasks = Vec::with_capacity(self.number_game_states);
+        for state in random_states.into_iter() {
+            let max_depth = self.max_depth;
+            tasks.push(tokio::spawn(async move {
+                let mut measurements = Vec::with_capacity(ORDERING_CONFIGS.len() * max_depth);
+                for (ordering_name, config) in ORDERING_CONFIGS.iter() {
+                    // The depths are searched like in iterative deepening, so the ordering can use the best moves of the previous depths
+                    let mut move_ordering = MoveOrdering::new(*config);
+                    for depth in 1..=max_depth {
+                        move_ordering.reset_cutoff_statistics();
+                        let (_, evaluated_states) = move_ordered_minimax_with_ordering::<GS, MIN_DEPTH_TO_SORT, MIN_DEPTH_TO_CACHE>(&state, depth, &mut move_ordering);
+                        measurements.push(MoveOrderingMeasurement {
+                            ordering_name: ordering_name.to_string(),
+                            depth,
+                            cutoff_states: move_ordering.cutoff_states,
+                            first_move_cutoff_states: move_ordering.first_move_cutoff_states,
+                            evaluated_states,
+                        });
+                    }
+                }
+                return measurements;
+            }));
+        }
+
+        let mut summed_measurements: Vec<MoveOrderingMeasurement> = Vec::new();
+        for (i, task) in tasks.into_iter().enumerate() {
+            let measurements = task.await?;
+            if summed_measurements.is_empty() {
+                summed_measurements = measurements;
+            } else {
+                for (summed_measurement, measurement) in summed_measurements.iter_mut().zip(measurements.iter()) {
+                    summed_measurement.cutoff_states += measurement.cutoff_states;
+                    summed_measurement.first_move_cutoff_states += measurement.first_move_cutoff_states;
+                    summed_measurement.evaluated_states += measurement.evaluated_states;
+                }
+            }
+            println!("Progress: {}/{}", i + 1, self.number_game_states);
+        }
+
+        let cpu_name = env::var("CPU_NAME").unwrap_or("Unknown".to_string());
+        return Ok(MoveOrderingStatsData {
+            cpu_name,
+            measurements: summed_measurements,
+        });
+    }
+
+    fn generate_graph(&self, data: Self::DataType, data_time: String, output_folder_path: &str) -> anyhow::Result<()> {
+        let width = 1000;
+        let height = 500;
+
+        let graph_path = format!("{}/{}.svg", output_folder_path, data_time);
+        let root = SVGBackend::new(&graph_path, (width, height)).into_drawing_area();
+        root.fill(&WHITE)?;
+
+        let mut chart = ChartBuilder::on(&root)
+            .margin(10)
+            .set_label_area_size(LabelAreaPosition::Left, 70)
+            .set_label_area_size(LabelAreaPosition::Bottom, 50)
+            .build_cartesian_2d(1..self.max_depth + 1, 0f32..1f32)?;
+
+        chart
+            .configure_mesh()
+            .y_desc("First Move Cutoff Rate")
+            .x_desc("Depth")
+            .y_label_formatter(&|y| format!("{:.0}%", y * 100.0))
+            .axis_desc_style(("sans-serif", 20).into_font())
+            .label_style(("sans-serif", 15).into_font())
+            .draw()?;
+
+        let colors = [BLACK, GREEN_900, ORANGE, RED, DEEPPURPLE];
+        for ((ordering_name, _), color) in ORDERING_CONFIGS.iter().zip(colors) {
+            let ordering_measurements = data.measurements.iter()
+                .filter(|m| m.ordering_name == *ordering_name && m.cutoff_states > 0)
+                .map(|m| (m.depth, m.first_move_cutoff_states as f32 / m.cutoff_states as f32));
+
+            chart.draw_series(LineSeries::new(ordering_measurements, &color))?
+                .label(*ordering_name)
+                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &color));
+        }
+
+        chart.configure_series_labels()
+            .position(SeriesLabelPosition::LowerRight)
+            .border_style(&BLACK)
+            .background_style(WHITE.filled())
+            .label_font(("sans-serif", 16).into_font())
+            .draw()?;
+
+        let game_state_footnote_text = Text::new(
+            format!("N = {} game states with {} blocks", self.number_game_states, self.block_count),
+            (width as i32 - 10, height as i32 - 10),
+            ("Arial", 12).into_font().color(&BLACK).pos(Pos::new(HPos::Right, VPos::Bottom)),
+        );
+        root.draw(&game_state_footnote_text)?;
+        let encoding_footnote_text = Text::new(
+            format!("Encoding: {}", self.game_state_name),
+            (width as i32 - 10, height as i32 - 23),
+            ("Arial", 12).into_font().color(&BLACK).pos(Pos::new(HPos::Right, VPos::Bottom)),
+        );
+        root.draw(&encoding_footnote_text)?;
+
+        root.present()?;
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,372 @@
+use std::fs;
+use std::time::{Duration, Instant};
+use anyhow::{bail, ensure, Context, Result};
+use crate::game_state::{GameState, MoveIdentity, SantoriniEval};
+use crate::generic_game_state::move_notation::find_move;
+use crate::generic_game_state::GenericGameState;
+use crate::minimax::minimax_cache::MinimaxCache;
+use crate::minimax::score::{get_moves_to_win, is_decisive, is_player_a_win, step_back};
+use crate::minimax::{alpha_beta_minimax, alpha_beta_sorted_minimax, aspiration_window_minimax, cached_minimax, cached_minimax_with_extension, minimax_custom_heuristic_with_count, move_ordered_minimax, mtdf_minimax, principal_variation_minimax, simple_minimax};
+use crate::play_game::puzzle_set::Puzzle;
+use crate::strategy::game_context::GameContext;
+use crate::strategy::StatefulStrategy;
+
+const ASPIRATION_WINDOW_SIZE: f32 = 1.0;
+const MAX_EXTENSION_DEPTH: usize = 2;
+
+// Expected score of a search, from the perspective of the player to move
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExpectedValue {
+    Win(usize),
+    Loss(usize),
+    // Neither player can force a win within the depth of the test
+    Undecided,
+}
+
+impl ExpectedValue {
+    pub fn parse(text: &str) -> Result<ExpectedValue> {
+        let parts: Vec<&str> = text.split_whitespace().collect();
+        return match parts.as_slice() {
+            ["win", moves] => Ok(ExpectedValue::Win(moves.parse()?)),
+            ["loss", moves] => Ok(ExpectedValue::Loss(moves.parse()?)),
+            ["undecided"] => Ok(ExpectedValue::Undecided),
+            _ => bail!("Invalid value '{}', expected 'win <moves>', 'loss <moves>' or 'undecided'", text),
+        };
+    }
+
+    pub fn to_value_string(&self) -> String {
+        return match self {
+            ExpectedValue::Win(moves) => format!("win {}", moves),
+            ExpectedValue::Loss(moves) => format!("loss {}", moves),
+            ExpectedValue::Undecided => "undecided".to_string(),
+        };
+    }
+
+    // The score is from player A's perspective
+    pub fn matches(&self, score: f32, player_a_to_move: bool) -> bool {
+        let player_to_move_wins = is_player_a_win(score) == player_a_to_move;
+        return match self {
+            ExpectedValue::Win(moves) => is_decisive(score) && player_to_move_wins && get_moves_to_win(score) == Some(*moves),
+            ExpectedValue::Loss(moves) => is_decisive(score) && !player_to_move_wins && get_moves_to_win(score) == Some(*moves),
+            ExpectedValue::Undecided => !is_decisive(score),
+        };
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchTest {
+    pub id: String,
+    pub position: String,
+    // Depth (plies) of the searches
+    pub depth: usize,
+    // The test is solved if the chosen move is one of these, empty if only the value is tested
+    pub best_moves: Vec<String>,
+    pub value: Option<ExpectedValue>,
+}
+
+impl SearchTest {
+    pub fn get_state<GS: GameState>(&self) -> Result<GS> {
+        let generic_game_state = GS::GenericGameState::from_position_string(&self.position)?;
+        return Ok(GS::from_generic_game_state(&generic_game_state));
+    }
+
+    /*
+    The unique first move of the puzzle is the only best move, the search needs the full depth of the forced win.
+    Searches only detect that a player cannot move when they expand the state, so wins by blocking the opponent need one more ply.
+     */
+    pub fn from_puzzle<GS: GameState>(id: &str, puzzle: &Puzzle) -> Result<SearchTest> {
+        let mut state = puzzle.get_state::<GS>()?;
+        for notation in &puzzle.solution {
+            state = find_move(&state, notation)?.1;
+        }
+        let blocks_opponent = !state.has_player_a_won() && !state.has_player_b_won();
+
+        return Ok(SearchTest {
+            id: id.to_string(),
+            position: puzzle.position.clone(),
+            depth: if blocks_opponent { puzzle.moves_to_win + 1 } else { puzzle.moves_to_win },
+            best_moves: puzzle.solution.iter().take(1).cloned().collect(),
+            value: Some(ExpectedValue::Win(puzzle.moves_to_win)),
+        });
+    }
+}
+
+/*
+A search test suite is a text file with one test per line. A test starts with the position string, followed by operations
+that are separated by semicolons: "id <name>", "depth <plies>", "bm <move> <move> ..." (any of the moves solves the test)
+and "value <win n|loss n|undecided>" (from the perspective of the player to move). The depth and at least one of bm and value are required.
+Empty lines and lines starting with '#' are ignored. Example:
+3410220230003020300104121/5,2/6,24/A; id win_in_1; depth 1; bm a2-a1+a2; value win 1
+ */
+pub fn load_search_test_suite(path: &str) -> Result<Vec<SearchTest>> {
+    let content = fs::read_to_string(path).with_context(|| format!("Could not read search test suite {}", path))?;
+    return parse_search_test_suite(&content);
+}
+
+pub fn parse_search_test_suite(content: &str) -> Result<Vec<SearchTest>> {
+    let mut tests = Vec::new();
+    for (line_index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut operations = line.split(';').map(|operation| operation.trim());
+        let position = operations.next().unwrap().to_string();
+        let mut test = SearchTest { id: format!("line_{}", line_index + 1), position, depth: 0, best_moves: Vec::new(), value: None };
+        let mut has_depth = false;
+
+        for operation in operations.filter(|operation| !operation.is_empty()) {
+            let (key, value) = operation.split_once(' ').map(|(key, value)| (key, value.trim())).unwrap_or((operation, ""));
+            match key {
+                "id" => test.id = value.to_string(),
+                "depth" => {
+                    test.depth = value.parse().with_context(|| format!("Invalid depth in line {} of the search test suite", line_index + 1))?;
+                    has_depth = true;
+                }
+                "bm" => test.best_moves = value.split_whitespace().map(|notation| notation.to_string()).collect(),
+                "value" => test.value = Some(ExpectedValue::parse(value).with_context(|| format!("Invalid value in line {} of the search test suite", line_index + 1))?),
+                _ => bail!("Unknown operation '{}' in line {} of the search test suite", key, line_index + 1),
+            }
+        }
+        ensure!(has_depth, "The test in line {} of the search test suite has no depth", line_index + 1);
+        ensure!(!test.best_moves.is_empty() || test.value.is_some(), "The test in line {} of the search test suite has neither bm nor value", line_index + 1);
+        tests.push(test);
+    }
+    return Ok(tests);
+}
+
+pub fn format_search_test_suite(tests: &[SearchTest]) -> String {
+    let mut content = String::new();
+    for test in tests {
+        content.push_str(&format!("{}; id {}; depth {}", test.position, test.id, test.depth));
+        if !test.best_moves.is_empty() {
+            content.push_str(&format!("; bm {}", test.best_moves.join(" ")));
+        }
+        if let Some(value) = test.value {
+            content.push_str(&format!("; value {}", value.to_value_string()));
+        }
+        content.push('\n');
+    }
+    return content;
+}
+
+pub fn save_search_test_suite(path: &str, tests: &[SearchTest]) -> Result<()> {
+    fs::write(path, format_search_test_suite(tests)).with_context(|| format!("Could not write search test suite {}", path))?;
+    return Ok(());
+}
+
+pub struct SearchTestSuiteResult {
+    pub name: String,
+    // Tests that were run, strategies skip tests without best moves
+    pub tests: usize,
+    pub failed_tests: Vec<String>,
+    pub duration: Duration,
+    // Only searches count their evaluated states
+    pub evaluated_states: Option<usize>,
+}
+
+impl SearchTestSuiteResult {
+    pub fn get_solved_tests(&self) -> usize {
+        return self.tests - self.failed_tests.len();
+    }
+}
+
+pub type SearchFunction<GS> = Box<dyn Fn(&GS, usize) -> (f32, usize)>;
+
+/*
+Every depth-limited search of minimax.rs with the configuration of the benchmarks in main.rs.
+The solvers are left out: infinite_depth_minimax and proof_number_search ignore the depth and only return the winner, not the moves to win,
+and shortest_forced_win only searches for the wins of one player and doesn't count evaluated states.
+ */
+pub fn get_depth_limited_search_functions<GS: GameState + SantoriniEval + MoveIdentity + 'static>() -> Vec<(String, SearchFunction<GS>)> {
+    return vec![
+        ("simple_minimax".to_string(), Box::new(simple_minimax::<GS>)),
+        ("alpha_beta_minimax".to_string(), Box::new(alpha_beta_minimax::<GS>)),
+        ("alpha_beta_sorted_minimax".to_string(), Box::new(alpha_beta_sorted_minimax::<GS, 3>)),
+        ("cached_minimax".to_string(), Box::new(cached_minimax::<GS, 3, 3>)),
+        ("cached_minimax_with_extension".to_string(), Box::new(|state: &GS, depth| cached_minimax_with_extension::<GS, 3, 3>(state, depth, MAX_EXTENSION_DEPTH))),
+        ("principal_variation_minimax".to_string(), Box::new(principal_variation_minimax::<GS, 3, 3>)),
+        ("mtdf_minimax".to_string(), Box::new(mtdf_minimax::<GS, 3, 3>)),
+        ("aspiration_window_minimax".to_string(), Box::new(|state: &GS, depth| aspiration_window_minimax::<GS, 3, 3>(state, depth, ASPIRATION_WINDOW_SIZE))),
+        ("move_ordered_minimax".to_string(), Box::new(move_ordered_minimax::<GS, 3, 3>)),
+        ("minimax_custom_heuristic".to_string(), Box::new(|state: &GS, depth| {
+            minimax_custom_heuristic_with_count(state, depth, 0, &mut MinimaxCache::new(), &|state: &GS| state.get_child_evaluation())
+        })),
+        ("minimax_custom_heuristic_with_extension".to_string(), Box::new(|state: &GS, depth| {
+            minimax_custom_heuristic_with_count(state, depth, MAX_EXTENSION_DEPTH, &mut MinimaxCache::new(), &|state: &GS| state.get_child_evaluation())
+        })),
+    ];
+}
+
+/*
+Runs the search on every test with the depth of the test. The searches only return the score, so the chosen move is the first child
+with the best score of a search with one less depth. Tests with a value additionally search the position itself.
+ */
+pub fn run_search_function<GS: GameState>(name: &str, search_function: &dyn Fn(&GS, usize) -> (f32, usize), tests: &[SearchTest]) -> Result<SearchTestSuiteResult> {
+    let mut failed_tests = Vec::new();
+    let mut evaluated_states = 0;
+    let start = Instant::now();
+
+    for test in tests {
+        let state = test.get_state::<GS>()?;
+        let player_a_to_move = state.is_player_a_turn();
+        let mut solved = true;
+
+        if !test.best_moves.is_empty() {
+            ensure!(test.depth > 0, "Test {} needs a depth of at least 1 to choose a move", test.id);
+            let (best_child, child_evaluated_states) = search_best_child(&state, test.depth, search_function);
+            evaluated_states += child_evaluated_states;
+            solved &= match best_child {
+                Some(child) => is_best_move(test, &state, &child)?,
+                None => false,
+            };
+        }
+
+        if let Some(value) = test.value {
+            let (score, value_evaluated_states) = search_function(&state, test.depth);
+            evaluated_states += value_evaluated_states;
+            solved &= value.matches(score, player_a_to_move);
+        }
+
+        if !solved {
+            failed_tests.push(test.id.clone());
+        }
+    }
+
+    return Ok(SearchTestSuiteResult {
+        name: name.to_string(),
+        tests: tests.len(),
+        failed_tests,
+        duration: start.elapsed(),
+        evaluated_states: Some(evaluated_states),
+    });
+}
+
+// The strategy chooses a move in every test with best moves, the depth of the tests is ignored
+pub fn run_strategy<GS: GameState>(name: &str, strategy: &mut dyn StatefulStrategy<GameState=GS>, tests: &[SearchTest]) -> Result<SearchTestSuiteResult> {
+    let mut failed_tests = Vec::new();
+    let mut run_tests = 0;
+    let start = Instant::now();
+
+    for test in tests.iter().filter(|test| !test.best_moves.is_empty()) {
+        let state = test.get_state::<GS>()?;
+        let is_player_a = state.is_player_a_turn();
+        let context = GameContext::new(state);
+        let possible_next_states = state.get_children_states();
+
+        strategy.new_game(is_player_a, &context)?;
+        let chosen_move = strategy.choose_move_in_context(is_player_a, &context, &possible_next_states)?;
+        ensure!(chosen_move < possible_next_states.len(), "Strategy {} chose an invalid move in test {}", name, test.id);
+
+        run_tests += 1;
+        if !is_best_move(test, &state, &possible_next_states[chosen_move])? {
+            failed_tests.push(test.id.clone());
+        }
+    }
+
+    return Ok(SearchTestSuiteResult {
+        name: name.to_string(),
+        tests: run_tests,
+        failed_tests,
+        duration: start.elapsed(),
+        evaluated_states: None,
+    });
+}
+
+// Returns the first child with the best score and the amount of evaluated states
+fn search_best_child<GS: GameState>(state: &GS, depth: usize, search_function: &dyn Fn(&GS, usize) -> (f32, usize)) -> (Option<GS>, usize) {
+    let player_a_to_move = state.is_player_a_turn();
+    let mut evaluated_states = 0;
+    let mut best_child: Option<(GS, f32)> = None;
+    for child in state.get_children_states() {
+        let (score, child_evaluated_states) = search_function(&child, depth - 1);
+        let score = step_back(score);
+        evaluated_states += child_evaluated_states;
+        if best_child.is_none_or(|(_, best_score)| if player_a_to_move { score > best_score } else { score < best_score }) {
+            best_child = Some((child, score));
+        }
+    }
+    return (best_child.map(|(child, _)| child), evaluated_states);
+}
+
+fn is_best_move<GS: GameState>(test: &SearchTest, state: &GS, child: &GS) -> Result<bool> {
+    for best_move in &test.best_moves {
+        let (_, best_child) = find_move(state, best_move).with_context(|| format!("Invalid best move in test {}", test.id))?;
+        if best_child == *child {
+            return Ok(true);
+        }
+    }
+    return Ok(false);
+}
+
+pub fn format_search_test_suite_report(results: &[SearchTestSuiteResult]) -> String {
+    let mut report = "Search | Solved | Time | Evaluated states | Failed tests\n".to_string();
+    for result in results {
+        let evaluated_states = result.evaluated_states.map(|evaluated_states| evaluated_states.to_string()).unwrap_or("-".to_string());
+        report.push_str(&format!(
+            "{} | {}/{} | {:.3}s | {} | {}\n",
+            result.name,
+            result.get_solved_tests(),
+            result.tests,
+            result.duration.as_secs_f64(),
+            evaluated_states,
+            if result.failed_tests.is_empty() { "-".to_string() } else { result.failed_tests.join(",") },
+        ));
+    }
+    return report;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::game_state_5x5_binary_composite::GameState5x5BinaryComposite;
+    use crate::minimax::score::{step_back, win_score};
+    use crate::stats::search_test_suite::{format_search_test_suite, get_depth_limited_search_functions, parse_search_test_suite, run_search_function, ExpectedValue};
+
+    #[test]
+    fn test_search_functions_solve_the_forced_wins() {
+        let tests = parse_search_test_suite(include_str!("../../test_suites/forced_wins_5x5.txt")).unwrap();
+        let tests: Vec<_> = tests.into_iter().filter(|test| test.depth <= 3).collect();
+        assert!(!tests.is_empty());
+        for (name, search_function) in get_depth_limited_search_functions::<GameState5x5BinaryComposite>() {
+            let result = run_search_function(&name, search_function.as_ref(), &tests).unwrap();
+            assert_eq!(result.tests, tests.len());
+            assert!(result.failed_tests.is_empty(), "{} failed the search tests {:?}", name, result.failed_tests);
+        }
+    }
+
+    #[test]
+    fn test_search_test_suite_is_parsed_to_the_same_tests() {
+        let tests = parse_search_test_suite(include_str!("../../test_suites/forced_wins_5x5.txt")).unwrap();
+        assert_eq!(parse_search_test_suite(&format_search_test_suite(&tests)).unwrap(), tests);
+
+        let tests = parse_search_test_suite("# Comment\n\n3410220230003020300104121/5,2/6,24/A; depth 1; bm a2-a1+a2\n").unwrap();
+        assert_eq!(tests.len(), 1);
+        assert_eq!(tests[0].id, "line_3");
+        assert_eq!(tests[0].value, None);
+    }
+
+    #[test]
+    fn test_invalid_search_test_suites_are_rejected() {
+        let position = "3410220230003020300104121/5,2/6,24/A";
+        assert!(parse_search_test_suite(&format!("{}; bm a2-a1+a2", position)).is_err());
+        assert!(parse_search_test_suite(&format!("{}; depth 1", position)).is_err());
+        assert!(parse_search_test_suite(&format!("{}; depth one; bm a2-a1+a2", position)).is_err());
+        assert!(parse_search_test_suite(&format!("{}; depth 1; value win", position)).is_err());
+        assert!(parse_search_test_suite(&format!("{}; depth 1; best a2-a1+a2", position)).is_err());
+    }
+
+    #[test]
+    fn test_expected_values_are_from_the_perspective_of_the_player_to_move() {
+        let player_a_wins_in_1 = step_back(win_score(true));
+        assert!(ExpectedValue::Win(1).matches(player_a_wins_in_1, true));
+        assert!(ExpectedValue::Loss(1).matches(player_a_wins_in_1, false));
+        assert!(!ExpectedValue::Win(1).matches(player_a_wins_in_1, false));
+        assert!(!ExpectedValue::Win(2).matches(player_a_wins_in_1, true));
+        assert!(!ExpectedValue::Undecided.matches(player_a_wins_in_1, true));
+        assert!(ExpectedValue::Undecided.matches(3.5, false));
+
+        for value in [ExpectedValue::Win(3), ExpectedValue::Loss(2), ExpectedValue::Undecided] {
+            assert_eq!(ExpectedValue::parse(&value.to_value_string()).unwrap(), value);
+        }
+    }
+}
@@ -1,3 +1,5 @@
 pub mod formatters;
 pub mod draw_minimax_benchmark;
 pub mod gather_minimax_benchmark;
+pub mod position_sampling;
+pub mod draw_evaluation_heatmap;
@@ -0,0 +1,224 @@
+use anyhow::Result;
+use plotters::prelude::*;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use crate::game_state::GameState;
+use crate::generic_game_state::generic_santorini_game_state::GenericSantoriniGameState;
+use crate::minimax::score::{get_moves_to_win, is_decisive, is_player_a_win, win_score};
+
+const BAD_COLOR: RGBColor = RGBColor(214, 39, 40);
+const NEUTRAL_COLOR: RGBColor = RGBColor(255, 221, 87);
+const GOOD_COLOR: RGBColor = RGBColor(46, 160, 67);
+
+// Tile of a move that its evaluation is shown on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeatmapTile {
+    // The tile the worker moved to, or the tiles of the placed workers
+    Destination,
+    // The tile that was built on, placements have none
+    Build,
+}
+
+impl HeatmapTile {
+    pub fn get_name(&self) -> &'static str {
+        return match self {
+            HeatmapTile::Destination => "Destination",
+            HeatmapTile::Build => "Build",
+        };
+    }
+}
+
+/*
+Evaluates every legal move of the state and returns the best evaluation of the moves for every tile (None if no move uses the tile).
+The best evaluation is the maximum if it is player A's turn and the minimum otherwise, as all evaluations are from player A's perspective.
+Children in which a player has won get a decisive score, the evaluation function gets all other children, so it can be a heuristic or a search, e.g.:
+let mut cache = MinimaxCache::new();
+get_tile_evaluations(&state, HeatmapTile::Build, |child| minimax_custom_heuristic(child, 3, &mut cache, &heuristic))
+ */
+pub fn get_tile_evaluations<const ROWS: usize, const COLUMNS: usize, const WORKERS_PER_PLAYER: usize, GS, F>(
+    state: &GS,
+    heatmap_tile: HeatmapTile,
+    mut evaluation_function: F,
+) -> Result<Vec<Option<f32>>>
+where
+    GS: GameState<GenericGameState=GenericSantoriniGameState<ROWS, COLUMNS, WORKERS_PER_PLAYER>>,
+    F: FnMut(&GS) -> f32,
+{
+    let generic_state = state.to_generic_game_state();
+    let tile_count = ROWS * COLUMNS;
+    let player_a_turn = generic_state.player_a_turn;
+
+    let mut tile_evaluations: Vec<Option<f32>> = vec![None; tile_count];
+    for child in state.get_children_states() {
+        let move_tiles = generic_state.get_move_tiles(&child.to_generic_game_state())?;
+        let tiles = match heatmap_tile {
+            HeatmapTile::Destination => move_tiles.to.map(|tile| vec![tile]).unwrap_or(move_tiles.placed),
+            HeatmapTile::Build => move_tiles.build.into_iter().collect(),
+        };
+        if tiles.is_empty() {
+            continue;
+        }
+
+        // Heuristics don't have to handle won states
+        let evaluation = if child.has_player_a_won() || child.has_player_b_won() {
+            win_score(child.has_player_a_won())
+        } else {
+            evaluation_function(&child)
+        };
+        for tile in tiles {
+            let is_better = match tile_evaluations[tile] {
+                None => true,
+                Some(best_evaluation) => if player_a_turn { evaluation > best_evaluation } else { evaluation < best_evaluation },
+            };
+            if is_better {
+                tile_evaluations[tile] = Some(evaluation);
+            }
+        }
+    }
+    return Ok(tile_evaluations);
+}
+
+/*
+Draws the state like GenericSantoriniGameState::draw_image and colors every tile with an evaluation from red (worst for the player to move)
+over yellow to green (best for the player to move). Heuristic evaluations are scaled between the lowest and highest evaluation,
+decisive evaluations are always fully red or green. The evaluations (from player A's perspective) are written at the top of the tiles.
+ */
+pub fn draw_evaluation_heatmap<const ROWS: usize, const COLUMNS: usize, const WORKERS_PER_PLAYER: usize>(
+    state: &GenericSantoriniGameState<ROWS, COLUMNS, WORKERS_PER_PLAYER>,
+    tile_evaluations: &[Option<f32>],
+    caption: &str,
+    path: &str,
+) -> Result<()> {
+    let root = SVGBackend::new(path, GenericSantoriniGameState::<ROWS, COLUMNS, WORKERS_PER_PLAYER>::get_image_size()).into_drawing_area();
+    state.draw_on(&root, caption)?;
+
+    // Evaluations from the perspective of the player to move, so higher is always better
+    let relative_evaluations: Vec<Option<f32>> = tile_evaluations.iter()
+        .map(|evaluation| evaluation.map(|evaluation| if state.player_a_turn { evaluation } else { -evaluation }))
+        .collect();
+    let heuristic_evaluations = relative_evaluations.iter().flatten().filter(|evaluation| !is_decisive(**evaluation));
+    let min_evaluation = heuristic_evaluations.clone().fold(f32::INFINITY, |min, evaluation| min.min(*evaluation));
+    let max_evaluation = heuristic_evaluations.fold(f32::NEG_INFINITY, |max, evaluation| max.max(*evaluation));
+
+    for (tile_id, (evaluation, relative_evaluation)) in tile_evaluations.iter().zip(relative_evaluations).enumerate() {
+        let (Some(evaluation), Some(relative_evaluation)) = (evaluation, relative_evaluation) else {
+            continue;
+        };
+
+        let quality = if is_decisive(relative_evaluation) {
+            if relative_evaluation > 0.0 { 1.0 } else { 0.0 }
+        } else if max_evaluation > min_evaluation {
+            (relative_evaluation - min_evaluation) / (max_evaluation - min_evaluation)
+        } else {
+            0.5
+        };
+        let color = if quality < 0.5 {
+            interpolate_color(BAD_COLOR, NEUTRAL_COLOR, quality * 2.0)
+        } else {
+            interpolate_color(NEUTRAL_COLOR, GOOD_COLOR, (quality - 0.5) * 2.0)
+        };
+
+        GenericSantoriniGameState::<ROWS, COLUMNS, WORKERS_PER_PLAYER>::draw_tile_highlight(&root, tile_id, color.mix(0.5).filled())?;
+        let (x, y) = GenericSantoriniGameState::<ROWS, COLUMNS, WORKERS_PER_PLAYER>::get_tile_position(tile_id);
+
+        let text = match get_moves_to_win(*evaluation) {
+            Some(moves_to_win) => format!("{}#{}", if is_player_a_win(*evaluation) { "A" } else { "B" }, moves_to_win),
+            None => format!("{:.2}", evaluation),
+        };
+        root.draw(&Text::new(
+            text,
+            (x + 50, y + 18),
+            ("Arial", 18.0).into_font().color(&BLACK).pos(Pos::new(HPos::Center, VPos::Center)),
+        ))?;
+    }
+
+    root.present()?;
+    return Ok(());
+}
+
+fn interpolate_color(from: RGBColor, to: RGBColor, ratio: f32) -> RGBColor {
+    let interpolate = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * ratio).round() as u8;
+    return RGBColor(interpolate(from.0, to.0), interpolate(from.1, to.1), interpolate(from.2, to.2));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::game_state_4x4_binary_3bit::GameState4x4Binary3Bit;
+    use crate::game_state::game_state_5x5_binary_composite::GameState5x5BinaryComposite;
+    use crate::game_state::{get_initial_setup_state, GameState, SantoriniEval};
+    use crate::generic_game_state::generic_santorini_game_state::GenericSantoriniGameState;
+    use crate::generic_game_state::GenericGameState;
+    use crate::minimax::minimax_cache::MinimaxCache;
+    use crate::minimax::minimax_custom_heuristic;
+    use crate::minimax::score::{is_player_b_win, win_score};
+    use crate::stats::utils::draw_evaluation_heatmap::{get_tile_evaluations, HeatmapTile};
+    use crate::strategy::heuristics::dynamic_heuristic::{dynamic_heuristic, DynamicHeuristicParams};
+
+    // Best evaluation per tile, computed from the differences between the state and its children instead of the move tiles
+    fn get_expected_tile_evaluations<
+        const ROWS: usize, const COLUMNS: usize, const WORKERS_PER_PLAYER: usize,
+        GS: GameState<GenericGameState=GenericSantoriniGameState<ROWS, COLUMNS, WORKERS_PER_PLAYER>> + SantoriniEval
+    >(state: &GS, heatmap_tile: HeatmapTile) -> Vec<Option<f32>> {
+        let generic_state = state.to_generic_game_state();
+        let player_a_turn = generic_state.player_a_turn;
+        let workers = |state: &GenericSantoriniGameState<ROWS, COLUMNS, WORKERS_PER_PLAYER>| {
+            let workers = if player_a_turn { state.player_a_workers } else { state.player_b_workers };
+            return workers.map(|workers| workers.to_vec()).unwrap_or_default();
+        };
+
+        let mut tile_evaluations = vec![None; ROWS * COLUMNS];
+        for child in state.get_children_states() {
+            let generic_child = child.to_generic_game_state();
+            let evaluation = if child.has_player_a_won() || child.has_player_b_won() { win_score(child.has_player_a_won()) } else { child.get_child_evaluation() };
+            for tile in 0..ROWS * COLUMNS {
+                let is_move_tile = match heatmap_tile {
+                    HeatmapTile::Destination => workers(&generic_child).contains(&(tile as u8)) && !workers(&generic_state).contains(&(tile as u8)),
+                    HeatmapTile::Build => generic_child.get_tile_height(tile) > generic_state.get_tile_height(tile),
+                };
+                if is_move_tile {
+                    let best_evaluation: f32 = tile_evaluations[tile].unwrap_or(if player_a_turn { f32::NEG_INFINITY } else { f32::INFINITY });
+                    tile_evaluations[tile] = Some(if player_a_turn { best_evaluation.max(evaluation) } else { best_evaluation.min(evaluation) });
+                }
+            }
+        }
+        return tile_evaluations;
+    }
+
+    fn check_tile_evaluations<
+        const ROWS: usize, const COLUMNS: usize, const WORKERS_PER_PLAYER: usize,
+        GS: GameState<GenericGameState=GenericSantoriniGameState<ROWS, COLUMNS, WORKERS_PER_PLAYER>> + SantoriniEval
+    >(state: &GS) {
+        if state.has_player_a_won() || state.has_player_b_won() {
+            return;
+        }
+        for heatmap_tile in [HeatmapTile::Destination, HeatmapTile::Build] {
+            let tile_evaluations = get_tile_evaluations(state, heatmap_tile, |child| {
+                assert!(!child.has_player_a_won() && !child.has_player_b_won(), "Won states get a decisive score without being evaluated");
+                return child.get_child_evaluation();
+            }).unwrap();
+            assert_eq!(tile_evaluations, get_expected_tile_evaluations(state, heatmap_tile), "{} {:?}", state.to_generic_game_state().to_position_string(), heatmap_tile);
+        }
+    }
+
+    #[test]
+    fn test_tile_evaluations_are_the_best_evaluations_of_the_moves_using_the_tile() {
+        check_tile_evaluations(&get_initial_setup_state::<GameState5x5BinaryComposite>());
+        check_tile_evaluations(&get_initial_setup_state::<GameState4x4Binary3Bit>());
+        for _ in 0..300 {
+            check_tile_evaluations(&GameState5x5BinaryComposite::from_generic_game_state(&GenericSantoriniGameState::<5, 5, 2>::generate_random_state()));
+            check_tile_evaluations(&GameState4x4Binary3Bit::from_generic_game_state(&GenericSantoriniGameState::<4, 4, 1>::generate_random_state()));
+        }
+    }
+
+    #[test]
+    fn test_searched_tile_evaluations_support_won_states_inside_the_search() {
+        // Player B threatens to win with a1-b1, player A can only prevent it by moving c3-b2 and building a dome on b1
+        let state = GameState5x5BinaryComposite::from_generic_game_state(&GenericSantoriniGameState::<5, 5, 2>::from_position_string("2310000000000000000000000/12,18/0,24/A").unwrap());
+        let params = DynamicHeuristicParams::default();
+        let mut cache = MinimaxCache::new();
+        let tile_evaluations = get_tile_evaluations(&state, HeatmapTile::Build, |child| {
+            minimax_custom_heuristic(child, 3, &mut cache, &|state: &GameState5x5BinaryComposite| dynamic_heuristic(state, &params))
+        }).unwrap();
+        assert!(!is_player_b_win(tile_evaluations[1].unwrap()));
+        assert!(tile_evaluations.iter().flatten().any(|evaluation| is_player_b_win(*evaluation)));
+    }
+}
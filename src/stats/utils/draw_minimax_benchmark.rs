@@ -3,6 +3,7 @@ use plotters::prelude::*;
 use plotters::style::text_anchor::{HPos, Pos, VPos};
 use serde::{Deserialize, Serialize};
 use crate::stats::utils::formatters::{ns_formatter, value_formatter};
+use crate::minimax::score::is_decisive;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct MinimaxMeasurement {
@@ -105,7 +106,7 @@ pub fn draw_minimax_benchmark(graph_path: String, graph_name: String, encoding_n
             for depth in 0..=max_depth {
                 raw_execution_times[depth].push(single_measurement[depth].computation_time.as_nanos() as usize);
                 raw_evaluated_states[depth].push(single_measurement[depth].evaluated_states);
-                if single_measurement[depth].result.is_infinite() {
+                if is_decisive(single_measurement[depth].result) {
                     // If the game is solved, we disregard the deeper depths
                     break;
                 }
@@ -2,13 +2,15 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 use rand::SeedableRng;
 use tokio::sync::Mutex;
-use crate::game_state::GameState;
-use crate::generic_game_state::GenericGameState;
+use crate::game_state::{GameState, SantoriniEval};
 use crate::stats::utils::draw_minimax_benchmark::{AverageMinimaxMeasurement, MinimaxMeasurement};
+use crate::minimax::score::is_decisive;
+use crate::stats::utils::position_sampling::PositionSampling;
 
-pub async fn gather_minimax_benchmark<GS: GameState + 'static>(
+pub async fn gather_minimax_benchmark<GS: GameState + SantoriniEval + 'static>(
     number_of_game_states: usize,
     block_count: usize,
+    position_sampling: &PositionSampling<GS>,
     max_depth: usize,
     function: fn(state: &GS, depth: usize) -> (f32, usize),
 ) -> anyhow::Result<(Vec<Vec<MinimaxMeasurement>>, Vec<AverageMinimaxMeasurement>)> {
@@ -17,8 +19,7 @@ pub async fn gather_minimax_benchmark<GS: GameState + 'static>(
     }
 
     let mut rng = rand::rngs::StdRng::seed_from_u64(0);
-    let random_states: Vec<GS> = (0..number_of_game_states)
-        .map(|_| GS::from_generic_game_state(&GenericGameState::generate_random_state_with_blocks_rng(&mut rng, block_count))).collect();
+    let random_states: Vec<GS> = position_sampling.sample_positions(&mut rng, block_count, number_of_game_states)?;
 
     let mut tasks = Vec::with_capacity(number_of_game_states);
     let states_progress = Arc::new(Mutex::new(0));
@@ -65,7 +66,7 @@ pub async fn gather_minimax_benchmark<GS: GameState + 'static>(
         for single_measurement in measurements.iter() {
             total_computation_time += single_measurement[depth].computation_time;
             total_evaluated_states += single_measurement[depth].evaluated_states;
-            if single_measurement[depth].result != 0.0 {
+            if is_decisive(single_measurement[depth].result) {
                 solved_states += 1;
             }
         }
@@ -0,0 +1,170 @@
+use anyhow::{bail, Result};
+use rand::Rng;
+use crate::game_state::{GameState, SantoriniEval};
+use crate::generic_game_state::GenericGameState;
+use crate::generic_game_state::position_constraints::PositionConstraints;
+use crate::minimax::minimax_cache::MinimaxCache;
+use crate::minimax::minimax_custom_heuristic;
+
+// Playouts that end before the target block count are restarted, this limits the number of restarts per position
+const MAX_PLAYOUT_ATTEMPTS: usize = 10_000;
+
+#[derive(Copy, Clone, Debug)]
+pub enum PlayoutPolicy<GS: GameState> {
+    Random,
+    // Chooses the child with the best static child evaluation, ties are broken randomly
+    Greedy,
+    // Like Greedy, but uniform noise in [-noise, noise] is added to every child evaluation
+    NoisyGreedy { noise: f32 },
+    // Chooses the child with the best heuristic value, searched with minimax if a search depth is given. The name is used for the stat names
+    Heuristic { name: &'static str, heuristic_function: fn(&GS) -> f32, search_depth: Option<usize> },
+}
+
+/**
+Uniform sampling distributes the blocks uniformly over the board, which creates many positions that never occur in real games.
+Playout sampling plays a game from a random worker placement with the given policy until the block count is reached.
+Moves that win immediately are not played, as the sampled positions should not be decided yet.
+Constrained sampling generates positions with the given properties, the block count of the constraints is replaced by the requested one.
+ */
+#[derive(Clone, Debug)]
+pub enum PositionSampling<GS: GameState> {
+    Uniform,
+    Playout(PlayoutPolicy<GS>),
+    Constrained(PositionConstraints),
+}
+
+impl<GS: GameState + SantoriniEval> PositionSampling<GS> {
+    // Appended to the stat names, so data of different samplings is not mixed
+    pub fn get_name_suffix(&self) -> String {
+        return match self {
+            PositionSampling::Uniform => "".to_string(),
+            PositionSampling::Playout(PlayoutPolicy::Random) => "_playout_random".to_string(),
+            PositionSampling::Playout(PlayoutPolicy::Greedy) => "_playout_greedy".to_string(),
+            PositionSampling::Playout(PlayoutPolicy::NoisyGreedy { noise }) => format!("_playout_noisy{}", noise),
+            PositionSampling::Playout(PlayoutPolicy::Heuristic { name, search_depth, .. }) => match search_depth {
+                Some(search_depth) => format!("_playout_{}_d{}", name, search_depth),
+                None => format!("_playout_{}", name),
+            },
+            PositionSampling::Constrained(constraints) => constraints.get_name_suffix(),
+        };
+    }
+
+    pub fn sample_position<RNG: Rng>(&self, rng: &mut RNG, block_count: usize) -> Result<GS> {
+        return match self {
+            PositionSampling::Uniform => Ok(GS::from_generic_game_state(&GS::GenericGameState::generate_random_state_with_blocks_rng(rng, block_count))),
+            PositionSampling::Playout(policy) => sample_playout_position(rng, block_count, *policy),
+            PositionSampling::Constrained(constraints) => {
+                let constraints = constraints.clone().with_block_count(block_count);
+                Ok(GS::from_generic_game_state(&GS::GenericGameState::generate_constrained_state_rng(rng, &constraints)?))
+            }
+        };
+    }
+
+    pub fn sample_positions<RNG: Rng>(&self, rng: &mut RNG, block_count: usize, number_of_positions: usize) -> Result<Vec<GS>> {
+        return (0..number_of_positions).map(|_| self.sample_position(rng, block_count)).collect();
+    }
+}
+
+fn choose_child<GS: GameState + SantoriniEval, RNG: Rng>(rng: &mut RNG, children_states: &[GS], maximizing_player: bool, policy: PlayoutPolicy<GS>) -> GS {
+    let noise = match policy {
+        PlayoutPolicy::Random => return children_states[rng.gen_range(0..children_states.len())],
+        PlayoutPolicy::Greedy | PlayoutPolicy::Heuristic { .. } => 0.0,
+        PlayoutPolicy::NoisyGreedy { noise } => noise,
+    };
+
+    let mut cache = MinimaxCache::new();
+    let mut best_children = Vec::new();
+    let mut best_evaluation = f32::NEG_INFINITY;
+    for child in children_states {
+        let mut evaluation = match policy {
+            PlayoutPolicy::Heuristic { heuristic_function, search_depth: Some(search_depth), .. } => minimax_custom_heuristic(child, search_depth, &mut cache, &heuristic_function),
+            PlayoutPolicy::Heuristic { heuristic_function, search_depth: None, .. } => heuristic_function(child),
+            _ => child.get_child_evaluation(),
+        };
+        if noise > 0.0 {
+            evaluation += rng.gen_range(-noise..=noise);
+        }
+        // The evaluations are from the perspective of player A
+        let evaluation = if maximizing_player { evaluation } else { -evaluation };
+
+        if evaluation > best_evaluation {
+            best_evaluation = evaluation;
+            best_children.clear();
+            best_children.push(*child);
+        } else if evaluation == best_evaluation {
+            best_children.push(*child);
+        }
+    }
+
+    if best_children.is_empty() {
+        // Only happens if every evaluation is NaN
+        return children_states[rng.gen_range(0..children_states.len())];
+    }
+    return best_children[rng.gen_range(0..best_children.len())];
+}
+
+fn sample_playout_position<GS: GameState + SantoriniEval, RNG: Rng>(rng: &mut RNG, block_count: usize, policy: PlayoutPolicy<GS>) -> Result<GS> {
+    let mut children_states = Vec::new();
+
+    for _ in 0..MAX_PLAYOUT_ATTEMPTS {
+        let mut state = GS::from_generic_game_state(&GS::GenericGameState::generate_random_state_with_blocks_rng(rng, 0));
+
+        // After the workers are placed, every move builds exactly one block
+        let mut current_block_count = 0;
+        while current_block_count < block_count {
+            state.get_children_states_reuse_vec(&mut children_states);
+            children_states.retain(|child| !child.has_player_a_won() && !child.has_player_b_won());
+            if children_states.is_empty() {
+                break;
+            }
+
+            state = choose_child(rng, &children_states, state.is_player_a_turn(), policy);
+            current_block_count += 1;
+        }
+
+        if current_block_count == block_count {
+            return Ok(state);
+        }
+    }
+
+    bail!("Could not reach a position with {} blocks in {} playouts", block_count, MAX_PLAYOUT_ATTEMPTS);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use crate::game_state::{GameState, SantoriniEval};
+    use crate::game_state::game_state_5x5_binary_composite::GameState5x5BinaryComposite;
+    use crate::stats::utils::position_sampling::{PlayoutPolicy, PositionSampling};
+
+    type GS = GameState5x5BinaryComposite;
+
+    fn child_evaluation(state: &GS) -> f32 {
+        return state.get_child_evaluation();
+    }
+
+    #[test]
+    fn test_same_seed_samples_same_playout_positions() {
+        let policies = [
+            PlayoutPolicy::Random,
+            PlayoutPolicy::Greedy,
+            PlayoutPolicy::NoisyGreedy { noise: 1.0 },
+            PlayoutPolicy::Heuristic { name: "child", heuristic_function: child_evaluation, search_depth: None },
+            PlayoutPolicy::Heuristic { name: "child", heuristic_function: child_evaluation, search_depth: Some(1) },
+        ];
+        for policy in policies {
+            let position_sampling = PositionSampling::Playout(policy);
+            let positions: Vec<GS> = position_sampling.sample_positions(&mut StdRng::seed_from_u64(0), 10, 5).unwrap();
+            let resampled_positions: Vec<GS> = position_sampling.sample_positions(&mut StdRng::seed_from_u64(0), 10, 5).unwrap();
+            assert!(positions == resampled_positions, "{}", position_sampling.get_name_suffix());
+
+            for position in positions {
+                let block_count: usize = position.get_santorini_state().tile_heights.iter().map(|&height| height as usize).sum();
+                assert_eq!(block_count, 10);
+                assert!(!position.has_player_a_won() && !position.has_player_b_won());
+            }
+        }
+    }
+}
@@ -1,9 +1,49 @@
+use anyhow::Result;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use crate::game_state::GameState;
+use crate::strategy::game_context::GameContext;
 
 pub trait Strategy : Copy + Clone + Send + Sync {
     type GameState: GameState;
 
     fn choose_move(&self, is_player_a: bool, current_state: &Self::GameState, possible_next_states: &Vec<Self::GameState>) -> usize;
+
+    // Strategies that choose randomly (e.g. between equally good moves) have to use the given random number generator
+    fn choose_move_with_rng<R: Rng>(&self, is_player_a: bool, current_state: &Self::GameState, possible_next_states: &Vec<Self::GameState>, _rng: &mut R) -> usize {
+        return self.choose_move(is_player_a, current_state, possible_next_states);
+    }
+}
+
+/**
+Strategy that can keep information (e.g. a search tree or cache) between moves and games.
+Every strategy is informed about a new game before its first move, and about every move of the opponent.
+An error while choosing a move forfeits the game.
+ */
+pub trait StatefulStrategy : Send {
+    type GameState: GameState;
+
+    fn new_game(&mut self, _is_player_a: bool, _context: &GameContext<Self::GameState>) -> Result<()> {
+        return Ok(());
+    }
+
+    // The opponent's move is the last state in the history of the context
+    fn opponent_moved(&mut self, _context: &GameContext<Self::GameState>) -> Result<()> {
+        return Ok(());
+    }
+
+    fn choose_move_in_context(&mut self, is_player_a: bool, context: &GameContext<Self::GameState>, possible_next_states: &Vec<Self::GameState>) -> Result<usize>;
+}
+
+// Adapter, so every stateless strategy can be used where a stateful strategy is expected
+impl<S: Strategy> StatefulStrategy for S {
+    type GameState = S::GameState;
+
+    // The random number generator is derived from the context, so games with the same seed are reproducible
+    fn choose_move_in_context(&mut self, is_player_a: bool, context: &GameContext<Self::GameState>, possible_next_states: &Vec<Self::GameState>) -> Result<usize> {
+        let mut rng = StdRng::seed_from_u64(context.get_move_seed(is_player_a));
+        return Ok(self.choose_move_with_rng(is_player_a, context.get_current_state(), possible_next_states, &mut rng));
+    }
 }
 
 pub mod heuristics;
@@ -11,4 +51,10 @@ pub mod random_strategy;
 pub mod minimax_strategy_4x4;
 pub mod console_input_strategy;
 pub mod heuristic_minimax_strategy;
-pub mod dynamic_minimax_strategy;
\ No newline at end of file
+pub mod dynamic_minimax_strategy;
+pub mod game_context;
+pub mod iterative_deepening_strategy;
+pub mod opening_book;
+pub mod opening_book_strategy;
+pub mod tablebase_strategy_4x4;
+pub mod external_engine_strategy;
\ No newline at end of file
@@ -2,6 +2,7 @@ use rand::Rng;
 use crate::game_state::{GameState, SantoriniEval, SantoriniState5x5};
 use crate::minimax::minimax_cache::MinimaxCache;
 use crate::minimax::minimax_custom_heuristic;
+use crate::minimax::score::win_score;
 use crate::strategy::heuristics::dynamic_heuristic::{dynamic_heuristic, DynamicHeuristicParams};
 use crate::strategy::Strategy;
 
@@ -26,13 +27,17 @@ impl<GS: GameState + SantoriniEval<SantoriniState = SantoriniState5x5>> DynamicM
 impl<GS: GameState + SantoriniEval<SantoriniState = SantoriniState5x5>> Strategy for DynamicMinimaxStrategy<GS> {
     type GameState = GS;
 
-    fn choose_move(&self, is_player_a: bool, _current_state: &GS, possible_next_states: &Vec<GS>) -> usize {
+    fn choose_move(&self, is_player_a: bool, current_state: &GS, possible_next_states: &Vec<GS>) -> usize {
+        return self.choose_move_with_rng(is_player_a, current_state, possible_next_states, &mut rand::thread_rng());
+    }
+
+    fn choose_move_with_rng<R: Rng>(&self, is_player_a: bool, _current_state: &GS, possible_next_states: &Vec<GS>, rng: &mut R) -> usize {
         let mut cache = MinimaxCache::new();
         let dynamic_heuristic_function = |state: &GS| {
             if state.has_player_a_won() {
-                return f32::INFINITY;
+                return win_score(true);
             } else if state.has_player_b_won() {
-                return f32::NEG_INFINITY;
+                return win_score(false);
             }
             dynamic_heuristic(state, &self.params)
         };
@@ -72,9 +77,8 @@ impl<GS: GameState + SantoriniEval<SantoriniState = SantoriniState5x5>> Strategy
         return if best_move_indices.len() == 1 {
             best_move_indices[0]
         } else {
-            let mut rng = rand::thread_rng();
             let best_move_index = rng.gen_range(0..best_move_indices.len());
             best_move_indices[best_move_index]
-        }
+        };
     }
 }
\ No newline at end of file
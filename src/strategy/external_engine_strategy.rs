@@ -0,0 +1,143 @@
+use std::io::{BufRead, BufReader, Write};
+use std::marker::PhantomData;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use anyhow::{anyhow, bail, ensure, Result};
+use crate::engine_protocol::{EngineCommand, SearchLimits};
+use crate::game_state::GameState;
+use crate::generic_game_state::move_notation::{find_move, get_move_notation};
+use crate::generic_game_state::GenericGameState;
+use crate::strategy::game_context::GameContext;
+use crate::strategy::StatefulStrategy;
+
+/**
+Lets an external engine that speaks the protocol of engine_protocol choose the moves, e.g. another build of this engine.
+The whole game is sent with every move, so the engine doesn't have to track the moves of the opponent.
+With a time control, the engine gets half of the time for the move, which leaves a margin for the principal variation and the communication.
+ */
+pub struct ExternalEngineStrategy<GS: GameState> {
+    process: Child,
+    input: ChildStdin,
+    output: BufReader<ChildStdout>,
+    // Used if the game has no time control
+    search_depth: usize,
+    // "id" lines of the handshake
+    engine_id: Vec<String>,
+    // Last "info" line of the most recent search
+    last_info: Option<String>,
+    phantom: PhantomData<GS>,
+}
+
+impl<GS: GameState> ExternalEngineStrategy<GS> {
+    pub fn new(program: &str, arguments: &[&str], search_depth: usize) -> Result<ExternalEngineStrategy<GS>> {
+        let mut process = Command::new(program)
+            .args(arguments)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let input = process.stdin.take().ok_or_else(|| anyhow!("Couldn't open the input of the engine"))?;
+        let output = BufReader::new(process.stdout.take().ok_or_else(|| anyhow!("Couldn't open the output of the engine"))?);
+
+        let mut strategy = ExternalEngineStrategy {
+            process,
+            input,
+            output,
+            search_depth,
+            engine_id: Vec::new(),
+            last_info: None,
+            phantom: PhantomData,
+        };
+        strategy.handshake()?;
+        return Ok(strategy);
+    }
+
+    pub fn get_engine_id(&self) -> &[String] {
+        return &self.engine_id;
+    }
+
+    pub fn get_last_info(&self) -> Option<&str> {
+        return self.last_info.as_deref();
+    }
+
+    fn send(&mut self, command: &EngineCommand) -> Result<()> {
+        writeln!(self.input, "{}", command)?;
+        self.input.flush()?;
+        return Ok(());
+    }
+
+    fn read_line(&mut self) -> Result<String> {
+        let mut line = String::new();
+        let bytes_read = self.output.read_line(&mut line)?;
+        ensure!(bytes_read > 0, "The engine closed its output");
+        let line = line.trim().to_string();
+        if let Some(message) = line.strip_prefix("error ") {
+            bail!("Engine error: {}", message);
+        }
+        return Ok(line);
+    }
+
+    fn handshake(&mut self) -> Result<()> {
+        self.send(&EngineCommand::Handshake)?;
+        loop {
+            let line = self.read_line()?;
+            if line == "stpok" {
+                break;
+            }
+            if let Some(id) = line.strip_prefix("id ") {
+                self.engine_id.push(id.to_string());
+            }
+        }
+
+        let board = format!("board {}x{} workers {}", GS::GenericGameState::ROWS, GS::GenericGameState::COLUMNS, GS::GenericGameState::WORKERS_PER_PLAYER);
+        ensure!(self.engine_id.contains(&board), "The engine doesn't play on a {}", board);
+        return Ok(());
+    }
+}
+
+impl<GS: GameState> StatefulStrategy for ExternalEngineStrategy<GS> {
+    type GameState = GS;
+
+    fn new_game(&mut self, _is_player_a: bool, _context: &GameContext<GS>) -> Result<()> {
+        self.last_info = None;
+        return self.send(&EngineCommand::NewGame);
+    }
+
+    fn choose_move_in_context(&mut self, _is_player_a: bool, context: &GameContext<GS>, possible_next_states: &Vec<GS>) -> Result<usize> {
+        let mut moves = Vec::with_capacity(context.history.len() - 1);
+        for states in context.history.windows(2) {
+            moves.push(get_move_notation(&states[0], &states[1])?);
+        }
+        let position_string = context.get_initial_state().to_generic_game_state().to_position_string();
+        self.send(&EngineCommand::Position { position_string: Some(position_string), moves })?;
+
+        let limits = match context.get_time_for_move() {
+            Some(time_for_move) => SearchLimits { move_time: Some(time_for_move / 2), ..SearchLimits::default() },
+            None => SearchLimits { depth: Some(self.search_depth), ..SearchLimits::default() },
+        };
+        self.send(&EngineCommand::Go(limits))?;
+
+        let best_move = loop {
+            let line = self.read_line()?;
+            if line.starts_with("info ") {
+                self.last_info = Some(line);
+            } else if let Some(best_move) = line.strip_prefix("bestmove ") {
+                break best_move.to_string();
+            }
+        };
+        ensure!(best_move != "none", "The engine found no move");
+
+        let (_, next_state) = find_move(context.get_current_state(), &best_move)?;
+        // The children are equal to possible_next_states, but the order is not guaranteed
+        return possible_next_states.iter().position(|state| *state == next_state)
+            .ok_or_else(|| anyhow!("Move '{}' of the engine is not possible", best_move));
+    }
+}
+
+impl<GS: GameState> Drop for ExternalEngineStrategy<GS> {
+    fn drop(&mut self) {
+        // The engine might have exited already
+        if self.send(&EngineCommand::Quit).is_err() {
+            let _ = self.process.kill();
+        }
+        let _ = self.process.wait();
+    }
+}
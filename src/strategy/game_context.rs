@@ -0,0 +1,142 @@
+use std::time::Duration;
+use rand::RngCore;
+use crate::game_state::GameState;
+use crate::generic_game_state::GenericGameState;
+
+const PLAYER_B_SEED_MASK: u64 = 0x9E37_79B9_7F4A_7C15;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RuleSet {
+    pub rows: usize,
+    pub columns: usize,
+    pub workers_per_player: usize,
+}
+
+impl RuleSet {
+    pub fn for_game_state<GS: GameState>() -> RuleSet {
+        return RuleSet {
+            rows: GS::GenericGameState::ROWS,
+            columns: GS::GenericGameState::COLUMNS,
+            workers_per_player: GS::GenericGameState::WORKERS_PER_PLAYER,
+        };
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TimeControl {
+    Unlimited,
+    // Every move has to be made within the given time
+    FixedPerMove(Duration),
+    // Every player has a total time for the game, which is increased by the increment after every move
+    Incremental { initial: Duration, increment: Duration },
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct PlayerClock {
+    pub time_used: Duration,
+    pub moves_made: usize,
+    // Remaining time for the rest of the game, only used with TimeControl::Incremental
+    pub remaining: Option<Duration>,
+}
+
+/**
+Everything a strategy can know about the current game.
+The history contains every state of the game, starting with the initial state and ending with the current state.
+ */
+#[derive(Clone, Debug)]
+pub struct GameContext<GS: GameState> {
+    pub rule_set: RuleSet,
+    pub time_control: TimeControl,
+    pub history: Vec<GS>,
+    // Time that was used for each move, move i leads to history[i + 1]
+    pub move_times: Vec<Duration>,
+    pub player_a_clock: PlayerClock,
+    pub player_b_clock: PlayerClock,
+    // Strategies that use randomness derive their random number generator from this seed (see get_strategy_seed)
+    pub seed: u64,
+}
+
+impl<GS: GameState> GameContext<GS> {
+    pub fn new(initial_state: GS) -> GameContext<GS> {
+        return Self::with_time_control(initial_state, TimeControl::Unlimited);
+    }
+
+    pub fn with_time_control(initial_state: GS, time_control: TimeControl) -> GameContext<GS> {
+        return Self::with_seed(initial_state, time_control, rand::thread_rng().next_u64());
+    }
+
+    pub fn with_seed(initial_state: GS, time_control: TimeControl, seed: u64) -> GameContext<GS> {
+        let clock = PlayerClock {
+            remaining: match time_control {
+                TimeControl::Incremental { initial, .. } => Some(initial),
+                _ => None,
+            },
+            ..PlayerClock::default()
+        };
+        return GameContext {
+            rule_set: RuleSet::for_game_state::<GS>(),
+            time_control,
+            history: vec![initial_state],
+            move_times: Vec::new(),
+            player_a_clock: clock,
+            player_b_clock: clock,
+            seed,
+        };
+    }
+
+    pub fn get_initial_state(&self) -> &GS {
+        return &self.history[0];
+    }
+
+    pub fn get_current_state(&self) -> &GS {
+        return self.history.last().expect("The history always contains the initial state");
+    }
+
+    // Number of moves (plies) that were played since the initial state
+    pub fn get_move_number(&self) -> usize {
+        return self.history.len() - 1;
+    }
+
+    pub fn get_clock(&self, player_a: bool) -> &PlayerClock {
+        return if player_a { &self.player_a_clock } else { &self.player_b_clock };
+    }
+
+    // Both players get different random number streams, even if they use the same strategy
+    pub fn get_strategy_seed(&self, player_a: bool) -> u64 {
+        return if player_a { self.seed } else { self.seed ^ PLAYER_B_SEED_MASK };
+    }
+
+    // Seed for a single move, used by strategies that don't keep a random number generator between moves
+    pub fn get_move_seed(&self, player_a: bool) -> u64 {
+        return self.get_strategy_seed(player_a).wrapping_add((self.get_move_number() as u64).wrapping_mul(PLAYER_B_SEED_MASK));
+    }
+
+    // Maximum time the player to move can use for the current move, None if the time is unlimited
+    pub fn get_time_for_move(&self) -> Option<Duration> {
+        return match self.time_control {
+            TimeControl::Unlimited => None,
+            TimeControl::FixedPerMove(time_per_move) => Some(time_per_move),
+            TimeControl::Incremental { .. } => self.get_clock(self.get_current_state().is_player_a_turn()).remaining,
+        };
+    }
+
+    // Returns false if the player to move exceeded their time, in which case the move is not applied
+    pub(crate) fn apply_move(&mut self, next_state: GS, time_used: Duration) -> bool {
+        if self.get_time_for_move().is_some_and(|time_for_move| time_used > time_for_move) {
+            return false;
+        }
+
+        let increment = match self.time_control {
+            TimeControl::Incremental { increment, .. } => increment,
+            _ => Duration::ZERO,
+        };
+        let clock = if self.get_current_state().is_player_a_turn() { &mut self.player_a_clock } else { &mut self.player_b_clock };
+        clock.time_used += time_used;
+        clock.moves_made += 1;
+        clock.remaining = clock.remaining.map(|remaining| remaining - time_used + increment);
+
+        self.move_times.push(time_used);
+        self.history.push(next_state);
+        return true;
+    }
+}
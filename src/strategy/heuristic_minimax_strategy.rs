@@ -25,7 +25,11 @@ impl<GS: GameState + SantoriniEval<SantoriniState = SantoriniState5x5>> Heuristi
 impl<GS: GameState + SantoriniEval<SantoriniState = SantoriniState5x5>> Strategy for HeuristicMinimaxStrategy<GS> {
     type GameState = GS;
 
-    fn choose_move(&self, is_player_a: bool, _current_state: &GS, possible_next_states: &Vec<GS>) -> usize {
+    fn choose_move(&self, is_player_a: bool, current_state: &GS, possible_next_states: &Vec<GS>) -> usize {
+        return self.choose_move_with_rng(is_player_a, current_state, possible_next_states, &mut rand::thread_rng());
+    }
+
+    fn choose_move_with_rng<R: Rng>(&self, is_player_a: bool, _current_state: &GS, possible_next_states: &Vec<GS>, rng: &mut R) -> usize {
         let mut cache = MinimaxCache::new();
 
         let mut best_move_indices = Vec::new();
@@ -63,9 +67,77 @@ impl<GS: GameState + SantoriniEval<SantoriniState = SantoriniState5x5>> Strategy
         return if best_move_indices.len() == 1 {
             best_move_indices[0]
         } else {
-            let mut rng = rand::thread_rng();
             let best_move_index = rng.gen_range(0..best_move_indices.len());
             best_move_indices[best_move_index]
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use crate::game_state::{GameState, SantoriniEval};
+    use crate::game_state::game_state_5x5_binary_composite::GameState5x5BinaryComposite;
+    use crate::generic_game_state::generic_santorini_game_state::GenericSantoriniGameState;
+    use crate::generic_game_state::GenericGameState;
+    use crate::strategy::heuristic_minimax_strategy::HeuristicMinimaxStrategy;
+    use crate::strategy::Strategy;
+
+    type GS = GameState5x5BinaryComposite;
+
+    fn child_evaluation(state: &GS) -> f32 {
+        return state.get_child_evaluation();
+    }
+
+    // The player has won if they reached height 3 or the opponent can't move
+    fn has_player_won(state: &GS, player_a: bool) -> bool {
+        if state.has_player_a_won() || state.has_player_b_won() {
+            return if player_a { state.has_player_a_won() } else { state.has_player_b_won() };
+        }
+        return state.is_player_a_turn() != player_a && state.get_children_states().is_empty();
+    }
+
+    fn can_player_to_move_win_immediately(state: &GS) -> bool {
+        return state.get_children_states().iter().any(|child| has_player_won(child, state.is_player_a_turn()));
+    }
+
+    #[test]
+    fn test_heuristic_minimax_prefers_fast_wins_and_slow_losses() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let strategy = HeuristicMinimaxStrategy::<GS>::new(2, child_evaluation);
+        let mut checked_wins = 0;
+        let mut checked_losses = 0;
+
+        for _ in 0..1000 {
+            let block_count = rng.gen_range(30..=60);
+            let state = GS::from_generic_game_state(&GenericSantoriniGameState::<5, 5, 2>::generate_random_state_with_blocks_rng(&mut rng, block_count));
+            if state.has_player_a_won() || state.has_player_b_won() {
+                continue;
+            }
+            let children = state.get_children_states();
+            if children.is_empty() {
+                continue;
+            }
+
+            // The assertions hold for every move that is chosen between equally good moves
+            let is_player_a = state.is_player_a_turn();
+            let chosen_child = children[strategy.choose_move(is_player_a, &state, &children)];
+
+            // A win in 1 is chosen over the (many) wins in 3 that follow from other moves
+            if can_player_to_move_win_immediately(&state) {
+                assert!(has_player_won(&chosen_child, is_player_a), "{}", state.to_generic_game_state());
+                checked_wins += 1;
+                continue;
+            }
+
+            // A move that doesn't lose immediately is preferred, even if it loses a few moves later
+            let losing_immediately = children.iter().filter(|child| can_player_to_move_win_immediately(child)).count();
+            if losing_immediately > 0 && losing_immediately < children.len() {
+                assert!(!can_player_to_move_win_immediately(&chosen_child), "{}", state.to_generic_game_state());
+                checked_losses += 1;
+            }
         }
+        assert!(checked_wins > 0 && checked_losses > 0);
     }
-}
\ No newline at end of file
+}
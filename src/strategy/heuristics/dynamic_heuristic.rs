@@ -100,7 +100,7 @@ const TILE_CENTER_INDEX: [usize; 25] = [
 pub fn dynamic_heuristic<GS: GameState + SantoriniEval<SantoriniState=SantoriniState5x5>>(state: &GS, params: &DynamicHeuristicParams) -> f32 {
     let state = state.get_santorini_state();
 
-    if state.worker_b_tiles[0] == 16 {
+    if state.worker_b_tiles[0] as usize >= state.tile_heights.len() {
         // Setup stage is not supported
         return 0.0;
     }
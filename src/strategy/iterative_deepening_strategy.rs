@@ -0,0 +1,79 @@
+use std::time::{Duration, Instant};
+use anyhow::Result;
+use crate::game_state::{GameState, SantoriniEval};
+use crate::minimax::minimax_cache::MinimaxCache;
+use crate::minimax::minimax_custom_heuristic;
+use crate::strategy::game_context::{GameContext, TimeControl};
+use crate::strategy::StatefulStrategy;
+
+// Expected number of remaining moves, used to divide the remaining time of incremental time controls
+const EXPECTED_REMAINING_MOVES: u32 = 20;
+
+/**
+Time based counterpart of HeuristicMinimaxStrategy: Searches with increasing depth until the time for the move is used up.
+Searches can't be interrupted, so the next depth is only searched if it is expected to finish in time,
+based on how much longer the last depth took than the one before.
+ */
+#[derive(Copy, Clone)]
+pub struct IterativeDeepeningStrategy<GS: GameState + SantoriniEval> {
+    // Used if the time is unlimited
+    max_depth: usize,
+    heuristic_function: fn(&GS) -> f32,
+}
+
+impl<GS: GameState + SantoriniEval> IterativeDeepeningStrategy<GS> {
+    pub fn new(max_depth: usize, heuristic_function: fn(&GS) -> f32) -> IterativeDeepeningStrategy<GS> {
+        assert!(max_depth < 100);
+        return IterativeDeepeningStrategy {
+            max_depth,
+            heuristic_function,
+        };
+    }
+
+    fn get_time_budget(context: &GameContext<GS>) -> Option<Duration> {
+        return match context.time_control {
+            TimeControl::Unlimited => None,
+            // Leave a safety margin, as the time is only checked between depths
+            TimeControl::FixedPerMove(time_per_move) => Some(time_per_move / 2),
+            TimeControl::Incremental { increment, .. } => context.get_time_for_move()
+                .map(|remaining| (remaining / EXPECTED_REMAINING_MOVES + increment / 2).min(remaining / 2)),
+        };
+    }
+}
+
+impl<GS: GameState + SantoriniEval> StatefulStrategy for IterativeDeepeningStrategy<GS> {
+    type GameState = GS;
+
+    fn choose_move_in_context(&mut self, is_player_a: bool, context: &GameContext<GS>, possible_next_states: &Vec<GS>) -> Result<usize> {
+        let start = Instant::now();
+        let time_budget = Self::get_time_budget(context);
+
+        let mut best_move_index = 0;
+        let mut previous_depth_time = Duration::ZERO;
+        for depth in 0..=self.max_depth {
+            let depth_start = Instant::now();
+            let mut cache = MinimaxCache::new();
+
+            let mut best_value = if is_player_a { f32::NEG_INFINITY } else { f32::INFINITY };
+            for (i, game_state) in possible_next_states.iter().enumerate() {
+                let value = minimax_custom_heuristic(game_state, depth, &mut cache, &self.heuristic_function);
+                if (is_player_a && value > best_value) || (!is_player_a && value < best_value) {
+                    best_value = value;
+                    best_move_index = i;
+                }
+            }
+
+            let depth_time = depth_start.elapsed();
+            if let Some(time_budget) = time_budget {
+                let growth_factor = if previous_depth_time.is_zero() { 10.0 } else { depth_time.as_secs_f64() / previous_depth_time.as_secs_f64() };
+                let expected_next_depth_time = depth_time.mul_f64(growth_factor.max(1.0));
+                if start.elapsed() + expected_next_depth_time > time_budget {
+                    break;
+                }
+            }
+            previous_depth_time = depth_time;
+        }
+
+        return Ok(best_move_index);
+    }
+}
@@ -0,0 +1,278 @@
+use std::fs;
+use anyhow::{ensure, Context, Result};
+use fnv::{FnvHashMap, FnvHashSet};
+use crate::game_state::{get_initial_setup_state, ContinuousBlockId, GameState, SantoriniEval, SimplifiedState};
+use crate::generic_game_state::GenericGameState;
+use crate::minimax::minimax_cache::MinimaxCache;
+use crate::minimax::minimax_custom_heuristic;
+use crate::minimax::score::win_score;
+use crate::play_game::play_game_with_context;
+use crate::precompute_state_winner::WinnerTablebase;
+use crate::strategy::game_context::{GameContext, TimeControl};
+use crate::strategy::StatefulStrategy;
+
+const EXACT_DEPTH: &str = "exact";
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BookEntry {
+    // From the perspective of player A, like minimax scores
+    pub evaluation: f32,
+    // Search depth of the evaluation, None for exact values from the tablebase
+    pub depth: Option<usize>,
+}
+
+impl BookEntry {
+    // Exact values are always preferred, otherwise the deeper search
+    fn is_better_than(&self, other: &BookEntry) -> bool {
+        return match (self.depth, other.depth) {
+            (_, None) => false,
+            (None, Some(_)) => true,
+            (Some(depth), Some(other_depth)) => depth > other_depth,
+        };
+    }
+}
+
+/**
+Evaluations of positions in the setup phase and the first moves of a game, in which the heuristics are not useful.
+Positions are stored by their canonical position string, so every entry covers all symmetric positions.
+
+A book file has one entry per line: the canonical position string, the evaluation and the search depth (or "exact").
+Empty lines and lines starting with '#' are ignored.
+ */
+#[derive(Clone, Debug, Default)]
+pub struct OpeningBook {
+    entries: FnvHashMap<String, BookEntry>,
+}
+
+fn get_book_key<GS: GameState>(state: &GS) -> String {
+    return state.to_generic_game_state().to_canonical_position_string();
+}
+
+/*
+Returns all positions that can be reached from the initial state within max_plies moves (including the initial state),
+only one position of every group of symmetric positions is kept.
+ */
+pub fn get_opening_positions<GS: GameState>(max_plies: usize) -> Vec<Vec<GS>> {
+    let initial_state = get_initial_setup_state::<GS>();
+    let mut positions_by_ply = vec![vec![initial_state]];
+    let mut children_states = Vec::new();
+
+    for _ in 0..max_plies {
+        let mut seen_keys = FnvHashSet::default();
+        let mut next_positions = Vec::new();
+        for state in positions_by_ply.last().unwrap() {
+            if state.has_player_a_won() || state.has_player_b_won() {
+                continue;
+            }
+            state.get_children_states_reuse_vec(&mut children_states);
+            for child in &children_states {
+                if seen_keys.insert(get_book_key(child)) {
+                    next_positions.push(*child);
+                }
+            }
+        }
+        positions_by_ply.push(next_positions);
+    }
+
+    return positions_by_ply;
+}
+
+impl OpeningBook {
+    pub fn new() -> OpeningBook {
+        return OpeningBook::default();
+    }
+
+    pub fn len(&self) -> usize {
+        return self.entries.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.entries.is_empty();
+    }
+
+    pub fn get<GS: GameState>(&self, state: &GS) -> Option<BookEntry> {
+        return self.entries.get(&get_book_key(state)).copied();
+    }
+
+    // Existing entries are only replaced by better ones (see BookEntry::is_better_than), returns true if the entry was stored
+    pub fn insert<GS: GameState>(&mut self, state: &GS, entry: BookEntry) -> bool {
+        return self.insert_by_key(get_book_key(state), entry);
+    }
+
+    fn insert_by_key(&mut self, key: String, entry: BookEntry) -> bool {
+        if let Some(existing_entry) = self.entries.get(&key) {
+            if !entry.is_better_than(existing_entry) {
+                return false;
+            }
+        }
+        self.entries.insert(key, entry);
+        return true;
+    }
+
+    fn needs_search(&self, state: &impl GameState, depth: usize) -> bool {
+        return match self.get(state) {
+            Some(entry) => BookEntry { evaluation: 0.0, depth: Some(depth) }.is_better_than(&entry),
+            None => true,
+        };
+    }
+
+    // Evaluates every position that can be reached within max_plies moves, returns the amount of added entries
+    pub fn add_searched_positions<GS: GameState + SantoriniEval, F: Fn(&GS) -> f32>(&mut self, max_plies: usize, depth: usize, heuristic_function: &F) -> usize {
+        let mut added_entries = 0;
+        let mut cache = MinimaxCache::new();
+        for positions in get_opening_positions::<GS>(max_plies).iter().skip(1) {
+            for state in positions {
+                if !self.needs_search(state, depth) {
+                    continue;
+                }
+                let evaluation = minimax_custom_heuristic(state, depth, &mut cache, heuristic_function);
+                if self.insert(state, BookEntry { evaluation, depth: Some(depth) }) {
+                    added_entries += 1;
+                }
+            }
+        }
+        return added_entries;
+    }
+
+    /*
+    Plays the given amount of games and evaluates every possible move of the first max_plies moves of each game.
+    This covers the lines that are actually played beyond the plies that add_searched_positions can cover.
+    Game i uses the seed (seed + i), so the same arguments always result in the same book.
+     */
+    pub fn add_self_play_positions<
+        GS: GameState + SantoriniEval,
+        S1: StatefulStrategy<GameState=GS>,
+        S2: StatefulStrategy<GameState=GS>,
+        F: Fn(&GS) -> f32
+    >(&mut self, player_a_strategy: &mut S1, player_b_strategy: &mut S2, number_of_games: usize, seed: u64, max_plies: usize, depth: usize, heuristic_function: &F) -> usize {
+        let initial_state = get_initial_setup_state::<GS>();
+        let mut added_entries = 0;
+        let mut cache = MinimaxCache::new();
+        let mut children_states = Vec::new();
+
+        for game_index in 0..number_of_games {
+            let context = GameContext::with_seed(initial_state, TimeControl::Unlimited, seed.wrapping_add(game_index as u64));
+            let game_result = play_game_with_context(player_a_strategy, player_b_strategy, context);
+
+            // The last state of the history has no move that was chosen
+            let history = &game_result.context.history;
+            for state in history.iter().take(max_plies.min(history.len() - 1)) {
+                state.get_children_states_reuse_vec(&mut children_states);
+                for child in &children_states {
+                    if !self.needs_search(child, depth) {
+                        continue;
+                    }
+                    let evaluation = minimax_custom_heuristic(child, depth, &mut cache, heuristic_function);
+                    if self.insert(child, BookEntry { evaluation, depth: Some(depth) }) {
+                        added_entries += 1;
+                    }
+                }
+            }
+        }
+        return added_entries;
+    }
+
+    // Adds the exact values of all positions within max_plies moves, for which the block count is loaded in the tablebase
+    pub fn add_tablebase_positions<GS: GameState + SimplifiedState + ContinuousBlockId>(&mut self, tablebase: &WinnerTablebase<GS>, max_plies: usize) -> usize {
+        let mut added_entries = 0;
+        for positions in get_opening_positions::<GS>(max_plies).iter().skip(1) {
+            for state in positions {
+                if let Some(player_a_wins) = tablebase.get_winner(state) {
+                    if self.insert(state, BookEntry { evaluation: win_score(player_a_wins), depth: None }) {
+                        added_entries += 1;
+                    }
+                }
+            }
+        }
+        return added_entries;
+    }
+
+    pub fn load(path: &str) -> Result<OpeningBook> {
+        let content = fs::read_to_string(path).with_context(|| format!("Could not read opening book {}", path))?;
+        return OpeningBook::parse(&content);
+    }
+
+    pub fn parse(content: &str) -> Result<OpeningBook> {
+        let mut book = OpeningBook::new();
+        for (line_index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            ensure!(parts.len() == 3, "Line {} of the opening book must have 3 parts separated by spaces, got {}", line_index + 1, parts.len());
+
+            let evaluation = parts[1].parse::<f32>().with_context(|| format!("Invalid evaluation in line {} of the opening book", line_index + 1))?;
+            let depth = if parts[2] == EXACT_DEPTH {
+                None
+            } else {
+                Some(parts[2].parse::<usize>().with_context(|| format!("Invalid depth in line {} of the opening book", line_index + 1))?)
+            };
+            book.insert_by_key(parts[0].to_string(), BookEntry { evaluation, depth });
+        }
+        return Ok(book);
+    }
+
+    // Entries are sorted, so the same book always results in the same file
+    pub fn save(&self, path: &str) -> Result<()> {
+        let mut keys: Vec<&String> = self.entries.keys().collect();
+        keys.sort();
+
+        let mut content = "# Canonical position string, evaluation from the perspective of player A, search depth or \"exact\"\n".to_string();
+        for key in keys {
+            let entry = &self.entries[key];
+            let depth = entry.depth.map(|depth| depth.to_string()).unwrap_or(EXACT_DEPTH.to_string());
+            content.push_str(&format!("{} {} {}\n", key, entry.evaluation, depth));
+        }
+        fs::write(path, content).with_context(|| format!("Could not write opening book {}", path))?;
+        return Ok(());
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state::{get_initial_setup_state, GameState};
+    use crate::game_state::game_state_5x5_binary_composite::GameState5x5BinaryComposite;
+    use crate::strategy::opening_book::{BookEntry, OpeningBook};
+
+    #[test]
+    fn test_entries_are_only_replaced_by_deeper_searches() {
+        let shallow_entry = BookEntry { evaluation: 1.0, depth: Some(2) };
+        let deep_entry = BookEntry { evaluation: 2.0, depth: Some(4) };
+        let exact_entry = BookEntry { evaluation: 3.0, depth: None };
+        assert!(deep_entry.is_better_than(&shallow_entry));
+        assert!(!shallow_entry.is_better_than(&deep_entry));
+        assert!(!deep_entry.is_better_than(&deep_entry));
+        assert!(exact_entry.is_better_than(&deep_entry));
+        assert!(!exact_entry.is_better_than(&exact_entry));
+
+        let state = get_initial_setup_state::<GameState5x5BinaryComposite>();
+        let mut book = OpeningBook::new();
+        assert!(book.insert(&state, deep_entry));
+        assert!(!book.insert(&state, shallow_entry));
+        assert_eq!(book.get(&state), Some(deep_entry));
+        assert!(book.insert(&state, exact_entry));
+        assert!(!book.insert(&state, deep_entry));
+        assert_eq!(book.get(&state), Some(exact_entry));
+    }
+
+    #[test]
+    fn test_saved_book_is_parsed_to_the_same_entries() {
+        let initial_state = get_initial_setup_state::<GameState5x5BinaryComposite>();
+        let mut book = OpeningBook::new();
+        book.insert(&initial_state, BookEntry { evaluation: 0.0, depth: None });
+        for (i, child) in initial_state.get_children_states().iter().enumerate() {
+            book.insert(child, BookEntry { evaluation: i as f32 * 0.25 - 10.0, depth: Some(i % 5) });
+        }
+
+        let book_path = std::env::temp_dir().join("santorini_test_opening_book.txt").to_string_lossy().to_string();
+        book.save(&book_path).unwrap();
+        let loaded_book = OpeningBook::load(&book_path).unwrap();
+        std::fs::remove_file(&book_path).unwrap();
+        assert_eq!(loaded_book.entries, book.entries);
+
+        assert!(OpeningBook::parse("0000000000000000000000000/-/-/A 1.0").is_err());
+        assert!(OpeningBook::parse("0000000000000000000000000/-/-/A 1.0 deep").is_err());
+    }
+}
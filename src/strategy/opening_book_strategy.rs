@@ -0,0 +1,141 @@
+use std::sync::Arc;
+use anyhow::Result;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::strategy::game_context::GameContext;
+use crate::strategy::opening_book::OpeningBook;
+use crate::strategy::StatefulStrategy;
+
+/**
+Plays the best move according to the opening book during the first moves of a game, and delegates to the wrapped strategy afterwards.
+The book is only used if it contains every possible move, as moves that are missing could be better than the ones in the book.
+ */
+pub struct OpeningBookStrategy<S: StatefulStrategy> {
+    book: Arc<OpeningBook>,
+    strategy: S,
+    // The book is only consulted for this many moves (plies) of a game
+    max_book_moves: usize,
+    rng: StdRng,
+}
+
+impl<S: StatefulStrategy> OpeningBookStrategy<S> {
+    pub fn new(book: Arc<OpeningBook>, strategy: S, max_book_moves: usize) -> OpeningBookStrategy<S> {
+        return OpeningBookStrategy {
+            book,
+            strategy,
+            max_book_moves,
+            // Reseeded for every game from the seed of the game context
+            rng: StdRng::seed_from_u64(0),
+        };
+    }
+
+    // Returns None if a possible move is not in the book, ties are broken randomly
+    fn choose_book_move(&mut self, is_player_a: bool, possible_next_states: &[S::GameState]) -> Option<usize> {
+        let mut best_move_indices = Vec::new();
+        let mut best_value = f32::NEG_INFINITY;
+
+        for (i, game_state) in possible_next_states.iter().enumerate() {
+            let evaluation = self.book.get(game_state)?.evaluation;
+            let value = if is_player_a { evaluation } else { -evaluation };
+
+            if value > best_value {
+                best_value = value;
+                best_move_indices.clear();
+                best_move_indices.push(i);
+            } else if value == best_value {
+                best_move_indices.push(i);
+            }
+        }
+
+        if best_move_indices.is_empty() {
+            return None;
+        }
+        return Some(best_move_indices[self.rng.gen_range(0..best_move_indices.len())]);
+    }
+}
+
+impl<S: StatefulStrategy> StatefulStrategy for OpeningBookStrategy<S> {
+    type GameState = S::GameState;
+
+    fn new_game(&mut self, is_player_a: bool, context: &GameContext<S::GameState>) -> Result<()> {
+        self.rng = StdRng::seed_from_u64(context.get_strategy_seed(is_player_a));
+        return self.strategy.new_game(is_player_a, context);
+    }
+
+    fn opponent_moved(&mut self, context: &GameContext<S::GameState>) -> Result<()> {
+        return self.strategy.opponent_moved(context);
+    }
+
+    fn choose_move_in_context(&mut self, is_player_a: bool, context: &GameContext<S::GameState>, possible_next_states: &Vec<S::GameState>) -> Result<usize> {
+        if context.get_move_number() < self.max_book_moves {
+            if let Some(book_move_index) = self.choose_book_move(is_player_a, possible_next_states) {
+                return Ok(book_move_index);
+            }
+        }
+        return self.strategy.choose_move_in_context(is_player_a, context, possible_next_states);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::game_state::{get_initial_setup_state, GameState};
+    use crate::game_state::game_state_4x4_binary_3bit::GameState4x4Binary3Bit;
+    use crate::generic_game_state::GenericGameState;
+    use crate::strategy::game_context::{GameContext, TimeControl};
+    use crate::strategy::opening_book::{BookEntry, OpeningBook};
+    use crate::strategy::opening_book_strategy::OpeningBookStrategy;
+    use crate::strategy::{StatefulStrategy, Strategy};
+
+    type GS = GameState4x4Binary3Bit;
+
+    // Always plays the last move, so it can be told apart from the book moves
+    #[derive(Copy, Clone)]
+    struct LastMoveStrategy;
+
+    impl Strategy for LastMoveStrategy {
+        type GameState = GS;
+
+        fn choose_move(&self, _is_player_a: bool, _current_state: &GS, possible_next_states: &Vec<GS>) -> usize {
+            return possible_next_states.len() - 1;
+        }
+    }
+
+    fn get_book_key(state: &GS) -> String {
+        return state.to_generic_game_state().to_canonical_position_string();
+    }
+
+    #[test]
+    fn test_book_move_is_played_only_if_every_child_is_in_the_book() {
+        let initial_state = get_initial_setup_state::<GS>();
+        let children_states = initial_state.get_children_states();
+        // The first child is the best one for player A, symmetric children share its entry
+        let best_key = get_book_key(&children_states[0]);
+        let mut book = OpeningBook::new();
+        for child in &children_states {
+            book.insert(child, BookEntry { evaluation: if get_book_key(child) == best_key { 1.0 } else { 0.0 }, depth: Some(2) });
+        }
+        assert_ne!(get_book_key(children_states.last().unwrap()), best_key);
+
+        let context = GameContext::with_seed(initial_state, TimeControl::Unlimited, 0);
+        let mut strategy = OpeningBookStrategy::new(Arc::new(book.clone()), LastMoveStrategy, 1);
+        strategy.new_game(true, &context).unwrap();
+        let chosen_move = strategy.choose_move_in_context(true, &context, &children_states).unwrap();
+        assert_eq!(get_book_key(&children_states[chosen_move]), best_key);
+
+        // Moves after max_book_moves are chosen by the wrapped strategy
+        let mut later_context = context.clone();
+        later_context.history.push(initial_state);
+        assert_eq!(strategy.choose_move_in_context(true, &later_context, &children_states).unwrap(), children_states.len() - 1);
+
+        // A child that is missing in the book could be better than the book moves
+        let mut incomplete_book = OpeningBook::new();
+        for child in children_states.iter().filter(|child| get_book_key(child) != get_book_key(children_states.last().unwrap())) {
+            incomplete_book.insert(child, BookEntry { evaluation: 1.0, depth: Some(2) });
+        }
+        let mut strategy = OpeningBookStrategy::new(Arc::new(incomplete_book), LastMoveStrategy, 1);
+        strategy.new_game(true, &context).unwrap();
+        assert_eq!(strategy.choose_move_in_context(true, &context, &children_states).unwrap(), children_states.len() - 1);
+    }
+}
@@ -18,9 +18,12 @@ impl<GS: GameState> RandomStrategy<GS> {
 impl<GS: GameState> Strategy for RandomStrategy<GS> {
     type GameState = GS;
 
-    fn choose_move(&self, _is_player_a: bool, _current_state: &GS, possible_next_states: &Vec<GS>) -> usize {
-        let mut rng = rand::thread_rng();
+    fn choose_move(&self, is_player_a: bool, current_state: &GS, possible_next_states: &Vec<GS>) -> usize {
+        return self.choose_move_with_rng(is_player_a, current_state, possible_next_states, &mut rand::thread_rng());
+    }
+
+    fn choose_move_with_rng<R: Rng>(&self, _is_player_a: bool, _current_state: &GS, possible_next_states: &Vec<GS>, rng: &mut R) -> usize {
         let index = rng.gen_range(0..possible_next_states.len());
         return index;
     }
-}
\ No newline at end of file
+}
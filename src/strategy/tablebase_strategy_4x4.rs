@@ -0,0 +1,296 @@
+use std::sync::Arc;
+use anyhow::{anyhow, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::game_state::{ContinuousBlockId, GameState, SimplifiedState};
+use crate::minimax::forced_win::shortest_forced_win_moves;
+use crate::precompute_state_winner::WinnerTablebase;
+use crate::strategy::game_context::GameContext;
+use crate::strategy::StatefulStrategy;
+
+/**
+Perfect play for the 4x4 game based on the presolved winners, so the tablebase has to contain every block count that can occur.
+The tablebase only stores the winner, so without distance search any winning move is chosen,
+and when losing, any move after which the opponent can't win immediately (if there is one).
+With distance search, the shortest forced win is searched for every move (up to max_distance_moves):
+The fastest winning move is chosen, and when losing, the move after which the opponent needs the most moves to win.
+ */
+pub struct TablebaseStrategy<GS: GameState + SimplifiedState + ContinuousBlockId> {
+    tablebase: Arc<WinnerTablebase<GS>>,
+    // Moves (plies) considered by the distance search, 0 disables the distance search
+    max_distance_moves: usize,
+    rng: StdRng,
+}
+
+// True if the player to move in the state can win with their next move (including moves after which the other player can't move)
+fn can_player_to_move_win_immediately<GS: GameState>(state: &GS) -> bool {
+    if state.has_player_a_won() || state.has_player_b_won() {
+        return false;
+    }
+    let player_a_turn = state.is_player_a_turn();
+    return state.get_children_states().iter().any(|child| {
+        let has_won = if player_a_turn { child.has_player_a_won() } else { child.has_player_b_won() };
+        has_won || child.get_children_states().is_empty()
+    });
+}
+
+// Moves of a losing player after which the opponent can't win immediately, all moves if every move loses immediately
+fn get_delaying_move_indices<GS: GameState>(possible_next_states: &[GS]) -> Vec<usize> {
+    let delaying_move_indices: Vec<usize> = (0..possible_next_states.len())
+        .filter(|&i| !can_player_to_move_win_immediately(&possible_next_states[i]))
+        .collect();
+    if delaying_move_indices.is_empty() {
+        return (0..possible_next_states.len()).collect();
+    }
+    return delaying_move_indices;
+}
+
+impl<GS: GameState + SimplifiedState + ContinuousBlockId> TablebaseStrategy<GS> {
+    pub fn new(tablebase: Arc<WinnerTablebase<GS>>) -> TablebaseStrategy<GS> {
+        return TablebaseStrategy {
+            tablebase,
+            max_distance_moves: 0,
+            // Reseeded for every game from the seed of the game context
+            rng: StdRng::seed_from_u64(0),
+        };
+    }
+
+    pub fn with_distance_search(mut self, max_distance_moves: usize) -> Self {
+        self.max_distance_moves = max_distance_moves;
+        return self;
+    }
+
+    // Moves (plies) the given player needs to win from the state, usize::MAX if no forced win is found within max_distance_moves
+    fn get_moves_to_win(&self, state: &GS, player_a_wins: bool) -> usize {
+        if state.has_player_a_won() || state.has_player_b_won() {
+            return 0;
+        }
+        let known_winner = |state: &GS| self.tablebase.get_winner(state);
+        return shortest_forced_win_moves(state, player_a_wins, self.max_distance_moves - 1, &known_winner).unwrap_or(usize::MAX);
+    }
+
+    fn choose_randomly(&mut self, move_indices: &[usize]) -> usize {
+        return move_indices[self.rng.gen_range(0..move_indices.len())];
+    }
+
+    // Chooses randomly between the moves with the best distance, a lower distance is better if minimize is true
+    fn choose_by_distance(&mut self, move_indices: &[usize], possible_next_states: &[GS], player_a_wins: bool, minimize: bool) -> usize {
+        let distances: Vec<usize> = move_indices.iter().map(|&i| self.get_moves_to_win(&possible_next_states[i], player_a_wins)).collect();
+        let best_distance = if minimize { *distances.iter().min().unwrap() } else { *distances.iter().max().unwrap() };
+        let best_move_indices: Vec<usize> = move_indices.iter().zip(distances.iter())
+            .filter(|(_, &distance)| distance == best_distance)
+            .map(|(&i, _)| i)
+            .collect();
+        return self.choose_randomly(&best_move_indices);
+    }
+}
+
+impl<GS: GameState + SimplifiedState + ContinuousBlockId> StatefulStrategy for TablebaseStrategy<GS> {
+    type GameState = GS;
+
+    fn new_game(&mut self, is_player_a: bool, context: &GameContext<GS>) -> Result<()> {
+        self.rng = StdRng::seed_from_u64(context.get_strategy_seed(is_player_a));
+        return Ok(());
+    }
+
+    fn choose_move_in_context(&mut self, is_player_a: bool, _context: &GameContext<GS>, possible_next_states: &Vec<GS>) -> Result<usize> {
+        let mut winning_move_indices = Vec::new();
+        for (i, game_state) in possible_next_states.iter().enumerate() {
+            let player_a_wins = self.tablebase.get_winner(game_state)
+                .ok_or_else(|| anyhow!("Block count {} is not contained in the tablebase", game_state.get_block_count()))?;
+            if player_a_wins == is_player_a {
+                winning_move_indices.push(i);
+            }
+        }
+
+        if !winning_move_indices.is_empty() {
+            // Immediate wins don't need a distance search
+            let immediate_win_indices: Vec<usize> = winning_move_indices.iter().copied()
+                .filter(|&i| if is_player_a { possible_next_states[i].has_player_a_won() } else { possible_next_states[i].has_player_b_won() })
+                .collect();
+            if !immediate_win_indices.is_empty() {
+                return Ok(self.choose_randomly(&immediate_win_indices));
+            }
+            if self.max_distance_moves == 0 {
+                return Ok(self.choose_randomly(&winning_move_indices));
+            }
+            return Ok(self.choose_by_distance(&winning_move_indices, possible_next_states, is_player_a, true));
+        }
+
+        if self.max_distance_moves == 0 {
+            return Ok(self.choose_randomly(&get_delaying_move_indices(possible_next_states)));
+        }
+        let all_move_indices: Vec<usize> = (0..possible_next_states.len()).collect();
+        return Ok(self.choose_by_distance(&all_move_indices, possible_next_states, !is_player_a, false));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::RangeInclusive;
+    use std::sync::Arc;
+    use fnv::{FnvHashMap, FnvHashSet};
+    use rand::Rng;
+    use crate::game_state::{ContinuousBlockId, GameState, SimplifiedState};
+    use crate::game_state::game_state_4x4_binary_3bit::GameState4x4Binary3Bit;
+    use crate::generic_game_state::generic_santorini_game_state::GenericSantoriniGameState;
+    use crate::generic_game_state::GenericGameState;
+    use crate::minimax::forced_win::{no_known_winner, shortest_forced_win_moves};
+    use crate::minimax::infinite_depth_minimax;
+    use crate::precompute_state_winner::bit_vector::BitVector;
+    use crate::precompute_state_winner::WinnerTablebase;
+    use crate::strategy::game_context::GameContext;
+    use crate::strategy::StatefulStrategy;
+    use crate::strategy::tablebase_strategy_4x4::{can_player_to_move_win_immediately, get_delaying_move_indices, TablebaseStrategy};
+
+    type GS = GameState4x4Binary3Bit;
+
+    // Block counts of the tested positions, every move builds a block, so no game from them lasts longer than MAX_MOVES
+    const POSITION_BLOCK_COUNTS: RangeInclusive<usize> = 46..=48;
+    const MAX_MOVES: usize = 19;
+
+    fn get_moves_to_win(state: &GS, player_a_wins: bool) -> Option<usize> {
+        return shortest_forced_win_moves(state, player_a_wins, MAX_MOVES, &no_known_winner);
+    }
+
+    fn collect_reachable_states(state: &GS, reachable_states: &mut FnvHashSet<GS>) {
+        if state.has_player_a_won() || state.has_player_b_won() || !reachable_states.insert(state.get_simplified_state()) {
+            return;
+        }
+        for child in state.get_children_states() {
+            collect_reachable_states(&child, reachable_states);
+        }
+    }
+
+    /*
+    Solves every state that is reachable from the positions, a set bit means that player B wins like in the presolved files.
+    The other states are never looked up, so the bit vectors only have to reach the highest continuous block id of the reachable states.
+     */
+    fn solve_tablebase(positions: &[GS], solved_cache: &mut FnvHashMap<GS, bool>) -> WinnerTablebase<GS> {
+        let mut reachable_states = FnvHashSet::default();
+        for position in positions {
+            collect_reachable_states(position, &mut reachable_states);
+        }
+
+        let block_counts = reachable_states.iter().map(|state| state.get_block_count() as isize).min().unwrap()..=64;
+        let mut bit_vector_data: Vec<Vec<u8>> = block_counts.clone().map(|_| Vec::new()).collect();
+        for state in reachable_states {
+            let data = &mut bit_vector_data[(state.get_block_count() as isize - block_counts.start()) as usize];
+            let continuous_block_id = state.get_continuous_block_id() as usize;
+            if data.len() <= continuous_block_id / 8 {
+                data.resize(continuous_block_id / 8 + 1, 0);
+            }
+            if !infinite_depth_minimax(state, solved_cache) {
+                data[continuous_block_id / 8] |= 1 << (continuous_block_id % 8);
+            }
+        }
+        return WinnerTablebase::from_bit_vectors(block_counts, bit_vector_data.into_iter().map(BitVector::from_data).collect());
+    }
+
+    // Positions in which the distance search has to choose between moves with different distances
+    fn find_positions(is_winning: bool, count: usize, solved_cache: &mut FnvHashMap<GS, bool>) -> Vec<GS> {
+        let mut rng = rand::thread_rng();
+        let mut positions = Vec::new();
+        while positions.len() < count {
+            let block_count = rng.gen_range(POSITION_BLOCK_COUNTS) as isize;
+            let state = GS::from_continuous_block_id(block_count, rng.gen_range(0..GS::get_continuous_block_id_count(block_count)));
+            if state.get_children_states().is_empty() || (infinite_depth_minimax(state, solved_cache) == state.is_player_a_turn()) != is_winning {
+                continue;
+            }
+            let winner = state.is_player_a_turn() == is_winning;
+            let distances: Vec<usize> = state.get_children_states().iter().filter_map(|child| get_moves_to_win(child, winner)).collect();
+            let has_immediate_win = is_winning && distances.contains(&0);
+            if !has_immediate_win && distances.iter().min() != distances.iter().max() {
+                positions.push(state);
+            }
+        }
+        return positions;
+    }
+
+    fn choose_state(strategy: &mut TablebaseStrategy<GS>, state: &GS) -> GS {
+        let context = GameContext::new(*state);
+        let possible_next_states = state.get_children_states();
+        strategy.new_game(state.is_player_a_turn(), &context).unwrap();
+        let move_index = strategy.choose_move_in_context(state.is_player_a_turn(), &context, &possible_next_states).unwrap();
+        return possible_next_states[move_index];
+    }
+
+    #[test]
+    fn test_choose_move_picks_winning_moves() {
+        let mut solved_cache = FnvHashMap::default();
+        let positions = find_positions(true, 10, &mut solved_cache);
+        let tablebase = Arc::new(solve_tablebase(&positions, &mut solved_cache));
+        let mut strategy = TablebaseStrategy::new(tablebase.clone());
+        let mut distance_strategy = TablebaseStrategy::new(tablebase.clone()).with_distance_search(MAX_MOVES);
+
+        for position in positions {
+            let is_player_a = position.is_player_a_turn();
+            assert_eq!(tablebase.get_winner(&position), Some(is_player_a));
+
+            let chosen_state = choose_state(&mut strategy, &position);
+            assert_eq!(infinite_depth_minimax(chosen_state, &mut solved_cache), is_player_a);
+
+            // With distance search, the fastest win is chosen
+            let fastest_win = position.get_children_states().iter().filter_map(|child| get_moves_to_win(child, is_player_a)).min();
+            let distance_chosen_state = choose_state(&mut distance_strategy, &position);
+            assert_eq!(get_moves_to_win(&distance_chosen_state, is_player_a), fastest_win);
+        }
+    }
+
+    #[test]
+    fn test_choose_move_resists_longest_when_losing() {
+        let mut solved_cache = FnvHashMap::default();
+        let positions = find_positions(false, 10, &mut solved_cache);
+        let tablebase = Arc::new(solve_tablebase(&positions, &mut solved_cache));
+        let mut strategy = TablebaseStrategy::new(tablebase.clone());
+        let mut distance_strategy = TablebaseStrategy::new(tablebase.clone()).with_distance_search(MAX_MOVES);
+
+        for position in positions {
+            let is_player_a = position.is_player_a_turn();
+            assert_eq!(tablebase.get_winner(&position), Some(!is_player_a));
+            let possible_next_states = position.get_children_states();
+
+            // Without distance search, moves that let the opponent win immediately are avoided if possible
+            let can_delay = possible_next_states.iter().any(|child| !can_player_to_move_win_immediately(child));
+            let chosen_state = choose_state(&mut strategy, &position);
+            assert!(!can_delay || !can_player_to_move_win_immediately(&chosen_state));
+
+            // With distance search, the move after which the opponent needs the most moves to win is chosen
+            let slowest_loss = possible_next_states.iter().map(|child| get_moves_to_win(child, !is_player_a).unwrap()).max();
+            let distance_chosen_state = choose_state(&mut distance_strategy, &position);
+            assert_eq!(get_moves_to_win(&distance_chosen_state, !is_player_a), slowest_loss);
+        }
+    }
+
+    #[test]
+    fn test_losing_player_avoids_moves_that_let_the_opponent_win_immediately() {
+        let mut rng = rand::thread_rng();
+        let mut solved_cache = FnvHashMap::default();
+        let mut filtered_positions = 0;
+        for _ in 0..300 {
+            let block_count = rng.gen_range(40..=55);
+            let generic_state = GenericSantoriniGameState::<4, 4, 1>::generate_random_state_with_blocks_rng(&mut rng, block_count);
+            let state = GameState4x4Binary3Bit::from_generic_game_state(&generic_state);
+            if state.has_player_a_won() || state.has_player_b_won() {
+                continue;
+            }
+            // Only positions that are lost for the player to move, like in choose_move_in_context without winning moves
+            let possible_next_states = state.get_children_states();
+            if possible_next_states.is_empty() || infinite_depth_minimax(state, &mut solved_cache) == state.is_player_a_turn() {
+                continue;
+            }
+
+            let delaying_move_indices = get_delaying_move_indices(&possible_next_states);
+            let can_delay = possible_next_states.iter().any(|child| !can_player_to_move_win_immediately(child));
+            if can_delay {
+                assert!(delaying_move_indices.iter().all(|&i| !can_player_to_move_win_immediately(&possible_next_states[i])), "{}", generic_state.to_position_string());
+            } else {
+                assert_eq!(delaying_move_indices.len(), possible_next_states.len());
+            }
+            if delaying_move_indices.len() < possible_next_states.len() {
+                filtered_positions += 1;
+            }
+        }
+        assert!(filtered_positions > 0);
+    }
+}
@@ -0,0 +1,44 @@
+use std::time::Duration;
+use santorini_minimax::game_state::game_state_4x4_binary_3bit::GameState4x4Binary3Bit;
+use santorini_minimax::game_state::game_state_5x5_binary_composite::GameState5x5BinaryComposite;
+use santorini_minimax::game_state::{get_initial_setup_state, GameState};
+use santorini_minimax::play_game::{play_game, play_game_with_time_control, GameEndReason};
+use santorini_minimax::strategy::external_engine_strategy::ExternalEngineStrategy;
+use santorini_minimax::strategy::game_context::TimeControl;
+use santorini_minimax::strategy::random_strategy::RandomStrategy;
+
+const ENGINE_PATH: &str = env!("CARGO_BIN_EXE_santorini_engine");
+
+fn assert_regular_game_end(end_reason: &GameEndReason) {
+    assert!(matches!(end_reason, GameEndReason::ReachedHeightThree | GameEndReason::NoPossibleMoves), "{:?}", end_reason);
+}
+
+#[test]
+fn test_engine_plays_a_full_game_against_random_moves() {
+    let mut engine_strategy = ExternalEngineStrategy::<GameState4x4Binary3Bit>::new(ENGINE_PATH, &["4x4"], 3).unwrap();
+    assert!(engine_strategy.get_engine_id().contains(&"name santorini-minimax".to_string()));
+    assert!(engine_strategy.get_engine_id().contains(&"board 4x4 workers 1".to_string()));
+
+    let mut random_strategy = RandomStrategy::new();
+    let result = play_game(&mut engine_strategy, &mut random_strategy, get_initial_setup_state::<GameState4x4Binary3Bit>());
+    assert_regular_game_end(&result.end_reason);
+    assert!(engine_strategy.get_last_info().is_some_and(|info| info.starts_with("info depth ")));
+
+    // The engine is reset for the next game
+    let result = play_game(&mut random_strategy, &mut engine_strategy, get_initial_setup_state::<GameState4x4Binary3Bit>());
+    assert_regular_game_end(&result.end_reason);
+}
+
+#[test]
+fn test_engine_keeps_the_time_control() {
+    let mut engine_strategy = ExternalEngineStrategy::<GameState5x5BinaryComposite>::new(ENGINE_PATH, &["5x5"], 3).unwrap();
+    let mut random_strategy = RandomStrategy::new();
+    let result = play_game_with_time_control(&mut engine_strategy, &mut random_strategy, get_initial_setup_state::<GameState5x5BinaryComposite>(), TimeControl::FixedPerMove(Duration::from_millis(500)));
+    assert_regular_game_end(&result.end_reason);
+    assert!(result.context.history.len() > 1 && !result.context.get_initial_state().has_player_a_won());
+}
+
+#[test]
+fn test_engine_for_another_board_is_rejected() {
+    assert!(ExternalEngineStrategy::<GameState4x4Binary3Bit>::new(ENGINE_PATH, &["5x5"], 3).is_err());
+}